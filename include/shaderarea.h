@@ -5,49 +5,978 @@
 
 G_BEGIN_DECLS
 
+/**
+ * GtkGlShadersShaderStage:
+ * @GTK_GL_SHADERS_SHADER_STAGE_VERTEX: the vertex shader
+ * @GTK_GL_SHADERS_SHADER_STAGE_FRAGMENT: the fragment shader
+ * @GTK_GL_SHADERS_SHADER_STAGE_LINK: linking of the shader program
+ * @GTK_GL_SHADERS_SHADER_STAGE_COMPUTE: a compute shader
+ *
+ * The stage of the shader pipeline a compile error comes from.
+ */
+typedef enum {
+  GTK_GL_SHADERS_SHADER_STAGE_VERTEX,
+  GTK_GL_SHADERS_SHADER_STAGE_FRAGMENT,
+  GTK_GL_SHADERS_SHADER_STAGE_LINK,
+  GTK_GL_SHADERS_SHADER_STAGE_COMPUTE,
+} GtkGlShadersShaderStage;
+
+GType gtk_gl_shaders_shader_stage_get_type(void);
+
+/**
+ * GtkGlShadersShaderDialect:
+ * @GTK_GL_SHADERS_SHADER_DIALECT_GLSL: GLSL with a `main` function writing to
+ *   an `out vec4`; legacy sources writing `gl_FragColor` are detected and
+ *   handled like %GTK_GL_SHADERS_SHADER_DIALECT_LEGACY ones
+ * @GTK_GL_SHADERS_SHADER_DIALECT_SHADERTOY: Shadertoy code defining
+ *   `mainImage(out vec4, in vec2)`
+ * @GTK_GL_SHADERS_SHADER_DIALECT_ISF: Interactive Shader Format: a JSON
+ *   header followed by code writing `gl_FragColor`
+ * @GTK_GL_SHADERS_SHADER_DIALECT_TRANSITION: a gl-transitions transition
+ *   defining `vec4 transition(vec2 uv)`
+ * @GTK_GL_SHADERS_SHADER_DIALECT_LEGACY: legacy GLSL (ES 1.00, glslCanvas)
+ *   writing `gl_FragColor`
+ *
+ * The dialect shader sources are written in.
+ */
+typedef enum {
+  GTK_GL_SHADERS_SHADER_DIALECT_GLSL,
+  GTK_GL_SHADERS_SHADER_DIALECT_SHADERTOY,
+  GTK_GL_SHADERS_SHADER_DIALECT_ISF,
+  GTK_GL_SHADERS_SHADER_DIALECT_TRANSITION,
+  GTK_GL_SHADERS_SHADER_DIALECT_LEGACY,
+} GtkGlShadersShaderDialect;
+
+GType gtk_gl_shaders_shader_dialect_get_type(void);
+
+/**
+ * GtkGlShadersShaderError:
+ * @GTK_GL_SHADERS_SHADER_ERROR_COMPILE: a shader stage failed to compile
+ * @GTK_GL_SHADERS_SHADER_ERROR_LINK: the shader program failed to link
+ * @GTK_GL_SHADERS_SHADER_ERROR_CONTEXT: no OpenGL context was available to
+ *   compile the shader
+ * @GTK_GL_SHADERS_SHADER_ERROR_PASS: a render or compute pass, or a storage
+ *   object, has an invalid name or size
+ * @GTK_GL_SHADERS_SHADER_ERROR_PARSE: a shader description, such as a
+ *   Shadertoy export, is malformed
+ * @GTK_GL_SHADERS_SHADER_ERROR_UNSUPPORTED: the OpenGL context lacks a
+ *   feature, such as compute shaders
+ * @GTK_GL_SHADERS_SHADER_ERROR_UNIFORM: a uniform value is malformed
+ *
+ * Error codes for the `GTK_GL_SHADERS_SHADER_ERROR` error domain.
+ */
+typedef enum {
+  GTK_GL_SHADERS_SHADER_ERROR_COMPILE,
+  GTK_GL_SHADERS_SHADER_ERROR_LINK,
+  GTK_GL_SHADERS_SHADER_ERROR_CONTEXT,
+  GTK_GL_SHADERS_SHADER_ERROR_PASS,
+  GTK_GL_SHADERS_SHADER_ERROR_PARSE,
+  GTK_GL_SHADERS_SHADER_ERROR_UNSUPPORTED,
+  GTK_GL_SHADERS_SHADER_ERROR_UNIFORM,
+} GtkGlShadersShaderError;
+
+#define GTK_GL_SHADERS_SHADER_ERROR (gtk_gl_shaders_shader_error_quark())
+GQuark gtk_gl_shaders_shader_error_quark(void);
+
+/**
+ * GtkGlShadersPassFormat:
+ * @GTK_GL_SHADERS_PASS_FORMAT_RGBA8: 8-bit normalized channels
+ * @GTK_GL_SHADERS_PASS_FORMAT_RGBA16_F: 16-bit floating point channels
+ * @GTK_GL_SHADERS_PASS_FORMAT_RGBA32_F: 32-bit floating point channels
+ *
+ * Pixel format of the output texture of a render pass.
+ */
+typedef enum {
+  GTK_GL_SHADERS_PASS_FORMAT_RGBA8,
+  GTK_GL_SHADERS_PASS_FORMAT_RGBA16_F,
+  GTK_GL_SHADERS_PASS_FORMAT_RGBA32_F,
+} GtkGlShadersPassFormat;
+
+GType gtk_gl_shaders_pass_format_get_type(void);
+
+/**
+ * GtkGlShadersPassFilter:
+ * @GTK_GL_SHADERS_PASS_FILTER_LINEAR: linear interpolation between texels
+ * @GTK_GL_SHADERS_PASS_FILTER_NEAREST: nearest texel
+ *
+ * Filtering used when sampling the output texture of a render pass.
+ */
+typedef enum {
+  GTK_GL_SHADERS_PASS_FILTER_LINEAR,
+  GTK_GL_SHADERS_PASS_FILTER_NEAREST,
+} GtkGlShadersPassFilter;
+
+GType gtk_gl_shaders_pass_filter_get_type(void);
+
+/**
+ * GtkGlShadersEasing:
+ * @GTK_GL_SHADERS_EASING_LINEAR: constant speed
+ * @GTK_GL_SHADERS_EASING_EASE_IN: starts slowly and accelerates (cubic)
+ * @GTK_GL_SHADERS_EASING_EASE_OUT: starts quickly and decelerates (cubic)
+ * @GTK_GL_SHADERS_EASING_EASE_IN_OUT: accelerates, then decelerates (cubic)
+ *
+ * Easing curve applied to the progress of a transition.
+ */
+typedef enum {
+  GTK_GL_SHADERS_EASING_LINEAR,
+  GTK_GL_SHADERS_EASING_EASE_IN,
+  GTK_GL_SHADERS_EASING_EASE_OUT,
+  GTK_GL_SHADERS_EASING_EASE_IN_OUT,
+} GtkGlShadersEasing;
+
+GType gtk_gl_shaders_easing_get_type(void);
+
+/**
+ * GtkGlShadersPrimitive:
+ * @GTK_GL_SHADERS_PRIMITIVE_POINTS: separate points, sized by `gl_PointSize`
+ * @GTK_GL_SHADERS_PRIMITIVE_LINES: separate lines, from pairs of vertices
+ * @GTK_GL_SHADERS_PRIMITIVE_LINE_STRIP: connected lines
+ * @GTK_GL_SHADERS_PRIMITIVE_LINE_LOOP: a closed loop of connected lines
+ * @GTK_GL_SHADERS_PRIMITIVE_TRIANGLES: separate triangles, from triples of
+ *   vertices
+ * @GTK_GL_SHADERS_PRIMITIVE_TRIANGLE_STRIP: connected triangles, as used by
+ *   the fullscreen quad
+ * @GTK_GL_SHADERS_PRIMITIVE_TRIANGLE_FAN: triangles sharing the first vertex
+ *
+ * The primitive type vertices are assembled into.
+ */
+typedef enum {
+  GTK_GL_SHADERS_PRIMITIVE_POINTS,
+  GTK_GL_SHADERS_PRIMITIVE_LINES,
+  GTK_GL_SHADERS_PRIMITIVE_LINE_STRIP,
+  GTK_GL_SHADERS_PRIMITIVE_LINE_LOOP,
+  GTK_GL_SHADERS_PRIMITIVE_TRIANGLES,
+  GTK_GL_SHADERS_PRIMITIVE_TRIANGLE_STRIP,
+  GTK_GL_SHADERS_PRIMITIVE_TRIANGLE_FAN,
+} GtkGlShadersPrimitive;
+
+GType gtk_gl_shaders_primitive_get_type(void);
+
+/**
+ * GtkGlShadersBlendMode:
+ * @GTK_GL_SHADERS_BLEND_MODE_NONE: sprites replace what is below them
+ * @GTK_GL_SHADERS_BLEND_MODE_ALPHA: sprites are blended by their alpha
+ * @GTK_GL_SHADERS_BLEND_MODE_PREMULTIPLIED: sprites have premultiplied alpha
+ * @GTK_GL_SHADERS_BLEND_MODE_ADDITIVE: sprites add to what is below them, for
+ *   glowing effects
+ *
+ * How particle sprites are blended over the widget's shader.
+ */
+typedef enum {
+  GTK_GL_SHADERS_BLEND_MODE_NONE,
+  GTK_GL_SHADERS_BLEND_MODE_ALPHA,
+  GTK_GL_SHADERS_BLEND_MODE_PREMULTIPLIED,
+  GTK_GL_SHADERS_BLEND_MODE_ADDITIVE,
+} GtkGlShadersBlendMode;
+
+GType gtk_gl_shaders_blend_mode_get_type(void);
+
+/**
+ * GtkGlShadersComputeSchedule:
+ * @GTK_GL_SHADERS_COMPUTE_SCHEDULE_EVERY_FRAME: on every frame the widget
+ *   renders
+ * @GTK_GL_SHADERS_COMPUTE_SCHEDULE_ON_DEMAND: once on the next frame, for
+ *   every call to gtk_gl_shaders_shader_area_run_compute()
+ *
+ * When a compute pass is dispatched.
+ */
+typedef enum {
+  GTK_GL_SHADERS_COMPUTE_SCHEDULE_EVERY_FRAME,
+  GTK_GL_SHADERS_COMPUTE_SCHEDULE_ON_DEMAND,
+} GtkGlShadersComputeSchedule;
+
+GType gtk_gl_shaders_compute_schedule_get_type(void);
+
+/**
+ * GtkGlShadersUniformType:
+ * @GTK_GL_SHADERS_UNIFORM_TYPE_FLOAT: `float`
+ * @GTK_GL_SHADERS_UNIFORM_TYPE_VEC2: `vec2`
+ * @GTK_GL_SHADERS_UNIFORM_TYPE_VEC3: `vec3`
+ * @GTK_GL_SHADERS_UNIFORM_TYPE_VEC4: `vec4`
+ * @GTK_GL_SHADERS_UNIFORM_TYPE_INT: `int`
+ * @GTK_GL_SHADERS_UNIFORM_TYPE_IVEC2: `ivec2`
+ * @GTK_GL_SHADERS_UNIFORM_TYPE_IVEC3: `ivec3`
+ * @GTK_GL_SHADERS_UNIFORM_TYPE_IVEC4: `ivec4`
+ *
+ * The element type of a uniform array.
+ */
+typedef enum {
+  GTK_GL_SHADERS_UNIFORM_TYPE_FLOAT,
+  GTK_GL_SHADERS_UNIFORM_TYPE_VEC2,
+  GTK_GL_SHADERS_UNIFORM_TYPE_VEC3,
+  GTK_GL_SHADERS_UNIFORM_TYPE_VEC4,
+  GTK_GL_SHADERS_UNIFORM_TYPE_INT,
+  GTK_GL_SHADERS_UNIFORM_TYPE_IVEC2,
+  GTK_GL_SHADERS_UNIFORM_TYPE_IVEC3,
+  GTK_GL_SHADERS_UNIFORM_TYPE_IVEC4,
+} GtkGlShadersUniformType;
+
+GType gtk_gl_shaders_uniform_type_get_type(void);
+
+/**
+ * GtkGlShadersUniformSource:
+ * @GTK_GL_SHADERS_UNIFORM_SOURCE_UNSET: nobody sets the uniform, which keeps
+ *   its default value
+ * @GTK_GL_SHADERS_UNIFORM_SOURCE_USER: the user sets the uniform, with a
+ *   setter or the initial uniforms
+ * @GTK_GL_SHADERS_UNIFORM_SOURCE_WIDGET: the widget sets the uniform, as a
+ *   builtin or a sampler bound to a texture unit
+ *
+ * Who supplies the value of a uniform listed by
+ * gtk_gl_shaders_shader_area_list_uniforms().
+ */
+typedef enum {
+  GTK_GL_SHADERS_UNIFORM_SOURCE_UNSET,
+  GTK_GL_SHADERS_UNIFORM_SOURCE_USER,
+  GTK_GL_SHADERS_UNIFORM_SOURCE_WIDGET,
+} GtkGlShadersUniformSource;
+
+GType gtk_gl_shaders_uniform_source_get_type(void);
+
 /**
  * GtkGlShadersShaderArea:
  *
- * A widget that renders custom GLSL fragment shaders.
+ * A widget that renders custom GLSL fragment shaders.
+ *
+ * `GtkGlShadersShaderArea` is a subclass of `GtkGLArea` that executes a
+ * user-provided fragment shader on a fullscreen quad. It supports loading
+ * textures and setting uniform values.
+ *
+ * As a `GtkGLArea` subclass, it inherits all GLArea functionality including
+ * OpenGL context management and can be used anywhere a GTK4 widget is expected.
+ *
+ * # Shader inputs
+ *
+ * - `uv` - A `vec2` from (0,0) at bottom-left to (1,1) at top-right
+ * - `tex0`, `tex1`, ... - Sampler uniforms for loaded textures
+ * - Custom uniforms - Set via the `gtk_gl_shaders_shader_area_set_uniform_*` functions
+ * - Builtin uniforms - `time`, `time_delta`, `frame`, `resolution`,
+ *   `scale_factor`, `date` and `texN_size`, filled automatically when declared
+ */
+G_DECLARE_FINAL_TYPE(GtkGlShadersShaderArea, gtk_gl_shaders_shader_area,
+                     GTK_GL_SHADERS, SHADER_AREA, GtkGLArea)
+
+/**
+ * GtkGlShadersShaderArea:animate:
+ *
+ * Whether the widget renders on every frame of the frame clock.
+ *
+ * The animation pauses automatically while the widget is unmapped or
+ * insensitive, and while its window is in the backdrop or suspended.
+ */
+
+/**
+ * GtkGlShadersShaderArea:max-fps:
+ *
+ * Maximum number of frames per second while animating, or 0 for no limit.
+ */
+
+/**
+ * GtkGlShadersShaderArea:on-demand:
+ *
+ * Whether the widget only renders when a uniform changes.
+ *
+ * Setting a uniform to the value it already has doesn't trigger a render.
+ */
+
+/**
+ * GtkGlShadersShaderArea:pointer-input:
+ *
+ * Whether pointer and touch events update the input builtin uniforms.
+ *
+ * When enabled, the widget installs its own motion, click, drag and touch
+ * event controllers and fills `pointer`, `pointer_uv`, `pointer_buttons`,
+ * `click`, `drag_delta`, `mouse` (Shadertoy's `iMouse`), `touches` and
+ * `touch_count` when the shader declares them.
+ */
+
+/**
+ * GtkGlShadersShaderArea:dialect:
+ *
+ * The dialect of the shader sources, including those of render passes.
+ *
+ * In the Shadertoy dialect, each shader is wrapped in a `main` function
+ * calling `mainImage`, and gets Shadertoy's inputs: `iResolution`, `iTime`,
+ * `iTimeDelta`, `iFrameRate`, `iFrame`, `iMouse`, `iDate`, `iChannel0` to
+ * `iChannel3` and `iChannelResolution`. Channels are bound with
+ * gtk_gl_shaders_shader_area_set_channel().
+ *
+ * In the ISF dialect, each shader starts with a JSON header whose inputs are
+ * declared as uniforms, and gets ISF's `gl_FragColor`, `isf_FragNormCoord`,
+ * `IMG_PIXEL()`, `IMG_NORM_PIXEL()`, `IMG_THIS_PIXEL()`, `IMG_SIZE()`,
+ * `TIME`, `TIMEDELTA`, `FRAMEINDEX`, `RENDERSIZE`, `DATE` and `PASSINDEX`.
+ *
+ * In the transition dialect, the shader defines `vec4 transition(vec2 uv)`
+ * and gets gl-transitions' `getFromColor()` and `getToColor()`, reading
+ * `tex0` and `tex1`, along with `progress` and `ratio`.
+ *
+ * In the legacy dialect, `gl_FragColor`, `gl_FragData`, `varying`,
+ * `texture2D` and `textureCube` are mapped onto their GLSL 3.30 / ES 3.00
+ * equivalents, `v_texcoord` onto `uv`, and glslCanvas' `u_tex0` and
+ * `u_tex0Resolution` onto `tex0` and `tex0_size`. A sampler named `texture`
+ * is renamed `legacy_texture`. The default GLSL dialect
+ * does the same for sources that use any of these.
+ *
+ * Changing the dialect recompiles the shaders.
+ */
+
+/**
+ * GtkGlShadersShaderArea:keyboard-input:
+ *
+ * Whether key events update the keyboard texture.
+ *
+ * When enabled, the widget becomes focusable, grabs the focus when clicked,
+ * and binds a 256×3 texture to the `keyboard` sampler, next to the `texN`
+ * textures. Texels are indexed by JavaScript key code: row 0 holds keys that
+ * are down, row 1 keys pressed since the last frame, and row 2 keys toggled by
+ * every press, like Shadertoy's keyboard texture.
+ */
+
+/**
+ * GtkGlShadersShaderArea:view-controls:
+ *
+ * Whether scroll, drag, pinch and rotate gestures drive the view transform.
+ *
+ * The transform is passed to the `view` (`vec4`: center, zoom and rotation)
+ * and `view_matrix` (`mat3`) uniforms. `view_matrix * vec3(uv, 1.0)` gives the
+ * view coordinates of a fragment; it is the identity when the view is reset.
+ */
+
+/**
+ * GtkGlShadersShaderArea:min-zoom:
+ *
+ * Minimum zoom factor of the view controls, or 0 for no limit. Raising it
+ * above the current zoom zooms the view in.
+ */
+
+/**
+ * GtkGlShadersShaderArea:max-zoom:
+ *
+ * Maximum zoom factor of the view controls, or 0 for no limit. Lowering it
+ * below the current zoom zooms the view out.
+ */
+
+/**
+ * GtkGlShadersShaderArea:inertia:
+ *
+ * Whether the view keeps moving and slows down after a drag is released.
+ */
+
+/**
+ * GtkGlShadersShaderArea:primitive:
+ *
+ * The primitive type the vertices of the widget's shader are drawn as.
+ *
+ * Together with #GtkGlShadersShaderArea:vertex-count, this only applies to
+ * custom vertex shaders; without one, the fullscreen quad is always drawn.
+ */
+
+/**
+ * GtkGlShadersShaderArea:vertex-count:
+ *
+ * The number of vertices drawn by a custom vertex shader, which reads it as
+ * `vertexCount`. Ignored without one. At most `G_MAXINT`.
+ */
+
+/**
+ * GtkGlShadersShaderArea:particle-count:
+ *
+ * The number of particles of the particle system, 1000 by default and at
+ * most `G_MAXINT`. Changing it resets every particle.
+ */
+
+/**
+ * GtkGlShadersShaderArea:particle-blend:
+ *
+ * How particle sprites are blended over the widget's shader.
+ */
+
+/**
+ * GtkGlShadersShaderArea:compute-supported:
+ *
+ * Whether the OpenGL context supports compute shaders and storage objects,
+ * which need OpenGL 4.3. It is checked when the widget is realized, and is
+ * %FALSE before that.
+ */
+
+/**
+ * GtkGlShadersShaderArea:fp64-supported:
+ *
+ * Whether the OpenGL context supports `double` and `dvecN` uniforms, which
+ * need OpenGL 4.0 or the `GL_ARB_gpu_shader_fp64` extension. It is checked
+ * when the widget is realized, and is %FALSE before that.
+ */
+
+/**
+ * GtkGlShadersShaderArea:orbit-camera:
+ *
+ * Whether pointer drags orbit the camera around the mesh and scrolling moves
+ * it closer or away.
+ *
+ * The camera is passed to the `camera_matrix` and `projection_matrix`
+ * (`mat4`) and `camera_position` (`vec3`) uniforms, which stay at their
+ * initial values while this is disabled.
+ */
+
+/**
+ * GtkGlShadersShaderArea:transition-duration:
+ *
+ * Duration of a transition in seconds.
+ */
+
+/**
+ * GtkGlShadersShaderArea:transition-easing:
+ *
+ * Easing curve applied to the `progress` uniform of a transition.
+ */
+
+/**
+ * GtkGlShadersShaderArea::finished:
+ * @this: the shader area widget
+ *
+ * Emitted when a transition reaches its second image, before the transition
+ * to the next queued image starts.
+ */
+
+/**
+ * GtkGlShadersShaderArea::uniforms-changed:
+ * @this: the shader area widget
+ *
+ * Emitted after the shaders are linked, e.g. when the widget is realized or
+ * a shader or pass is replaced, since the uniforms listed by
+ * gtk_gl_shaders_shader_area_list_uniforms() may have changed.
+ */
+
+/**
+ * GtkGlShadersShaderArea::compile-error:
+ * @this: the shader area widget
+ * @stage: the stage that failed
+ * @log: the raw info log returned by the driver
+ * @messages: the parsed messages, as a `GVariant` of type `a(iis)`
+ * @pass: the name of the render or compute pass, `particles` for the
+ *   particle shaders, or an empty string for the widget's own shader
+ *
+ * Emitted when the shader or the shader of a render pass fails to compile or
+ * link.
+ *
+ * Each entry of @messages is `(line, column, message)`. Line numbers are
+ * relative to the source passed by the user, and are `-1` when unknown, as
+ * are columns.
+ */
+
+/**
+ * gtk_gl_shaders_shader_area_new:
+ * @shader: (not nullable): GLSL fragment shader source code
+ * @textures: (array length=textures_count) (nullable): paths to image files to load as textures
+ * @textures_count: number of texture paths in the @textures array
+ * @uniforms: (nullable): initial uniform values as a `GVariant` dictionary
+ * Returns: (transfer full) (not nullable): a new `GtkGlShadersShaderArea` widget
+ *
+ * Creates a new shader widget with the given fragment shader, textures, and uniforms.
+ *
+ * The fragment shader should declare `in vec2 uv` for texture coordinates and
+ * `out vec4 out_color` for the output color. Textures are accessible as
+ * `uniform sampler2D tex0`, `tex1`, etc.
+ *
+ * Uniforms should be passed as a `GVariant` of type `a{sv}` (dictionary of
+ * string to variant). Float values use type 'd' (double), and vector values
+ * use type 'ad' (array of doubles).
+ */
+GtkGlShadersShaderArea *
+gtk_gl_shaders_shader_area_new(const char *shader, const char **textures,
+                               unsigned int textures_count,
+                               const GVariant *uniforms);
+
+/**
+ * gtk_gl_shaders_shader_area_new_checked:
+ * @shader: (not nullable): GLSL fragment shader source code
+ * @textures: (array length=textures_count) (nullable): paths to image files to load as textures
+ * @textures_count: number of texture paths in the @textures array
+ * @uniforms: (nullable): initial uniform values as a `GVariant` dictionary
+ * @error: return location for a #GError
+ * Returns: (transfer full) (nullable): a new `GtkGlShadersShaderArea` widget,
+ *   or %NULL if the shader failed to compile
+ *
+ * Creates a new shader widget like gtk_gl_shaders_shader_area_new(), but
+ * checks that the shader compiles and links first.
+ *
+ * The check uses a temporary OpenGL context from the default display, and
+ * leaves the context that was current before it current again.
+ */
+GtkGlShadersShaderArea *
+gtk_gl_shaders_shader_area_new_checked(const char *shader,
+                                       const char **textures,
+                                       unsigned int textures_count,
+                                       const GVariant *uniforms,
+                                       GError **error);
+
+/**
+ * gtk_gl_shaders_shader_area_new_from_files:
+ * @shader_path: (not nullable) (type filename): path of the GLSL fragment shader file
+ * @textures: (array length=textures_count) (nullable): paths to image files to load as textures
+ * @textures_count: number of texture paths in the @textures array
+ * @uniforms: (nullable): initial uniform values as a `GVariant` dictionary
+ * @error: return location for a #GError
+ * Returns: (transfer full) (nullable): a new `GtkGlShadersShaderArea` widget,
+ *   or %NULL if the shader file couldn't be read
+ *
+ * Creates a new shader widget from a shader file.
+ *
+ * The shader file and the textures are watched with `GFileMonitor`. When the
+ * shader changes it is recompiled in place, and when a texture changes it is
+ * uploaded again. If a reload fails, the previous state stays on screen and
+ * compile failures are reported through
+ * #GtkGlShadersShaderArea::compile-error.
+ */
+GtkGlShadersShaderArea *
+gtk_gl_shaders_shader_area_new_from_files(const char *shader_path,
+                                          const char **textures,
+                                          unsigned int textures_count,
+                                          const GVariant *uniforms,
+                                          GError **error);
+
+/**
+ * gtk_gl_shaders_shader_area_new_shadertoy:
+ * @shader: (not nullable): Shadertoy code defining `mainImage`
+ * @textures: (array length=textures_count) (nullable): paths to image files
+ *   to load as textures, bound to `iChannel0` to `iChannel3`
+ * @textures_count: number of texture paths in the @textures array
+ * Returns: (transfer full) (not nullable): a new `GtkGlShadersShaderArea` widget
+ *
+ * Creates a new shader widget running a Shadertoy shader.
+ *
+ * #GtkGlShadersShaderArea:dialect is set to Shadertoy, and
+ * #GtkGlShadersShaderArea:animate and #GtkGlShadersShaderArea:pointer-input
+ * are enabled.
+ */
+GtkGlShadersShaderArea *
+gtk_gl_shaders_shader_area_new_shadertoy(const char *shader,
+                                         const char **textures,
+                                         unsigned int textures_count);
+
+/**
+ * gtk_gl_shaders_shader_area_new_from_shadertoy_json:
+ * @json: (not nullable): a Shadertoy JSON export
+ * @media_dir: (nullable): directory where the textures of the shader were
+ *   downloaded
+ * @error: return location for a #GError
+ * Returns: (transfer full) (nullable): a new `GtkGlShadersShaderArea` widget,
+ *   or %NULL if the export is malformed
+ *
+ * Creates a new shader widget from a Shadertoy JSON export, as returned by
+ * the Shadertoy API or exported from the website.
+ *
+ * Buffers A to D become render passes named `buffer_a` to `buffer_d`, and
+ * the channels of every pass are bound like on Shadertoy. Textures are looked
+ * up by file name in @media_dir. Cubemap, video and sound inputs are not
+ * supported, and are left unbound with a warning.
+ */
+GtkGlShadersShaderArea *
+gtk_gl_shaders_shader_area_new_from_shadertoy_json(const char *json,
+                                                   const char *media_dir,
+                                                   GError **error);
+
+/**
+ * gtk_gl_shaders_shader_area_new_isf:
+ * @shader: (not nullable): an ISF shader, JSON header included
+ * @textures: (array length=textures_count) (nullable): paths to image files
+ *   bound to the `image` inputs, then to the `IMPORTED` images, in order
+ * @textures_count: number of texture paths in the @textures array
+ * @uniforms: (nullable): initial uniform values as a `GVariant` dictionary,
+ *   overriding the defaults of the header
+ * @error: return location for a #GError
+ * Returns: (transfer full) (nullable): a new `GtkGlShadersShaderArea` widget,
+ *   or %NULL if the header is malformed
+ *
+ * Creates a new shader widget running an Interactive Shader Format shader,
+ * with #GtkGlShadersShaderArea:dialect set to
+ * %GTK_GL_SHADERS_SHADER_DIALECT_ISF.
+ *
+ * The `float`, `long`, `bool`, `event`, `color` and `point2D` inputs of the
+ * header are declared as uniforms and initialized to their `DEFAULT`. Every
+ * pass with a `TARGET` but the last becomes a render pass named after its
+ * target; sizes relative to `$WIDTH` and `$HEIGHT` scale with the widget.
+ * `event` inputs are reset to false after the frame that sees them set.
+ *
+ * gtk_gl_shaders_shader_area_set_shader() and file reloads rebuild these
+ * passes from the new header.
+ */
+GtkGlShadersShaderArea *
+gtk_gl_shaders_shader_area_new_isf(const char *shader, const char **textures,
+                                   unsigned int textures_count,
+                                   const GVariant *uniforms, GError **error);
+
+/**
+ * gtk_gl_shaders_shader_area_get_isf_inputs:
+ * @this: (not nullable): the shader area widget
+ * Returns: (transfer full) (not nullable): the inputs as a `GVariant` of type
+ *   `aa{sv}`
+ *
+ * Returns the inputs declared by the widget's ISF shader, for building
+ * controls.
+ *
+ * Each input is a dictionary of the ISF attributes it sets, lowercased:
+ * `name`, `type`, `label`, `default`, `min`, `max`, `identity`, `values` and
+ * `labels`. Numbers are doubles. The array is empty if the shader has no
+ * valid ISF header.
+ */
+GVariant *
+gtk_gl_shaders_shader_area_get_isf_inputs(GtkGlShadersShaderArea *this);
+
+/**
+ * gtk_gl_shaders_shader_area_new_transition:
+ * @transition: (not nullable): code defining `vec4 transition(vec2 uv)`
+ * @from: (not nullable): path of the image to transition from
+ * @to: (not nullable): path of the image to transition to
+ * Returns: (transfer full) (not nullable): a new `GtkGlShadersShaderArea` widget
+ *
+ * Creates a new shader widget playing a transition from the gl-transitions
+ * collection, with #GtkGlShadersShaderArea:dialect set to
+ * %GTK_GL_SHADERS_SHADER_DIALECT_TRANSITION.
+ *
+ * The parameters of the transition are initialized from their
+ * `// = value` comments. @from is shown until
+ * gtk_gl_shaders_shader_area_start_transition() or
+ * gtk_gl_shaders_shader_area_queue_image() is called.
+ */
+GtkGlShadersShaderArea *
+gtk_gl_shaders_shader_area_new_transition(const char *transition,
+                                          const char *from, const char *to);
+
+/**
+ * gtk_gl_shaders_shader_area_start_transition:
+ * @this: (not nullable): the shader area widget
+ *
+ * Plays the transition from the first image to the second one over
+ * #GtkGlShadersShaderArea:transition-duration seconds, restarting it if it is
+ * running. #GtkGlShadersShaderArea::finished is emitted when it ends.
+ */
+void gtk_gl_shaders_shader_area_start_transition(GtkGlShadersShaderArea *this);
+
+/**
+ * gtk_gl_shaders_shader_area_queue_image:
+ * @this: (not nullable): the shader area widget
+ * @path: (not nullable): path of the image to transition to
+ *
+ * Queues an image to transition to after the current transition.
+ *
+ * Once a transition finishes, the image it transitioned to becomes the one
+ * to transition from, and the transition to the next queued image starts. If
+ * no transition is running, it starts right away.
+ */
+void gtk_gl_shaders_shader_area_queue_image(GtkGlShadersShaderArea *this,
+                                            const char *path);
+
+/**
+ * gtk_gl_shaders_shader_area_set_channel:
+ * @this: (not nullable): the shader area widget
+ * @pass: (nullable): name of the render pass, or %NULL for the widget's shader
+ * @channel: index of the channel, from 0 to 3
+ * @sampler: (nullable): name of the sampler to bind (`tex0`, `keyboard`, the
+ *   name of a render pass...), or %NULL to leave the channel unbound
+ *
+ * Binds a Shadertoy `iChannelN` to a sampler. By default, `iChannelN` is
+ * bound to the texture `texN`.
+ */
+void gtk_gl_shaders_shader_area_set_channel(GtkGlShadersShaderArea *this,
+                                            const char *pass,
+                                            unsigned int channel,
+                                            const char *sampler);
+
+/**
+ * gtk_gl_shaders_shader_area_set_shader:
+ * @this: (not nullable): the shader area widget
+ * @source: (not nullable): the new GLSL fragment shader source code
+ * @error: return location for a #GError
+ * Returns: %TRUE if the shader was replaced, %FALSE if it failed to compile
+ *
+ * Replaces the fragment shader at runtime.
+ *
+ * The program is recompiled in place: uniform locations and texture samplers
+ * are resolved again and current uniform values are kept. If the new shader
+ * fails to compile or link, the previous one keeps running and
+ * #GtkGlShadersShaderArea::compile-error is emitted.
+ *
+ * If the widget is not realized yet, the shader is compiled when it is.
+ */
+gboolean gtk_gl_shaders_shader_area_set_shader(GtkGlShadersShaderArea *this,
+                                               const char *source,
+                                               GError **error);
+
+/**
+ * gtk_gl_shaders_shader_area_set_vertex_shader:
+ * @this: (not nullable): the shader area widget
+ * @source: (nullable): the GLSL vertex shader source code, or %NULL for the
+ *   fullscreen quad
+ * @error: return location for a #GError
+ * Returns: %TRUE if the vertex shader was replaced, %FALSE if it failed to
+ *   compile
+ *
+ * Replaces the vertex shader drawing the widget's shader, in the style of
+ * vertexshaderart.
+ *
+ * The vertex shader gets `vertexId` (`gl_VertexID` as a `float`) and the
+ * `vertexCount` uniform, and no vertex attributes: it computes positions
+ * procedurally. #GtkGlShadersShaderArea:primitive and
+ * #GtkGlShadersShaderArea:vertex-count choose what is drawn. Render passes
+ * keep drawing the fullscreen quad.
+ *
+ * If the shaders fail to compile or link, the previous vertex shader keeps
+ * running and #GtkGlShadersShaderArea::compile-error is emitted.
+ */
+gboolean
+gtk_gl_shaders_shader_area_set_vertex_shader(GtkGlShadersShaderArea *this,
+                                             const char *source,
+                                             GError **error);
+
+/**
+ * gtk_gl_shaders_shader_area_set_particles:
+ * @this: (not nullable): the shader area widget
+ * @update: (nullable): GLSL fragment shader computing the next state of each
+ *   particle, or %NULL to remove the particle system
+ * @vertex: (nullable): GLSL vertex shader placing the sprite of each particle
+ * @fragment: (nullable): GLSL fragment shader shading the sprites
+ * @buffers: number of RGBA32F state buffers, from 1 to 4
+ * @error: return location for a #GError
+ * Returns: %TRUE if the particle system was set, %FALSE if it failed to
+ *   compile
+ *
+ * Sets the particle system drawn over the widget's shader.
+ *
+ * The state of each particle lives in float textures, one texel per
+ * particle. Every frame, @update renders the next state to `particle_out0`…
+ * from the current one in `particle_state0`…, then each particle is drawn as
+ * an instanced quad by @vertex and @fragment. Both @update and @vertex get
+ * `particle_index`, `particle_texel` and `particle_count`, and @vertex gets
+ * `particle_corner`, the corner of the quad. The state starts out zeroed, so
+ * @update is responsible for spawning particles, typically from emitter
+ * uniforms set like any other uniform.
+ *
+ * #GtkGlShadersShaderArea:particle-count and
+ * #GtkGlShadersShaderArea:particle-blend choose how many particles are drawn
+ * and how. If the shaders fail to compile or link, the previous particle
+ * system keeps running and #GtkGlShadersShaderArea::compile-error is emitted.
+ * On a widget that isn't realized yet, the shaders are compiled when it is,
+ * and the widget renders without particles if they fail.
+ */
+gboolean gtk_gl_shaders_shader_area_set_particles(GtkGlShadersShaderArea *this,
+                                                  const char *update,
+                                                  const char *vertex,
+                                                  const char *fragment,
+                                                  unsigned int buffers,
+                                                  GError **error);
+
+/**
+ * gtk_gl_shaders_shader_area_add_pass:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the pass, used as the name of its sampler
+ * @shader: (not nullable): GLSL fragment shader source code of the pass
+ * @width: width of the output texture in pixels, or 0 to follow the widget
+ * @height: height of the output texture in pixels, or 0 to follow the widget
+ * @scale: size of the output texture relative to the widget, when @width or
+ *   @height is 0
+ * @format: pixel format of the output texture
+ * @filter: filtering used when sampling the output texture
+ * @error: return location for a #GError
+ * Returns: %TRUE if the pass was added, %FALSE otherwise
+ *
+ * Adds an offscreen render pass, rendered after the existing ones and before
+ * the widget's own shader, which draws to the screen.
+ *
+ * The pass shader has the same inputs as the widget's shader, with
+ * `resolution` set to the size of the pass. Every shader can sample the output
+ * of the pass as a `uniform sampler2D` named @name: passes rendered earlier in
+ * the frame give their new output, while the pass itself and later passes
+ * give their output from the previous frame, which allows feedback effects.
+ */
+gboolean gtk_gl_shaders_shader_area_add_pass(GtkGlShadersShaderArea *this,
+                                             const char *name,
+                                             const char *shader,
+                                             unsigned int width,
+                                             unsigned int height, float scale,
+                                             GtkGlShadersPassFormat format,
+                                             GtkGlShadersPassFilter filter,
+                                             GError **error);
+
+/**
+ * gtk_gl_shaders_shader_area_remove_pass:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the pass to remove
+ * Returns: %TRUE if a pass with that name existed
+ *
+ * Removes a render pass. If the shaders failed to compile, they are compiled
+ * again without it.
+ */
+gboolean gtk_gl_shaders_shader_area_remove_pass(GtkGlShadersShaderArea *this,
+                                                const char *name);
+
+/**
+ * gtk_gl_shaders_shader_area_add_compute:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the pass
+ * @shader: (not nullable): GLSL compute shader source code, without the
+ *   `local_size` layout
+ * @local_x: width of a work group
+ * @local_y: height of a work group
+ * @local_z: depth of a work group
+ * @groups_x: number of work groups dispatched along x
+ * @groups_y: number of work groups dispatched along y
+ * @groups_z: number of work groups dispatched along z
+ * @schedule: when the pass is dispatched
+ * @error: return location for a #GError
+ * Returns: %TRUE if the pass was added, %FALSE otherwise
+ *
+ * Adds a compute pass, dispatched after the existing ones and before the
+ * particles, the render passes and the widget's shader.
+ *
+ * Compute shaders are compiled as GLSL 4.30 and need an OpenGL 4.3 context
+ * (see #GtkGlShadersShaderArea:compute-supported). They read and write the
+ * storage buffers and images of the widget, and get the same builtins and
+ * uniforms as the other shaders. If the widget is realized without compute
+ * support, this fails with %GTK_GL_SHADERS_SHADER_ERROR_UNSUPPORTED; passes
+ * added before that are reported through
+ * #GtkGlShadersShaderArea::compile-error on realize.
+ */
+gboolean gtk_gl_shaders_shader_area_add_compute(
+    GtkGlShadersShaderArea *this, const char *name, const char *shader,
+    unsigned int local_x, unsigned int local_y, unsigned int local_z,
+    unsigned int groups_x, unsigned int groups_y, unsigned int groups_z,
+    GtkGlShadersComputeSchedule schedule, GError **error);
+
+/**
+ * gtk_gl_shaders_shader_area_remove_compute:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the pass to remove
+ * Returns: %TRUE if a compute pass with that name existed
+ *
+ * Removes a compute pass.
+ */
+gboolean
+gtk_gl_shaders_shader_area_remove_compute(GtkGlShadersShaderArea *this,
+                                          const char *name);
+
+/**
+ * gtk_gl_shaders_shader_area_run_compute:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of an on-demand compute pass
+ * Returns: %TRUE if the widget is realized with that pass
+ *
+ * Dispatches a %GTK_GL_SHADERS_COMPUTE_SCHEDULE_ON_DEMAND compute pass on
+ * the next frame, and queues that frame.
+ */
+gboolean gtk_gl_shaders_shader_area_run_compute(GtkGlShadersShaderArea *this,
+                                                const char *name);
+
+/**
+ * gtk_gl_shaders_shader_area_set_storage_buffer:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the buffer block in the shaders
+ * @data: (not nullable): initial contents, which also give the size of the
+ *   buffer
+ * @error: return location for a #GError
+ * Returns: %TRUE if the buffer was set, %FALSE otherwise
+ *
+ * Creates a shader storage buffer, or replaces the contents of the buffer
+ * with the same name.
  *
- * `GtkGlShadersShaderArea` is a subclass of `GtkGLArea` that executes a
- * user-provided fragment shader on a fullscreen quad. It supports loading
- * textures and setting uniform values.
+ * Shaders access it by declaring a buffer block named @name, e.g.
+ * `layout(std430) buffer name { float values[]; };`.
+ */
+gboolean
+gtk_gl_shaders_shader_area_set_storage_buffer(GtkGlShadersShaderArea *this,
+                                              const char *name, GBytes *data,
+                                              GError **error);
+
+/**
+ * gtk_gl_shaders_shader_area_set_storage_image:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the image uniform in the shaders
+ * @width: width in pixels
+ * @height: height in pixels
+ * @format: pixel format of the image
+ * @data: (nullable): initial pixels, row by row from the bottom, or %NULL
+ *   for a cleared image
+ * @error: return location for a #GError
+ * Returns: %TRUE if the image was set, %FALSE otherwise
  *
- * As a `GtkGLArea` subclass, it inherits all GLArea functionality including
- * OpenGL context management and can be used anywhere a GTK4 widget is expected.
+ * Creates a storage image, or replaces the image with the same name.
  *
- * # Shader inputs
+ * Compute shaders read and write it as an `image2D` named @name, with a
+ * format qualifier matching @format (`rgba8`, `rgba16f` or `rgba32f`), and
+ * render shaders sample it as a `sampler2D` of the same name.
+ */
+gboolean gtk_gl_shaders_shader_area_set_storage_image(
+    GtkGlShadersShaderArea *this, const char *name, unsigned int width,
+    unsigned int height, GtkGlShadersPassFormat format, GBytes *data,
+    GError **error);
+
+/**
+ * gtk_gl_shaders_shader_area_read_storage_buffer:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the buffer
+ * Returns: (transfer full) (nullable): the contents of the buffer as of the
+ *   last frame, or %NULL if the widget isn't realized with that buffer
  *
- * - `uv` - A `vec2` from (0,0) at bottom-left to (1,1) at top-right
- * - `tex0`, `tex1`, ... - Sampler uniforms for loaded textures
- * - Custom uniforms - Set via the `gtk_gl_shaders_shader_area_set_uniform_*` functions
+ * Reads back a storage buffer.
  */
-G_DECLARE_FINAL_TYPE(GtkGlShadersShaderArea, gtk_gl_shaders_shader_area,
-                     GTK_GL_SHADERS, SHADER_AREA, GtkGLArea)
+GBytes *
+gtk_gl_shaders_shader_area_read_storage_buffer(GtkGlShadersShaderArea *this,
+                                               const char *name);
 
 /**
- * gtk_gl_shaders_shader_area_new:
- * @shader: (not nullable): GLSL fragment shader source code
- * @textures: (array length=textures_count) (nullable): paths to image files to load as textures
- * @textures_count: number of texture paths in the @textures array
- * @uniforms: (nullable): initial uniform values as a `GVariant` dictionary
- * Returns: (transfer full) (not nullable): a new `GtkGlShadersShaderArea` widget
+ * gtk_gl_shaders_shader_area_read_storage_image:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the image
+ * Returns: (transfer full) (nullable): the pixels of the image as of the last
+ *   frame, row by row from the bottom, or %NULL if the widget isn't realized
+ *   with that image
  *
- * Creates a new shader widget with the given fragment shader, textures, and uniforms.
+ * Reads back a storage image.
+ */
+GBytes *
+gtk_gl_shaders_shader_area_read_storage_image(GtkGlShadersShaderArea *this,
+                                              const char *name);
+
+/**
+ * gtk_gl_shaders_shader_area_load_mesh:
+ * @this: (not nullable): the shader area widget
+ * @path: (nullable) (type filename): path of an OBJ, glTF or GLB file, or
+ *   %NULL to restore the fullscreen quad
+ * @error: return location for a #GError
+ * Returns: %TRUE if the mesh was loaded, %FALSE if the file couldn't be read
+ *   or parsed
  *
- * The fragment shader should declare `in vec2 uv` for texture coordinates and
- * `out vec4 out_color` for the output color. Textures are accessible as
- * `uniform sampler2D tex0`, `tex1`, etc.
+ * Draws the widget's shader over the triangles of a mesh instead of the
+ * fullscreen quad, with depth testing.
  *
- * Uniforms should be passed as a `GVariant` of type `a{sv}` (dictionary of
- * string to variant). Float values use type 'd' (double), and vector values
- * use type 'ad' (array of doubles).
+ * The mesh is centered and scaled to fit from -1 to 1 by the `model_matrix`
+ * uniform, and viewed through `camera_matrix` and `projection_matrix` (see
+ * #GtkGlShadersShaderArea:orbit-camera). Unless a custom vertex shader is
+ * set, the fragment shader gets `uv`, `world_position` and `world_normal`;
+ * a custom vertex shader reads `position`, `normal` and `texcoord` at
+ * attribute locations 0, 1 and 2. Loading a mesh enables
+ * #GtkGLArea:has-depth-buffer. If the file can't be loaded, the previous mesh
+ * is kept.
  */
-GtkGlShadersShaderArea *
-gtk_gl_shaders_shader_area_new(const char *shader, const char **textures,
-                               unsigned int textures_count,
-                               const GVariant *uniforms);
+gboolean gtk_gl_shaders_shader_area_load_mesh(GtkGlShadersShaderArea *this,
+                                              const char *path,
+                                              GError **error);
+
+/**
+ * gtk_gl_shaders_shader_area_reset_camera:
+ * @this: (not nullable): the shader area widget
+ *
+ * Resets the camera driven by #GtkGlShadersShaderArea:orbit-camera.
+ */
+void gtk_gl_shaders_shader_area_reset_camera(GtkGlShadersShaderArea *this);
+
+/**
+ * gtk_gl_shaders_shader_area_reset_view:
+ * @this: (not nullable): the shader area widget
+ *
+ * Resets the view transform driven by #GtkGlShadersShaderArea:view-controls.
+ */
+void gtk_gl_shaders_shader_area_reset_view(GtkGlShadersShaderArea *this);
 
 /**
  * gtk_gl_shaders_shader_area_set_uniform_float:
@@ -152,4 +1081,459 @@ void gtk_gl_shaders_shader_area_set_uniform_ivec4(
     GtkGlShadersShaderArea *this, const char *name, int a, int b, int c,
     int d);
 
+/**
+ * gtk_gl_shaders_shader_area_set_uniform_bool:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the uniform to set
+ * @value: value to set
+ *
+ * Sets a `bool` uniform value.
+ */
+void gtk_gl_shaders_shader_area_set_uniform_bool(
+    GtkGlShadersShaderArea *this, const char *name, gboolean value);
+
+/**
+ * gtk_gl_shaders_shader_area_set_uniform_uint:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the uniform to set
+ * @value: value to set
+ *
+ * Sets a `uint` uniform value.
+ */
+void gtk_gl_shaders_shader_area_set_uniform_uint(
+    GtkGlShadersShaderArea *this, const char *name, unsigned int value);
+
+/**
+ * gtk_gl_shaders_shader_area_set_uniform_uvec2:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the uniform to set
+ * @a: first component
+ * @b: second component
+ *
+ * Sets a `uvec2` uniform value.
+ */
+void gtk_gl_shaders_shader_area_set_uniform_uvec2(
+    GtkGlShadersShaderArea *this, const char *name, unsigned int a,
+    unsigned int b);
+
+/**
+ * gtk_gl_shaders_shader_area_set_uniform_uvec3:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the uniform to set
+ * @a: first component
+ * @b: second component
+ * @c: third component
+ *
+ * Sets a `uvec3` uniform value.
+ */
+void gtk_gl_shaders_shader_area_set_uniform_uvec3(
+    GtkGlShadersShaderArea *this, const char *name, unsigned int a,
+    unsigned int b, unsigned int c);
+
+/**
+ * gtk_gl_shaders_shader_area_set_uniform_uvec4:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the uniform to set
+ * @a: first component
+ * @b: second component
+ * @c: third component
+ * @d: fourth component
+ *
+ * Sets a `uvec4` uniform value.
+ */
+void gtk_gl_shaders_shader_area_set_uniform_uvec4(
+    GtkGlShadersShaderArea *this, const char *name, unsigned int a,
+    unsigned int b, unsigned int c, unsigned int d);
+
+/**
+ * gtk_gl_shaders_shader_area_set_uniform_double:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the uniform to set
+ * @value: value to set
+ *
+ * Sets a `double` uniform value, on contexts where
+ * #GtkGlShadersShaderArea:fp64-supported is %TRUE.
+ */
+void gtk_gl_shaders_shader_area_set_uniform_double(
+    GtkGlShadersShaderArea *this, const char *name, double value);
+
+/**
+ * gtk_gl_shaders_shader_area_set_uniform_dvec2:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the uniform to set
+ * @a: first component
+ * @b: second component
+ *
+ * Sets a `dvec2` uniform value, on contexts where
+ * #GtkGlShadersShaderArea:fp64-supported is %TRUE.
+ */
+void gtk_gl_shaders_shader_area_set_uniform_dvec2(
+    GtkGlShadersShaderArea *this, const char *name, double a, double b);
+
+/**
+ * gtk_gl_shaders_shader_area_set_uniform_dvec3:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the uniform to set
+ * @a: first component
+ * @b: second component
+ * @c: third component
+ *
+ * Sets a `dvec3` uniform value, on contexts where
+ * #GtkGlShadersShaderArea:fp64-supported is %TRUE.
+ */
+void gtk_gl_shaders_shader_area_set_uniform_dvec3(
+    GtkGlShadersShaderArea *this, const char *name, double a, double b,
+    double c);
+
+/**
+ * gtk_gl_shaders_shader_area_set_uniform_dvec4:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the uniform to set
+ * @a: first component
+ * @b: second component
+ * @c: third component
+ * @d: fourth component
+ *
+ * Sets a `dvec4` uniform value, on contexts where
+ * #GtkGlShadersShaderArea:fp64-supported is %TRUE.
+ */
+void gtk_gl_shaders_shader_area_set_uniform_dvec4(
+    GtkGlShadersShaderArea *this, const char *name, double a, double b,
+    double c, double d);
+
+/**
+ * gtk_gl_shaders_shader_area_set_uniform_mat2:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the uniform to set
+ * @values: (array fixed-size=4): the 4 elements of the matrix, column by
+ *   column
+ *
+ * Sets a `mat2` uniform value.
+ */
+void gtk_gl_shaders_shader_area_set_uniform_mat2(
+    GtkGlShadersShaderArea *this, const char *name, const float *values);
+
+/**
+ * gtk_gl_shaders_shader_area_set_uniform_mat3:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the uniform to set
+ * @values: (array fixed-size=9): the 9 elements of the matrix, column by
+ *   column
+ *
+ * Sets a `mat3` uniform value.
+ */
+void gtk_gl_shaders_shader_area_set_uniform_mat3(
+    GtkGlShadersShaderArea *this, const char *name, const float *values);
+
+/**
+ * gtk_gl_shaders_shader_area_set_uniform_mat4:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the uniform to set
+ * @values: (array fixed-size=16): the 16 elements of the matrix, column by
+ *   column
+ *
+ * Sets a `mat4` uniform value.
+ */
+void gtk_gl_shaders_shader_area_set_uniform_mat4(
+    GtkGlShadersShaderArea *this, const char *name, const float *values);
+
+/**
+ * gtk_gl_shaders_shader_area_set_uniform_matrix:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the uniform to set
+ * @matrix: (not nullable): the matrix
+ *
+ * Sets a `mat4` uniform value from a #graphene_matrix_t. Graphene transforms
+ * row vectors, so the matrix transforms a `vec4` in GLSL as `matrix * v`
+ * with the same result as graphene_matrix_transform_vec4().
+ */
+void gtk_gl_shaders_shader_area_set_uniform_matrix(
+    GtkGlShadersShaderArea *this, const char *name,
+    const graphene_matrix_t *matrix);
+
+/**
+ * gtk_gl_shaders_shader_area_set_uniform_graphene_vec2:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the uniform to set
+ * @value: (not nullable): the vector
+ *
+ * Sets a `vec2` uniform value from a #graphene_vec2_t.
+ */
+void gtk_gl_shaders_shader_area_set_uniform_graphene_vec2(
+    GtkGlShadersShaderArea *this, const char *name,
+    const graphene_vec2_t *value);
+
+/**
+ * gtk_gl_shaders_shader_area_set_uniform_graphene_vec3:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the uniform to set
+ * @value: (not nullable): the vector
+ *
+ * Sets a `vec3` uniform value from a #graphene_vec3_t.
+ */
+void gtk_gl_shaders_shader_area_set_uniform_graphene_vec3(
+    GtkGlShadersShaderArea *this, const char *name,
+    const graphene_vec3_t *value);
+
+/**
+ * gtk_gl_shaders_shader_area_set_uniform_graphene_vec4:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the uniform to set
+ * @value: (not nullable): the vector
+ *
+ * Sets a `vec4` uniform value from a #graphene_vec4_t.
+ */
+void gtk_gl_shaders_shader_area_set_uniform_graphene_vec4(
+    GtkGlShadersShaderArea *this, const char *name,
+    const graphene_vec4_t *value);
+
+/**
+ * gtk_gl_shaders_shader_area_set_uniform_point:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the uniform to set
+ * @point: (not nullable): the point
+ *
+ * Sets a `vec2` uniform value from a #graphene_point_t.
+ */
+void gtk_gl_shaders_shader_area_set_uniform_point(
+    GtkGlShadersShaderArea *this, const char *name,
+    const graphene_point_t *point);
+
+/**
+ * gtk_gl_shaders_shader_area_set_uniform_rect:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the uniform to set
+ * @rect: (not nullable): the rectangle
+ *
+ * Sets a `vec4` uniform value from a #graphene_rect_t, as its origin followed
+ * by its size. The rectangle is normalized to a positive size first.
+ */
+void gtk_gl_shaders_shader_area_set_uniform_rect(
+    GtkGlShadersShaderArea *this, const char *name,
+    const graphene_rect_t *rect);
+
+/**
+ * gtk_gl_shaders_shader_area_set_uniform_rgba:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the uniform to set
+ * @color: (not nullable): the color
+ *
+ * Sets a `vec4` uniform value from a #GdkRGBA, as red, green, blue and alpha.
+ */
+void gtk_gl_shaders_shader_area_set_uniform_rgba(
+    GtkGlShadersShaderArea *this, const char *name, const GdkRGBA *color);
+
+/**
+ * gtk_gl_shaders_shader_area_set_uniform_array:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the array uniform to set
+ * @type: the element type of the array
+ * @data: (not nullable): the components of every element, packed as
+ *   native-endian 32-bit floats or integers
+ * @error: return location for a #GError
+ * Returns: %TRUE if the array was set, %FALSE if @data doesn't hold a whole
+ *   number of elements, or more than the shader declares
+ *
+ * Sets an array uniform, such as `uniform vec3 colors[8];`, from packed
+ * bytes. From JavaScript, pass the buffer of a `Float32Array` or
+ * `Int32Array` as a #GBytes.
+ *
+ * Arrays with more elements than the shader declares are rejected, while
+ * missing elements keep their previous value. The size is only known once
+ * the widget is realized: before that, the array is stored and checked
+ * then, with errors logged.
+ */
+gboolean gtk_gl_shaders_shader_area_set_uniform_array(
+    GtkGlShadersShaderArea *this, const char *name,
+    GtkGlShadersUniformType type, GBytes *data, GError **error);
+
+/**
+ * gtk_gl_shaders_shader_area_set_uniform_float_array:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the array uniform to set
+ * @components: number of components of each element, 1 for `float` to 4
+ *   for `vec4`
+ * @values: (array length=n_values): the components of every element
+ * @n_values: the number of components in @values
+ * @error: return location for a #GError
+ * Returns: %TRUE if the array was set, %FALSE if @values doesn't hold a
+ *   whole number of elements, or more than the shader declares
+ *
+ * Sets a `float` or `vecN` array uniform from its components, like
+ * gtk_gl_shaders_shader_area_set_uniform_array().
+ */
+gboolean gtk_gl_shaders_shader_area_set_uniform_float_array(
+    GtkGlShadersShaderArea *this, const char *name, unsigned int components,
+    const float *values, unsigned int n_values, GError **error);
+
+/**
+ * gtk_gl_shaders_shader_area_set_uniform_int_array:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the array uniform to set
+ * @components: number of components of each element, 1 for `int` to 4 for
+ *   `ivec4`
+ * @values: (array length=n_values): the components of every element
+ * @n_values: the number of components in @values
+ * @error: return location for a #GError
+ * Returns: %TRUE if the array was set, %FALSE if @values doesn't hold a
+ *   whole number of elements, or more than the shader declares
+ *
+ * Sets an `int` or `ivecN` array uniform from its components, like
+ * gtk_gl_shaders_shader_area_set_uniform_array().
+ */
+gboolean gtk_gl_shaders_shader_area_set_uniform_int_array(
+    GtkGlShadersShaderArea *this, const char *name, unsigned int components,
+    const int *values, unsigned int n_values, GError **error);
+
+/**
+ * gtk_gl_shaders_shader_area_set_uniforms:
+ * @this: (not nullable): the shader area widget
+ * @uniforms: (not nullable): a `a{sv}` dictionary of uniform values, typed
+ *   like the uniforms passed to gtk_gl_shaders_shader_area_new()
+ * @error: return location for a #GError
+ * Returns: %TRUE if the uniforms were set, %FALSE otherwise
+ *
+ * Sets several uniforms at once, so that they're applied to the same frame,
+ * with a single redraw.
+ *
+ * Every value is parsed and converted to the type declared by the shaders
+ * before any is applied: if one is invalid, none are set and @error is set.
+ * Inside a transaction, the values are only checked when it's committed.
+ */
+gboolean gtk_gl_shaders_shader_area_set_uniforms(GtkGlShadersShaderArea *this,
+                                                 GVariant *uniforms,
+                                                 GError **error);
+
+/**
+ * gtk_gl_shaders_shader_area_begin_uniforms:
+ * @this: (not nullable): the shader area widget
+ *
+ * Starts a uniform transaction: the values given to the
+ * `gtk_gl_shaders_shader_area_set_uniform*()` functions are kept until the
+ * matching gtk_gl_shaders_shader_area_commit_uniforms(), which applies them
+ * all to the same frame, so that it never renders with only some of them, or
+ * gtk_gl_shaders_shader_area_abort_uniforms(), which drops them.
+ *
+ * Transactions can be nested; the values are applied when the outermost one
+ * is committed. A transaction left open for many frames is logged as a
+ * warning.
+ */
+void gtk_gl_shaders_shader_area_begin_uniforms(GtkGlShadersShaderArea *this);
+
+/**
+ * gtk_gl_shaders_shader_area_commit_uniforms:
+ * @this: (not nullable): the shader area widget
+ * @error: return location for a #GError
+ * Returns: %TRUE if the uniforms were set, %FALSE otherwise
+ *
+ * Ends a transaction started by gtk_gl_shaders_shader_area_begin_uniforms().
+ *
+ * When the outermost transaction is committed, the uniforms set since are
+ * applied like with gtk_gl_shaders_shader_area_set_uniforms(): if a value
+ * can't be converted to the type declared by the shaders, none are set and
+ * @error is set.
+ */
+gboolean
+gtk_gl_shaders_shader_area_commit_uniforms(GtkGlShadersShaderArea *this,
+                                           GError **error);
+
+/**
+ * gtk_gl_shaders_shader_area_abort_uniforms:
+ * @this: (not nullable): the shader area widget
+ *
+ * Aborts a transaction started by gtk_gl_shaders_shader_area_begin_uniforms(),
+ * along with any outer ones: the uniforms set since the outermost one was
+ * started are dropped, and later ones are applied immediately again.
+ */
+void gtk_gl_shaders_shader_area_abort_uniforms(GtkGlShadersShaderArea *this);
+
+/**
+ * gtk_gl_shaders_shader_area_set_uniform_block:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the uniform block in the shaders
+ * @data: (not nullable): the contents of the block, laid out as declared
+ *   by the shaders (usually following the `std140` rules)
+ * @error: return location for a #GError
+ * Returns: %TRUE if the block was set, %FALSE otherwise
+ *
+ * Sets the contents of a uniform block, such as
+ * `layout(std140) uniform Params { vec4 color; float speed; };`, replacing
+ * those of the block with the same name. The block is backed by a uniform
+ * buffer shared by every shader declaring it. Blocks set after every
+ * uniform buffer binding is taken are ignored with a warning.
+ *
+ * Bytes beyond the size of the block in the shaders are dropped with a
+ * warning, and missing ones are zero.
+ */
+gboolean gtk_gl_shaders_shader_area_set_uniform_block(
+    GtkGlShadersShaderArea *this, const char *name, GBytes *data,
+    GError **error);
+
+/**
+ * gtk_gl_shaders_shader_area_set_uniform_block_members:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the uniform block in the shaders
+ * @members: (not nullable): a `a{sv}` dictionary of member values, typed
+ *   like the uniforms passed to gtk_gl_shaders_shader_area_new()
+ * @error: return location for a #GError
+ * Returns: %TRUE if the block was set, %FALSE otherwise
+ *
+ * Sets the contents of a uniform block from the values of its members,
+ * which the widget packs at the offsets OpenGL reports for them, so the
+ * block layout doesn't need to be known.
+ *
+ * Unknown members, and arrays with more elements than the shader declares,
+ * are dropped with a warning, and missing ones are zero.
+ */
+gboolean gtk_gl_shaders_shader_area_set_uniform_block_members(
+    GtkGlShadersShaderArea *this, const char *name, GVariant *members,
+    GError **error);
+
+/**
+ * gtk_gl_shaders_shader_area_remove_uniform_block:
+ * @this: (not nullable): the shader area widget
+ * @name: (not nullable): name of the block to remove
+ * Returns: %TRUE if a uniform block with that name existed
+ *
+ * Removes a uniform block, and frees its buffer.
+ */
+gboolean
+gtk_gl_shaders_shader_area_remove_uniform_block(GtkGlShadersShaderArea *this,
+                                                const char *name);
+
+/**
+ * gtk_gl_shaders_shader_area_list_uniforms:
+ * @this: (not nullable): the shader area widget
+ * Returns: (transfer full) (not nullable): the uniforms as a `GVariant` of
+ *   type `aa{sv}`
+ *
+ * Lists the uniforms the shaders use, samplers and images excluded, sorted by
+ * name, for building settings UIs.
+ *
+ * Each uniform is a dictionary with the keys `name`, `type` (the GLSL type,
+ * of an element for arrays), `size` (the number of elements, 1 unless it is
+ * an array), `source` (a #GtkGlShadersUniformSource value) and `value`,
+ * present when known and typed like the uniforms passed to
+ * gtk_gl_shaders_shader_area_new(). Builtins have no value, as it changes on
+ * every frame.
+ *
+ * The array is empty until the widget is realized. Members of uniform blocks
+ * aren't listed.
+ */
+GVariant *
+gtk_gl_shaders_shader_area_list_uniforms(GtkGlShadersShaderArea *this);
+
+/**
+ * gtk_gl_shaders_shader_area_list_samplers:
+ * @this: (not nullable): the shader area widget
+ * Returns: (transfer full) (not nullable): the samplers as a `GVariant` of
+ *   type `aa{sv}`
+ *
+ * Lists the samplers and images the shaders use, like
+ * gtk_gl_shaders_shader_area_list_uniforms(). The value of the samplers the
+ * widget binds is their texture unit.
+ */
+GVariant *
+gtk_gl_shaders_shader_area_list_samplers(GtkGlShadersShaderArea *this);
+
 G_END_DECLS
\ No newline at end of file
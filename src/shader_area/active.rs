@@ -0,0 +1,755 @@
+//! Active uniforms and type coercion.
+//!
+//! After linking, the driver reports the type and array size of every
+//! uniform a program actually uses. Values are converted to that declared
+//! type before being applied, since calling the wrong `glUniform*` function
+//! (e.g. `glUniform1f` on an `int`, as happens with JS numbers) fails
+//! silently:
+//!
+//! - Numbers convert between `float`, `double`, `int`, `uint` and `bool`, as
+//!   long as integers stay whole and in range.
+//! - Components are regrouped by the declared size, so that four floats fill
+//!   a `mat2` and eight fill a `vec2[4]`, but their count has to match (or
+//!   be a whole number of elements, for arrays).
+//!
+//! Values that can't be converted are rejected with a [`ShaderError::Uniform`]
+//! naming the uniform and both types.
+//!
+//! The active uniforms are also listed as [`UniformInfo`]s, for settings UIs
+//! built from what the shader declares.
+
+use std::collections::HashMap;
+
+use gtk::glib::{self, Variant, VariantDict, prelude::*, translate::IntoGlib};
+
+use super::{ShaderError, Uniform};
+
+/// The type of the components of a uniform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scalar {
+    /// `float`
+    Float,
+    /// `double`
+    Double,
+    /// `int`
+    Int,
+    /// `uint`
+    UInt,
+    /// `bool`
+    Bool,
+}
+
+impl Scalar {
+    /// Returns the prefix of GLSL vector and matrix types of this scalar.
+    fn prefix(self) -> &'static str {
+        match self {
+            Self::Float => "",
+            Self::Double => "d",
+            Self::Int => "i",
+            Self::UInt => "u",
+            Self::Bool => "b",
+        }
+    }
+
+    /// Returns the GLSL name of the scalar type.
+    fn name(self) -> &'static str {
+        match self {
+            Self::Float => "float",
+            Self::Double => "double",
+            Self::Int => "int",
+            Self::UInt => "uint",
+            Self::Bool => "bool",
+        }
+    }
+
+    /// Converts a component, or returns `None` if it doesn't fit.
+    fn convert(self, value: f64) -> Option<f64> {
+        let whole = value.fract() == 0.0;
+        match self {
+            Self::Float | Self::Double => Some(value),
+            Self::Int => (whole && value >= f64::from(i32::MIN) && value <= f64::from(i32::MAX))
+                .then_some(value),
+            Self::UInt => (whole && value >= 0.0 && value <= f64::from(u32::MAX)).then_some(value),
+            Self::Bool => Some(f64::from(u8::from(value != 0.0))),
+        }
+    }
+}
+
+/// A GLSL uniform type, as reported by `glGetActiveUniform`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlslType {
+    /// A scalar, vector or matrix (vectors have a single column)
+    Value {
+        /// Type of the components
+        scalar: Scalar,
+        /// Number of matrix columns
+        columns: usize,
+        /// Number of rows, or of vector components
+        rows: usize,
+    },
+    /// A sampler or image, set to a texture or image unit
+    Opaque(&'static str),
+    /// A type the widget doesn't know about
+    Unknown(u32),
+}
+
+impl GlslType {
+    /// Returns the type for an OpenGL type enum.
+    pub fn from_gl(ty: u32) -> Self {
+        let value = |scalar, columns, rows| Self::Value {
+            scalar,
+            columns,
+            rows,
+        };
+        match ty {
+            epoxy::FLOAT => value(Scalar::Float, 1, 1),
+            epoxy::FLOAT_VEC2 => value(Scalar::Float, 1, 2),
+            epoxy::FLOAT_VEC3 => value(Scalar::Float, 1, 3),
+            epoxy::FLOAT_VEC4 => value(Scalar::Float, 1, 4),
+            epoxy::DOUBLE => value(Scalar::Double, 1, 1),
+            epoxy::DOUBLE_VEC2 => value(Scalar::Double, 1, 2),
+            epoxy::DOUBLE_VEC3 => value(Scalar::Double, 1, 3),
+            epoxy::DOUBLE_VEC4 => value(Scalar::Double, 1, 4),
+            epoxy::INT => value(Scalar::Int, 1, 1),
+            epoxy::INT_VEC2 => value(Scalar::Int, 1, 2),
+            epoxy::INT_VEC3 => value(Scalar::Int, 1, 3),
+            epoxy::INT_VEC4 => value(Scalar::Int, 1, 4),
+            epoxy::UNSIGNED_INT => value(Scalar::UInt, 1, 1),
+            epoxy::UNSIGNED_INT_VEC2 => value(Scalar::UInt, 1, 2),
+            epoxy::UNSIGNED_INT_VEC3 => value(Scalar::UInt, 1, 3),
+            epoxy::UNSIGNED_INT_VEC4 => value(Scalar::UInt, 1, 4),
+            epoxy::BOOL => value(Scalar::Bool, 1, 1),
+            epoxy::BOOL_VEC2 => value(Scalar::Bool, 1, 2),
+            epoxy::BOOL_VEC3 => value(Scalar::Bool, 1, 3),
+            epoxy::BOOL_VEC4 => value(Scalar::Bool, 1, 4),
+            epoxy::FLOAT_MAT2 => value(Scalar::Float, 2, 2),
+            epoxy::FLOAT_MAT3 => value(Scalar::Float, 3, 3),
+            epoxy::FLOAT_MAT4 => value(Scalar::Float, 4, 4),
+            epoxy::FLOAT_MAT2x3 => value(Scalar::Float, 2, 3),
+            epoxy::FLOAT_MAT2x4 => value(Scalar::Float, 2, 4),
+            epoxy::FLOAT_MAT3x2 => value(Scalar::Float, 3, 2),
+            epoxy::FLOAT_MAT3x4 => value(Scalar::Float, 3, 4),
+            epoxy::FLOAT_MAT4x2 => value(Scalar::Float, 4, 2),
+            epoxy::FLOAT_MAT4x3 => value(Scalar::Float, 4, 3),
+            epoxy::DOUBLE_MAT2 => value(Scalar::Double, 2, 2),
+            epoxy::DOUBLE_MAT3 => value(Scalar::Double, 3, 3),
+            epoxy::DOUBLE_MAT4 => value(Scalar::Double, 4, 4),
+            epoxy::SAMPLER_2D => Self::Opaque("sampler2D"),
+            epoxy::SAMPLER_3D => Self::Opaque("sampler3D"),
+            epoxy::SAMPLER_CUBE => Self::Opaque("samplerCube"),
+            epoxy::SAMPLER_2D_SHADOW => Self::Opaque("sampler2DShadow"),
+            epoxy::SAMPLER_2D_ARRAY => Self::Opaque("sampler2DArray"),
+            epoxy::INT_SAMPLER_2D => Self::Opaque("isampler2D"),
+            epoxy::UNSIGNED_INT_SAMPLER_2D => Self::Opaque("usampler2D"),
+            epoxy::IMAGE_2D => Self::Opaque("image2D"),
+            epoxy::IMAGE_3D => Self::Opaque("image3D"),
+            ty => Self::Unknown(ty),
+        }
+    }
+
+    /// Returns the GLSL name of the type.
+    pub fn name(self) -> String {
+        match self {
+            Self::Value {
+                scalar,
+                columns: 1,
+                rows: 1,
+            } => scalar.name().to_owned(),
+            Self::Value {
+                scalar,
+                columns: 1,
+                rows,
+            } => format!("{}vec{rows}", scalar.prefix()),
+            Self::Value {
+                scalar,
+                columns,
+                rows,
+            } if columns == rows => format!("{}mat{columns}", scalar.prefix()),
+            Self::Value {
+                scalar,
+                columns,
+                rows,
+            } => format!("{}mat{columns}x{rows}", scalar.prefix()),
+            Self::Opaque(name) => name.to_owned(),
+            Self::Unknown(ty) => format!("type 0x{ty:04X}"),
+        }
+    }
+
+    /// Returns whether the type is a sampler or an image.
+    pub fn is_opaque(self) -> bool {
+        matches!(self, Self::Opaque(_))
+    }
+}
+
+/// Who supplies the value of a uniform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, glib::Enum)]
+#[enum_type(name = "GtkGlShadersUniformSource")]
+pub enum UniformSource {
+    /// Nobody: the uniform keeps its default value
+    Unset,
+    /// The user, with a setter or the initial uniforms
+    User,
+    /// The widget, as a builtin or a sampler bound to a texture unit
+    Widget,
+}
+
+/// A uniform used by the shaders, as listed by
+/// [`ShaderArea::list_uniforms`](super::ShaderArea::list_uniforms).
+#[derive(Debug, Clone, PartialEq)]
+pub struct UniformInfo {
+    /// Name of the uniform (of the array, for arrays)
+    pub name: String,
+    /// GLSL type, of an element for arrays
+    pub type_name: String,
+    /// Number of elements, 1 unless it is an array
+    pub size: i32,
+    /// Current value, if known (builtins change on every frame)
+    pub value: Option<Uniform>,
+    /// Who supplies the value
+    pub source: UniformSource,
+}
+
+impl UniformInfo {
+    /// Returns the uniform as a `GVariant` of type `a{sv}`, with the keys
+    /// `name`, `type`, `size`, `source` (a [`UniformSource`] value) and
+    /// `value` if known, typed like the uniforms passed to
+    /// [`ShaderArea::new`](super::ShaderArea::new).
+    pub fn to_variant(&self) -> Variant {
+        let dict = VariantDict::new(None);
+        dict.insert("name", &self.name);
+        dict.insert("type", &self.type_name);
+        dict.insert("size", self.size);
+        dict.insert("source", self.source.into_glib());
+        if let Some(value) = &self.value {
+            dict.insert_value("value", &value.to_variant());
+        }
+        dict.end()
+    }
+}
+
+/// A uniform used by a program, as reported by the driver.
+#[derive(Debug, Clone, Copy)]
+pub struct ActiveUniform {
+    /// Declared type (of an element, for arrays)
+    pub ty: GlslType,
+    /// Number of elements, 1 unless it is an array
+    pub size: i32,
+    /// Whether it is declared as an array, even of a single element
+    pub array: bool,
+}
+
+impl ActiveUniform {
+    /// Returns the GLSL name of the declared type, with the array size.
+    pub fn type_name(&self) -> String {
+        if self.array {
+            format!("{}[{}]", self.ty.name(), self.size)
+        } else {
+            self.ty.name()
+        }
+    }
+
+    /// Converts a value to the declared type.
+    ///
+    /// Arrays longer than the declared size are kept whole, to be rejected by
+    /// the caller.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError::Uniform`] naming the uniform and both types if
+    /// the value can't be converted.
+    pub fn coerce(&self, name: &str, value: &Uniform) -> Result<Uniform, glib::Error> {
+        let (scalar, components) = value.components();
+        let error = |reason: &str| {
+            glib::Error::new(
+                ShaderError::Uniform,
+                &format!(
+                    "Can't convert uniform `{name}` from {} to {}, as declared by the shader{reason}",
+                    value.type_name(),
+                    self.type_name()
+                ),
+            )
+        };
+
+        let (target, width) = match self.ty {
+            GlslType::Value {
+                scalar,
+                columns,
+                rows,
+            } => (scalar, columns * rows),
+            // Texture and image units
+            GlslType::Opaque(_) => (Scalar::Int, 1),
+            GlslType::Unknown(_) => return Err(error("")),
+        };
+        if scalar == target && value.type_name() == self.type_name() {
+            return Ok(value.clone());
+        }
+
+        let values: Vec<f64> = components
+            .iter()
+            .map(|&v| target.convert(v))
+            .collect::<Option<_>>()
+            .ok_or_else(|| error(&format!(" (a component doesn't fit in {})", target.name())))?;
+        let count_matches = if self.array {
+            !values.is_empty() && values.len().is_multiple_of(width)
+        } else {
+            values.len() == width
+        };
+        if !count_matches {
+            return Err(error(""));
+        }
+
+        build(self.ty, target, self.array, &values).ok_or_else(|| error(" (not supported)"))
+    }
+}
+
+/// Builds a value of a declared type from converted components.
+fn build(ty: GlslType, scalar: Scalar, array: bool, values: &[f64]) -> Option<Uniform> {
+    let floats = || values.iter().map(|&v| v as f32).collect::<Vec<_>>();
+    let ints = || values.iter().map(|&v| v as i32).collect::<Vec<_>>();
+    let (columns, width) = match ty {
+        GlslType::Value { columns, rows, .. } => (columns, columns * rows),
+        _ => (1, 1),
+    };
+
+    if array {
+        // Matrix arrays have no variant
+        let uints = || values.iter().map(|&v| v as u32).collect::<Vec<_>>();
+        return match scalar {
+            _ if columns != 1 => None,
+            Scalar::Float => Uniform::float_array(width, &floats()),
+            Scalar::Double => Uniform::double_array(width, values),
+            Scalar::Int | Scalar::Bool => Uniform::int_array(width, &ints()),
+            Scalar::UInt => Uniform::uint_array(width, &uints()),
+        };
+    }
+
+    let u = |i: usize| values[i] as u32;
+    let d = |i: usize| values[i];
+    Some(match (scalar, columns, values.len()) {
+        (Scalar::Float, 1, 1) => Uniform::Float(floats()[0]),
+        (Scalar::Float, 1, 2) => Uniform::Vec2(floats().try_into().ok()?),
+        (Scalar::Float, 1, 3) => Uniform::Vec3(floats().try_into().ok()?),
+        (Scalar::Float, 1, 4) => Uniform::Vec4(floats().try_into().ok()?),
+        (Scalar::Float, 2, 4) => Uniform::Mat2(floats().try_into().ok()?),
+        (Scalar::Float, 3, 9) => Uniform::Mat3(floats().try_into().ok()?),
+        (Scalar::Float, 4, 16) => Uniform::Mat4(floats().try_into().ok()?),
+        // Boolean vectors are set with the integer functions
+        (Scalar::Int, 1, 1) => Uniform::Int(ints()[0]),
+        (Scalar::Bool, 1, 1) => Uniform::Bool(values[0] != 0.0),
+        (Scalar::Int | Scalar::Bool, 1, 2) => Uniform::IVec2(ints().try_into().ok()?),
+        (Scalar::Int | Scalar::Bool, 1, 3) => Uniform::IVec3(ints().try_into().ok()?),
+        (Scalar::Int | Scalar::Bool, 1, 4) => Uniform::IVec4(ints().try_into().ok()?),
+        (Scalar::UInt, 1, 1) => Uniform::UInt(u(0)),
+        (Scalar::UInt, 1, 2) => Uniform::UVec2([u(0), u(1)]),
+        (Scalar::UInt, 1, 3) => Uniform::UVec3([u(0), u(1), u(2)]),
+        (Scalar::UInt, 1, 4) => Uniform::UVec4([u(0), u(1), u(2), u(3)]),
+        (Scalar::Double, 1, 1) => Uniform::Double(d(0)),
+        (Scalar::Double, 1, 2) => Uniform::DVec2([d(0), d(1)]),
+        (Scalar::Double, 1, 3) => Uniform::DVec3([d(0), d(1), d(2)]),
+        (Scalar::Double, 1, 4) => Uniform::DVec4([d(0), d(1), d(2), d(3)]),
+        _ => return None,
+    })
+}
+
+impl Uniform {
+    /// Returns the type of the components of the value, and every component
+    /// (of every element, for arrays).
+    fn components(&self) -> (Scalar, Vec<f64>) {
+        fn all<T: Copy + Into<f64>>(values: &[T]) -> Vec<f64> {
+            values.iter().map(|&v| v.into()).collect()
+        }
+
+        match self {
+            Self::Float(v) => (Scalar::Float, all(&[*v])),
+            Self::Vec2(v) => (Scalar::Float, all(v)),
+            Self::Vec3(v) => (Scalar::Float, all(v)),
+            Self::Vec4(v) => (Scalar::Float, all(v)),
+            Self::Mat2(v) => (Scalar::Float, all(v)),
+            Self::Mat3(v) => (Scalar::Float, all(v)),
+            Self::Mat4(v) => (Scalar::Float, all(v)),
+            Self::FloatArray(v) => (Scalar::Float, all(v)),
+            Self::Vec2Array(v) => (Scalar::Float, all(v.as_flattened())),
+            Self::Vec3Array(v) => (Scalar::Float, all(v.as_flattened())),
+            Self::Vec4Array(v) => (Scalar::Float, all(v.as_flattened())),
+            Self::Int(v) => (Scalar::Int, all(&[*v])),
+            Self::IVec2(v) => (Scalar::Int, all(v)),
+            Self::IVec3(v) => (Scalar::Int, all(v)),
+            Self::IVec4(v) => (Scalar::Int, all(v)),
+            Self::IntArray(v) => (Scalar::Int, all(v)),
+            Self::IVec2Array(v) => (Scalar::Int, all(v.as_flattened())),
+            Self::IVec3Array(v) => (Scalar::Int, all(v.as_flattened())),
+            Self::IVec4Array(v) => (Scalar::Int, all(v.as_flattened())),
+            Self::UIntArray(v) => (Scalar::UInt, all(v)),
+            Self::UVec2Array(v) => (Scalar::UInt, all(v.as_flattened())),
+            Self::UVec3Array(v) => (Scalar::UInt, all(v.as_flattened())),
+            Self::UVec4Array(v) => (Scalar::UInt, all(v.as_flattened())),
+            Self::DoubleArray(v) => (Scalar::Double, v.clone()),
+            Self::DVec2Array(v) => (Scalar::Double, v.as_flattened().to_vec()),
+            Self::DVec3Array(v) => (Scalar::Double, v.as_flattened().to_vec()),
+            Self::DVec4Array(v) => (Scalar::Double, v.as_flattened().to_vec()),
+            Self::UInt(v) => (Scalar::UInt, all(&[*v])),
+            Self::UVec2(v) => (Scalar::UInt, all(v)),
+            Self::UVec3(v) => (Scalar::UInt, all(v)),
+            Self::UVec4(v) => (Scalar::UInt, all(v)),
+            Self::Double(v) => (Scalar::Double, vec![*v]),
+            Self::DVec2(v) => (Scalar::Double, v.to_vec()),
+            Self::DVec3(v) => (Scalar::Double, v.to_vec()),
+            Self::DVec4(v) => (Scalar::Double, v.to_vec()),
+            Self::Bool(v) => (Scalar::Bool, vec![f64::from(u8::from(*v))]),
+        }
+    }
+
+    /// Returns the value as a `GVariant`, with matrices and arrays of vectors
+    /// as nested arrays.
+    pub fn to_variant(&self) -> Variant {
+        fn doubles<T: Copy + Into<f64>>(values: &[T]) -> Vec<f64> {
+            values.iter().map(|&v| v.into()).collect()
+        }
+        fn nested<T: Copy + Into<f64>, const N: usize>(values: &[[T; N]]) -> Vec<Vec<f64>> {
+            values.iter().map(|v| doubles(v)).collect()
+        }
+        fn vectors<T: Copy, const N: usize>(values: &[[T; N]]) -> Vec<Vec<T>> {
+            values.iter().map(|v| v.to_vec()).collect()
+        }
+        fn columns<const N: usize>(values: &[f32]) -> Vec<Vec<f64>> {
+            values.chunks_exact(N).map(doubles).collect()
+        }
+
+        match self {
+            Self::Float(v) => f64::from(*v).to_variant(),
+            Self::Vec2(v) => doubles(v).to_variant(),
+            Self::Vec3(v) => doubles(v).to_variant(),
+            Self::Vec4(v) => doubles(v).to_variant(),
+            Self::Int(v) => v.to_variant(),
+            Self::IVec2(v) => v.to_vec().to_variant(),
+            Self::IVec3(v) => v.to_vec().to_variant(),
+            Self::IVec4(v) => v.to_vec().to_variant(),
+            Self::Bool(v) => v.to_variant(),
+            Self::UInt(v) => v.to_variant(),
+            Self::UVec2(v) => v.to_vec().to_variant(),
+            Self::UVec3(v) => v.to_vec().to_variant(),
+            Self::UVec4(v) => v.to_vec().to_variant(),
+            Self::Double(v) => v.to_variant(),
+            Self::DVec2(v) => v.to_vec().to_variant(),
+            Self::DVec3(v) => v.to_vec().to_variant(),
+            Self::DVec4(v) => v.to_vec().to_variant(),
+            Self::Mat2(v) => columns::<2>(v).to_variant(),
+            Self::Mat3(v) => columns::<3>(v).to_variant(),
+            Self::Mat4(v) => columns::<4>(v).to_variant(),
+            Self::FloatArray(v) => doubles(v).to_variant(),
+            Self::Vec2Array(v) => nested(v).to_variant(),
+            Self::Vec3Array(v) => nested(v).to_variant(),
+            Self::Vec4Array(v) => nested(v).to_variant(),
+            Self::IntArray(v) => v.to_variant(),
+            Self::IVec2Array(v) => vectors(v).to_variant(),
+            Self::IVec3Array(v) => vectors(v).to_variant(),
+            Self::IVec4Array(v) => vectors(v).to_variant(),
+            Self::UIntArray(v) => v.to_variant(),
+            Self::UVec2Array(v) => vectors(v).to_variant(),
+            Self::UVec3Array(v) => vectors(v).to_variant(),
+            Self::UVec4Array(v) => vectors(v).to_variant(),
+            Self::DoubleArray(v) => v.to_variant(),
+            Self::DVec2Array(v) => vectors(v).to_variant(),
+            Self::DVec3Array(v) => vectors(v).to_variant(),
+            Self::DVec4Array(v) => vectors(v).to_variant(),
+        }
+    }
+
+    /// Returns the GLSL name of the type of the value, with the number of
+    /// elements for arrays.
+    pub fn type_name(&self) -> String {
+        let element = match self {
+            Self::Float(_) | Self::FloatArray(_) => "float",
+            Self::Vec2(_) | Self::Vec2Array(_) => "vec2",
+            Self::Vec3(_) | Self::Vec3Array(_) => "vec3",
+            Self::Vec4(_) | Self::Vec4Array(_) => "vec4",
+            Self::Int(_) | Self::IntArray(_) => "int",
+            Self::IVec2(_) | Self::IVec2Array(_) => "ivec2",
+            Self::IVec3(_) | Self::IVec3Array(_) => "ivec3",
+            Self::IVec4(_) | Self::IVec4Array(_) => "ivec4",
+            Self::UInt(_) | Self::UIntArray(_) => "uint",
+            Self::UVec2(_) | Self::UVec2Array(_) => "uvec2",
+            Self::UVec3(_) | Self::UVec3Array(_) => "uvec3",
+            Self::UVec4(_) | Self::UVec4Array(_) => "uvec4",
+            Self::Double(_) | Self::DoubleArray(_) => "double",
+            Self::DVec2(_) | Self::DVec2Array(_) => "dvec2",
+            Self::DVec3(_) | Self::DVec3Array(_) => "dvec3",
+            Self::DVec4(_) | Self::DVec4Array(_) => "dvec4",
+            Self::Bool(_) => "bool",
+            Self::Mat2(_) => "mat2",
+            Self::Mat3(_) => "mat3",
+            Self::Mat4(_) => "mat4",
+        };
+        match self.array_len() {
+            Some(len) => format!("{element}[{len}]"),
+            None => element.to_owned(),
+        }
+    }
+}
+
+/// Returns every uniform of the default block a program uses, by name
+/// (without the `[0]` the driver appends to arrays).
+///
+/// # Safety
+///
+/// This function calls unsafe OpenGL functions. An active OpenGL context
+/// must be bound before calling.
+pub unsafe fn active_uniforms(program: u32) -> HashMap<String, ActiveUniform> {
+    let mut uniforms = HashMap::new();
+    unsafe {
+        let mut count = 0;
+        epoxy::GetProgramiv(program, epoxy::ACTIVE_UNIFORMS, &raw mut count);
+        let mut max_length = 0;
+        epoxy::GetProgramiv(
+            program,
+            epoxy::ACTIVE_UNIFORM_MAX_LENGTH,
+            &raw mut max_length,
+        );
+        let mut name = vec![0u8; max_length.max(1) as usize];
+        for i in 0..count.max(0) as u32 {
+            let (mut length, mut size, mut ty) = (0, 0, 0);
+            epoxy::GetActiveUniform(
+                program,
+                i,
+                name.len() as i32,
+                &raw mut length,
+                &raw mut size,
+                &raw mut ty,
+                name.as_mut_ptr().cast(),
+            );
+            // Members of uniform blocks are set through their buffer
+            let mut block = -1;
+            epoxy::GetActiveUniformsiv(
+                program,
+                1,
+                &raw const i,
+                epoxy::UNIFORM_BLOCK_INDEX,
+                &raw mut block,
+            );
+            if block != -1 {
+                continue;
+            }
+
+            let full = &name[..length.max(0) as usize];
+            // Arrays are reported under the name of their first element
+            let (name, array) = match full.strip_suffix(b"[0]") {
+                Some(array) => (array, true),
+                None => (full, false),
+            };
+            uniforms.insert(
+                String::from_utf8_lossy(name).into_owned(),
+                ActiveUniform {
+                    ty: GlslType::from_gl(ty),
+                    size,
+                    array,
+                },
+            );
+        }
+    }
+    uniforms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn declared(scalar: Scalar, columns: usize, rows: usize, size: i32) -> ActiveUniform {
+        ActiveUniform {
+            ty: GlslType::Value {
+                scalar,
+                columns,
+                rows,
+            },
+            size,
+            array: size > 1,
+        }
+    }
+
+    fn coerce(uniform: ActiveUniform, value: Uniform) -> Result<Uniform, String> {
+        uniform.coerce("u", &value).map_err(|e| {
+            assert!(e.matches(ShaderError::Uniform));
+            e.message().to_owned()
+        })
+    }
+
+    #[test]
+    fn keeps_matching_values() {
+        let value = Uniform::Vec3([0.1, 0.2, 0.3]);
+        assert_eq!(
+            coerce(declared(Scalar::Float, 1, 3, 1), value.clone()),
+            Ok(value)
+        );
+        let value = Uniform::Vec2Array(vec![[1.0, 2.0], [3.0, 4.0]]);
+        assert_eq!(
+            coerce(declared(Scalar::Float, 1, 2, 2), value.clone()),
+            Ok(value)
+        );
+    }
+
+    #[test]
+    fn converts_scalars() {
+        let float = declared(Scalar::Float, 1, 1, 1);
+        let int = declared(Scalar::Int, 1, 1, 1);
+        let uint = declared(Scalar::UInt, 1, 1, 1);
+        let double = declared(Scalar::Double, 1, 1, 1);
+        let bool = declared(Scalar::Bool, 1, 1, 1);
+
+        assert_eq!(coerce(int, Uniform::Float(-3.0)), Ok(Uniform::Int(-3)));
+        assert_eq!(coerce(float, Uniform::Int(2)), Ok(Uniform::Float(2.0)));
+        assert_eq!(coerce(uint, Uniform::Int(7)), Ok(Uniform::UInt(7)));
+        assert_eq!(coerce(int, Uniform::UInt(7)), Ok(Uniform::Int(7)));
+        assert_eq!(coerce(bool, Uniform::Float(0.5)), Ok(Uniform::Bool(true)));
+        assert_eq!(coerce(bool, Uniform::Int(0)), Ok(Uniform::Bool(false)));
+        assert_eq!(coerce(int, Uniform::Bool(true)), Ok(Uniform::Int(1)));
+        // Doubles keep their precision, and are narrowed for floats
+        assert_eq!(
+            coerce(double, Uniform::Float(0.5)),
+            Ok(Uniform::Double(0.5))
+        );
+        assert_eq!(coerce(float, Uniform::Double(0.1)), Ok(Uniform::Float(0.1)));
+        assert_eq!(
+            coerce(double, Uniform::Double(0.1 + 1e-12)),
+            Ok(Uniform::Double(0.1 + 1e-12))
+        );
+    }
+
+    #[test]
+    fn rejects_components_out_of_range() {
+        let int = declared(Scalar::Int, 1, 1, 1);
+        let uint = declared(Scalar::UInt, 1, 1, 1);
+        assert_eq!(
+            coerce(int, Uniform::Float(2.5)),
+            Err(
+                "Can't convert uniform `u` from float to int, as declared by the shader \
+                 (a component doesn't fit in int)"
+                    .to_owned()
+            )
+        );
+        assert!(coerce(int, Uniform::Double(f64::from(i32::MAX) + 1.0)).is_err());
+        assert!(coerce(int, Uniform::UInt(u32::MAX)).is_err());
+        assert!(coerce(uint, Uniform::Int(-1)).is_err());
+        assert!(coerce(uint, Uniform::Float(f32::NAN)).is_err());
+        assert_eq!(
+            coerce(uint, Uniform::Double(f64::from(u32::MAX))),
+            Ok(Uniform::UInt(u32::MAX))
+        );
+    }
+
+    #[test]
+    fn regroups_components() {
+        assert_eq!(
+            coerce(
+                declared(Scalar::Float, 2, 2, 1),
+                Uniform::Vec4([1.0, 2.0, 3.0, 4.0])
+            ),
+            Ok(Uniform::Mat2([1.0, 2.0, 3.0, 4.0]))
+        );
+        // Boolean vectors are set as integers
+        assert_eq!(
+            coerce(declared(Scalar::Bool, 1, 2, 1), Uniform::Vec2([2.0, 0.0])),
+            Ok(Uniform::IVec2([1, 0]))
+        );
+        assert_eq!(
+            coerce(declared(Scalar::Double, 1, 3, 1), Uniform::IVec3([1, 2, 3])),
+            Ok(Uniform::DVec3([1.0, 2.0, 3.0]))
+        );
+        assert_eq!(
+            coerce(declared(Scalar::Float, 1, 4, 1), Uniform::Vec3([0.0; 3])),
+            Err(
+                "Can't convert uniform `u` from vec3 to vec4, as declared by the shader".to_owned()
+            )
+        );
+        assert!(coerce(declared(Scalar::Float, 1, 1, 1), Uniform::Vec2([0.0; 2])).is_err());
+        // Non-square matrices have no variant
+        assert_eq!(
+            coerce(
+                declared(Scalar::Float, 2, 3, 1),
+                Uniform::FloatArray(vec![0.0; 6])
+            ),
+            Err(
+                "Can't convert uniform `u` from float[6] to mat2x3, as declared by the shader \
+                 (not supported)"
+                    .to_owned()
+            )
+        );
+    }
+
+    #[test]
+    fn regroups_arrays() {
+        let vec2s = declared(Scalar::Float, 1, 2, 4);
+        assert_eq!(vec2s.type_name(), "vec2[4]");
+        assert_eq!(
+            coerce(vec2s, Uniform::IntArray(vec![1, 2, 3, 4])),
+            Ok(Uniform::Vec2Array(vec![[1.0, 2.0], [3.0, 4.0]]))
+        );
+        assert!(coerce(vec2s, Uniform::FloatArray(vec![0.0; 7])).is_err());
+        assert!(coerce(vec2s, Uniform::FloatArray(Vec::new())).is_err());
+        // Extra elements are left to the caller
+        assert_eq!(
+            coerce(vec2s, Uniform::FloatArray(vec![0.0; 12])),
+            Ok(Uniform::Vec2Array(vec![[0.0; 2]; 6]))
+        );
+
+        assert_eq!(
+            coerce(
+                declared(Scalar::UInt, 1, 1, 2),
+                Uniform::IntArray(vec![1, 2])
+            ),
+            Ok(Uniform::UIntArray(vec![1, 2]))
+        );
+        assert_eq!(
+            coerce(
+                declared(Scalar::Double, 1, 2, 2),
+                Uniform::Vec4([1.0, 2.0, 3.0, 4.0])
+            ),
+            Ok(Uniform::DVec2Array(vec![[1.0, 2.0], [3.0, 4.0]]))
+        );
+        assert_eq!(
+            coerce(
+                declared(Scalar::Bool, 1, 1, 3),
+                Uniform::FloatArray(vec![0.0, 1.0, 2.0])
+            ),
+            Ok(Uniform::IntArray(vec![0, 1, 1]))
+        );
+        // A single-element array is still an array
+        let single = ActiveUniform {
+            array: true,
+            ..declared(Scalar::Float, 1, 1, 1)
+        };
+        assert_eq!(
+            coerce(single, Uniform::Int(1)),
+            Ok(Uniform::FloatArray(vec![1.0]))
+        );
+        // Matrix arrays have no variant
+        assert!(
+            coerce(
+                declared(Scalar::Float, 2, 2, 2),
+                Uniform::FloatArray(vec![0.0; 8])
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn sets_units_of_opaque_types() {
+        let sampler = ActiveUniform {
+            ty: GlslType::Opaque("sampler2D"),
+            size: 1,
+            array: false,
+        };
+        assert_eq!(coerce(sampler, Uniform::Float(2.0)), Ok(Uniform::Int(2)));
+        assert!(coerce(sampler, Uniform::Float(-1.5)).is_err());
+
+        let unknown = ActiveUniform {
+            ty: GlslType::Unknown(0x1234),
+            ..sampler
+        };
+        assert_eq!(
+            coerce(unknown, Uniform::Int(0)),
+            Err(
+                "Can't convert uniform `u` from int to type 0x1234, as declared by the shader"
+                    .to_owned()
+            )
+        );
+    }
+}
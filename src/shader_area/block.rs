@@ -0,0 +1,432 @@
+//! Uniform blocks.
+//!
+//! A named uniform block, such as `layout(std140) uniform Params { … };`,
+//! is backed by a uniform buffer shared by every shader that declares it,
+//! so that dozens of parameters are uploaded at once rather than looked up
+//! and set one by one. Its contents are given either as bytes, laid out by
+//! the application (usually following the `std140` rules), or as member
+//! values, packed by the widget at the offsets the driver reports for the
+//! block's members.
+
+use std::collections::HashMap;
+
+use gtk::glib;
+
+use super::{
+    ShaderError, Uniform,
+    active::{ActiveUniform, GlslType},
+    pass::is_identifier,
+};
+
+/// The contents of a uniform block.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockContents {
+    /// Raw bytes, copied from the start of the buffer
+    Bytes(glib::Bytes),
+    /// Values of the block's members, by name
+    Members(HashMap<String, Uniform>),
+}
+
+/// A named uniform block with its contents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UniformBlock {
+    /// Name of the block in the shaders (not the instance name)
+    pub name: String,
+    /// Contents of the block
+    pub contents: BlockContents,
+}
+
+impl UniformBlock {
+    /// Checks that the block has a valid name and some contents.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError::Uniform`] otherwise.
+    pub fn validate(&self) -> Result<(), glib::Error> {
+        if !is_identifier(&self.name) {
+            return Err(glib::Error::new(
+                ShaderError::Uniform,
+                &format!("Invalid uniform block name `{}`", self.name),
+            ));
+        }
+        let empty = match &self.contents {
+            BlockContents::Bytes(data) => data.is_empty(),
+            BlockContents::Members(members) => members.is_empty(),
+        };
+        if empty {
+            return Err(glib::Error::new(
+                ShaderError::Uniform,
+                &format!("Uniform block `{}` is empty", self.name),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Assigns the block of `program` to a binding point.
+    ///
+    /// # Returns
+    ///
+    /// Whether the program declares the block.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    pub unsafe fn bind(&self, program: u32, binding: u32) -> bool {
+        unsafe {
+            let index = self.index(program);
+            if index != epoxy::INVALID_INDEX {
+                epoxy::UniformBlockBinding(program, index, binding);
+            }
+            index != epoxy::INVALID_INDEX
+        }
+    }
+
+    /// Fills a uniform buffer with the contents of the block, laid out for
+    /// the first of `programs` that declares it (blocks with the `std140`
+    /// or `shared` layout are the same in every program).
+    ///
+    /// # Returns
+    ///
+    /// Whether one of the programs declares the block.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    pub unsafe fn upload(&self, buffer: u32, programs: impl IntoIterator<Item = u32>) -> bool {
+        unsafe {
+            let Some((program, index)) = programs
+                .into_iter()
+                .map(|program| (program, self.index(program)))
+                .find(|&(_, index)| index != epoxy::INVALID_INDEX)
+            else {
+                return false;
+            };
+            let mut size = 0;
+            epoxy::GetActiveUniformBlockiv(
+                program,
+                index,
+                epoxy::UNIFORM_BLOCK_DATA_SIZE,
+                &raw mut size,
+            );
+            let data = self.pack(program, index, size.max(0) as usize);
+
+            epoxy::BindBuffer(epoxy::UNIFORM_BUFFER, buffer);
+            epoxy::BufferData(
+                epoxy::UNIFORM_BUFFER,
+                data.len() as isize,
+                data.as_ptr().cast(),
+                epoxy::DYNAMIC_DRAW,
+            );
+            epoxy::BindBuffer(epoxy::UNIFORM_BUFFER, 0);
+            true
+        }
+    }
+
+    /// Returns the index of the block in `program`, or
+    /// [`epoxy::INVALID_INDEX`] if the program doesn't declare it.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    unsafe fn index(&self, program: u32) -> u32 {
+        let name_c = format!("{}\0", self.name);
+        unsafe { epoxy::GetUniformBlockIndex(program, name_c.as_ptr().cast::<i8>()) }
+    }
+
+    /// Lays out the contents of the block for a buffer of `size` bytes,
+    /// using the member offsets of `program`, which declares the block at
+    /// `index`.
+    ///
+    /// Members are converted to their declared type. Bytes beyond `size`,
+    /// unknown members, values that can't be converted and arrays longer than
+    /// declared are dropped with a warning.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    unsafe fn pack(&self, program: u32, index: u32, size: usize) -> Vec<u8> {
+        let mut buffer = vec![0u8; size];
+        match &self.contents {
+            BlockContents::Bytes(data) => {
+                if data.len() > size {
+                    log::warn!(
+                        "Uniform block `{}` has {} bytes, but the shader declares {size}",
+                        self.name,
+                        data.len()
+                    );
+                }
+                let len = data.len().min(size);
+                buffer[..len].copy_from_slice(&data[..len]);
+            }
+            BlockContents::Members(members) => {
+                for (name, value) in members {
+                    let Some(layout) = (unsafe { self.member_layout(program, index, name) }) else {
+                        log::warn!("Uniform block `{}` has no member `{name}`", self.name);
+                        continue;
+                    };
+                    // Members are converted like any uniform
+                    match layout.member.coerce(name, value) {
+                        // Extra elements would overwrite the following members
+                        Ok(value)
+                            if value
+                                .array_len()
+                                .is_some_and(|len| len > layout.element_count()) =>
+                        {
+                            log::error!(
+                                "Uniform block member `{name}` has {} elements, but the shader declares {}",
+                                value.array_len().unwrap_or_default(),
+                                layout.element_count()
+                            );
+                        }
+                        Ok(value) => layout.write(&mut buffer, &value),
+                        Err(e) => log::error!("{e}"),
+                    }
+                }
+            }
+        }
+        buffer
+    }
+
+    /// Queries where a member of the block lives in its buffer.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    unsafe fn member_layout(&self, program: u32, index: u32, name: &str) -> Option<MemberLayout> {
+        // Members of blocks with an instance name are prefixed by the block
+        // name, and arrays are reported as their first element
+        let candidates = [
+            name.to_owned(),
+            format!("{name}[0]"),
+            format!("{}.{name}", self.name),
+            format!("{}.{name}[0]", self.name),
+        ];
+        unsafe {
+            for candidate in candidates {
+                let array = candidate.ends_with("[0]");
+                let name_c = format!("{candidate}\0");
+                let name_ptr = name_c.as_ptr().cast::<i8>();
+                let mut member = epoxy::INVALID_INDEX;
+                epoxy::GetUniformIndices(program, 1, &raw const name_ptr, &raw mut member);
+                if member == epoxy::INVALID_INDEX {
+                    continue;
+                }
+
+                let query = |pname| {
+                    let mut value = 0;
+                    epoxy::GetActiveUniformsiv(
+                        program,
+                        1,
+                        &raw const member,
+                        pname,
+                        &raw mut value,
+                    );
+                    value
+                };
+                // A uniform of the default block with the same name
+                if query(epoxy::UNIFORM_BLOCK_INDEX) != index as i32 {
+                    continue;
+                }
+                return Some(MemberLayout {
+                    member: ActiveUniform {
+                        ty: GlslType::from_gl(query(epoxy::UNIFORM_TYPE) as u32),
+                        size: query(epoxy::UNIFORM_SIZE),
+                        array,
+                    },
+                    offset: query(epoxy::UNIFORM_OFFSET).max(0) as usize,
+                    array_stride: query(epoxy::UNIFORM_ARRAY_STRIDE).max(0) as usize,
+                    matrix_stride: query(epoxy::UNIFORM_MATRIX_STRIDE).max(0) as usize,
+                    row_major: query(epoxy::UNIFORM_IS_ROW_MAJOR) != 0,
+                });
+            }
+        }
+        None
+    }
+}
+
+/// Where a member of a uniform block lives in its buffer, in bytes.
+#[derive(Debug, Clone, Copy)]
+struct MemberLayout {
+    /// Declared type of the member
+    member: ActiveUniform,
+    /// Offset of the member, or of its first element
+    offset: usize,
+    /// Distance between array elements
+    array_stride: usize,
+    /// Distance between matrix columns, or rows if `row_major`
+    matrix_stride: usize,
+    /// Whether a matrix is stored row by row, as with `layout(row_major)`
+    row_major: bool,
+}
+
+impl MemberLayout {
+    /// Returns the number of elements of the member, 1 unless it is an array.
+    fn element_count(self) -> usize {
+        self.member.size.max(1) as usize
+    }
+
+    /// Writes a value into the buffer of the block, dropping elements past
+    /// the declared size and anything that falls outside of the buffer.
+    fn write(self, buffer: &mut [u8], value: &Uniform) {
+        let (elements, columns) = components(value);
+        for (i, element) in elements.iter().take(self.element_count()).enumerate() {
+            let base = self.offset + i * self.array_stride;
+            let rows = element.len() / columns;
+            for (j, component) in element.iter().enumerate() {
+                // Components are given column by column
+                let size = component.len();
+                let (column, row) = (j / rows, j % rows);
+                let at = if self.row_major {
+                    base + row * self.matrix_stride + column * size
+                } else {
+                    base + column * self.matrix_stride + row * size
+                };
+                if let Some(destination) = buffer.get_mut(at..at + size) {
+                    destination.copy_from_slice(component);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the native-endian bytes of every component of each element of a
+/// value (a single one unless it is an array), with the number of matrix
+/// columns of an element.
+///
+/// Booleans are 32-bit integers in uniform blocks, and doubles take 8 bytes.
+fn components(value: &Uniform) -> (Vec<Vec<Vec<u8>>>, usize) {
+    fn bytes<T: Copy, const N: usize>(
+        values: &[T],
+        to_bytes: impl Fn(T) -> [u8; N],
+    ) -> Vec<Vec<u8>> {
+        values.iter().map(|&v| to_bytes(v).to_vec()).collect()
+    }
+    fn floats(values: &[f32]) -> Vec<Vec<u8>> {
+        bytes(values, f32::to_ne_bytes)
+    }
+    fn ints(values: &[i32]) -> Vec<Vec<u8>> {
+        bytes(values, i32::to_ne_bytes)
+    }
+    fn uints(values: &[u32]) -> Vec<Vec<u8>> {
+        bytes(values, u32::to_ne_bytes)
+    }
+    fn doubles(values: &[f64]) -> Vec<Vec<u8>> {
+        bytes(values, f64::to_ne_bytes)
+    }
+
+    match value {
+        Uniform::Float(v) => (vec![floats(&[*v])], 1),
+        Uniform::Vec2(v) => (vec![floats(v)], 1),
+        Uniform::Vec3(v) => (vec![floats(v)], 1),
+        Uniform::Vec4(v) => (vec![floats(v)], 1),
+        Uniform::Int(v) => (vec![ints(&[*v])], 1),
+        Uniform::IVec2(v) => (vec![ints(v)], 1),
+        Uniform::IVec3(v) => (vec![ints(v)], 1),
+        Uniform::IVec4(v) => (vec![ints(v)], 1),
+        Uniform::Bool(v) => (vec![ints(&[i32::from(*v)])], 1),
+        Uniform::UInt(v) => (vec![uints(&[*v])], 1),
+        Uniform::UVec2(v) => (vec![uints(v)], 1),
+        Uniform::UVec3(v) => (vec![uints(v)], 1),
+        Uniform::UVec4(v) => (vec![uints(v)], 1),
+        Uniform::Double(v) => (vec![doubles(&[*v])], 1),
+        Uniform::DVec2(v) => (vec![doubles(v)], 1),
+        Uniform::DVec3(v) => (vec![doubles(v)], 1),
+        Uniform::DVec4(v) => (vec![doubles(v)], 1),
+        Uniform::Mat2(v) => (vec![floats(v)], 2),
+        Uniform::Mat3(v) => (vec![floats(v)], 3),
+        Uniform::Mat4(v) => (vec![floats(v)], 4),
+        Uniform::FloatArray(v) => (v.iter().map(|v| floats(&[*v])).collect(), 1),
+        Uniform::Vec2Array(v) => (v.iter().map(|v| floats(v)).collect(), 1),
+        Uniform::Vec3Array(v) => (v.iter().map(|v| floats(v)).collect(), 1),
+        Uniform::Vec4Array(v) => (v.iter().map(|v| floats(v)).collect(), 1),
+        Uniform::IntArray(v) => (v.iter().map(|v| ints(&[*v])).collect(), 1),
+        Uniform::IVec2Array(v) => (v.iter().map(|v| ints(v)).collect(), 1),
+        Uniform::IVec3Array(v) => (v.iter().map(|v| ints(v)).collect(), 1),
+        Uniform::IVec4Array(v) => (v.iter().map(|v| ints(v)).collect(), 1),
+        Uniform::UIntArray(v) => (v.iter().map(|v| uints(&[*v])).collect(), 1),
+        Uniform::UVec2Array(v) => (v.iter().map(|v| uints(v)).collect(), 1),
+        Uniform::UVec3Array(v) => (v.iter().map(|v| uints(v)).collect(), 1),
+        Uniform::UVec4Array(v) => (v.iter().map(|v| uints(v)).collect(), 1),
+        Uniform::DoubleArray(v) => (v.iter().map(|v| doubles(&[*v])).collect(), 1),
+        Uniform::DVec2Array(v) => (v.iter().map(|v| doubles(v)).collect(), 1),
+        Uniform::DVec3Array(v) => (v.iter().map(|v| doubles(v)).collect(), 1),
+        Uniform::DVec4Array(v) => (v.iter().map(|v| doubles(v)).collect(), 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shader_area::active::Scalar;
+
+    fn layout(columns: usize, rows: usize, size: i32, row_major: bool) -> MemberLayout {
+        MemberLayout {
+            member: ActiveUniform {
+                ty: GlslType::Value {
+                    scalar: Scalar::Float,
+                    columns,
+                    rows,
+                },
+                size,
+                array: size > 1,
+            },
+            offset: 16,
+            array_stride: 16,
+            matrix_stride: 16,
+            row_major,
+        }
+    }
+
+    fn floats(buffer: &[u8]) -> Vec<f32> {
+        buffer
+            .chunks_exact(4)
+            .map(|b| f32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+            .collect()
+    }
+
+    #[test]
+    fn writes_array_elements_at_their_stride() {
+        // `float a[2]` at 16, with std140's 16-byte array stride
+        let mut buffer = vec![0; 64];
+        layout(1, 1, 2, false).write(&mut buffer, &Uniform::FloatArray(vec![1.0, 2.0]));
+        let mut expected = [0.0; 16];
+        expected[4] = 1.0;
+        expected[8] = 2.0;
+        assert_eq!(floats(&buffer), expected);
+    }
+
+    #[test]
+    fn drops_elements_past_the_declared_size() {
+        // `float a[2]` followed by a member where a third element would be
+        let mut buffer = vec![0; 64];
+        layout(1, 1, 2, false).write(&mut buffer, &Uniform::FloatArray(vec![1.0, 2.0, 3.0]));
+        assert_eq!(floats(&buffer)[12], 0.0);
+
+        // Nor past the end of the buffer
+        let mut buffer = vec![0; 24];
+        layout(1, 2, 2, false).write(&mut buffer, &Uniform::Vec2Array(vec![[1.0, 2.0]; 2]));
+        assert_eq!(floats(&buffer), [0.0, 0.0, 0.0, 0.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn writes_matrices_by_columns_or_rows() {
+        let matrix = Uniform::Mat2([1.0, 2.0, 3.0, 4.0]);
+
+        let mut buffer = vec![0; 48];
+        layout(2, 2, 1, false).write(&mut buffer, &matrix);
+        let floats_column_major = floats(&buffer);
+        assert_eq!(floats_column_major[4..6], [1.0, 2.0]);
+        assert_eq!(floats_column_major[8..10], [3.0, 4.0]);
+
+        let mut buffer = vec![0; 48];
+        layout(2, 2, 1, true).write(&mut buffer, &matrix);
+        let floats_row_major = floats(&buffer);
+        assert_eq!(floats_row_major[4..6], [1.0, 3.0]);
+        assert_eq!(floats_row_major[8..10], [2.0, 4.0]);
+    }
+}
@@ -0,0 +1,318 @@
+//! Uniforms filled automatically by the widget.
+//!
+//! A shader that declares one of these uniforms gets its value supplied on
+//! every frame, without any setter call:
+//!
+//! | Name           | GLSL Type | Value                                                   |
+//! | -------------- | --------- | ------------------------------------------------------- |
+//! | `time`         | `float`   | Seconds since the first frame, from the frame clock     |
+//! | `time_delta`   | `float`   | Seconds since the previous frame                        |
+//! | `frame`        | `int`     | Index of the current frame, starting at 0               |
+//! | `resolution`   | `vec2`    | Size of the widget in device pixels                     |
+//! | `scale_factor` | `float`   | Ratio between device pixels and logical pixels          |
+//! | `date`         | `vec4`    | Year, month (1-12), day and seconds since midnight      |
+//! | `texN_size`    | `vec2`    | Size in pixels of the texture `texN`                    |
+//!
+//! Builtins declared with another type, like `uniform float frame;`, are
+//! converted like the values set by the user; those that can't be are
+//! logged as errors and left unset.
+//!
+//! With the `pointer-input` property enabled, these are filled as well, in
+//! device pixels with the origin at the bottom-left:
+//!
+//! | Name              | GLSL Type   | Value                                                |
+//! | ----------------- | ----------- | ---------------------------------------------------- |
+//! | `pointer`         | `vec2`      | Pointer position                                     |
+//! | `pointer_uv`      | `vec2`      | Pointer position in uv space                         |
+//! | `pointer_buttons` | `int`       | Pressed buttons (1: primary, 2: middle, 4: secondary) |
+//! | `click`           | `vec2`      | Position of the last press                           |
+//! | `drag_delta`      | `vec2`      | Offset of the current (or last) drag                 |
+//! | `mouse`           | `vec4`      | Shadertoy-style `iMouse`                             |
+//! | `touches`         | `vec3[10]`  | Active touch points as `(x, y, 1.0)`                 |
+//! | `touch_count`     | `int`       | Number of active touch points                        |
+//!
+//! With the `view-controls` property enabled, scroll, drag, pinch and rotate
+//! gestures drive a view transform, exposed as:
+//!
+//! | Name          | GLSL Type | Value                                                    |
+//! | ------------- | --------- | -------------------------------------------------------- |
+//! | `view`        | `vec4`    | Center of the view (xy), zoom factor and rotation        |
+//! | `view_matrix` | `mat3`    | Maps `vec3(uv, 1.0)` to view coordinates                 |
+//!
+//! With a mesh loaded, the widget supplies the matrices placing it in front
+//! of the camera (see [`super::mesh`]), driven by pointer drags and scrolls
+//! with the `orbit-camera` property enabled:
+//!
+//! | Name                | GLSL Type | Value                                          |
+//! | ------------------- | --------- | ---------------------------------------------- |
+//! | `model_matrix`      | `mat4`    | Centers the mesh and fits it from -1 to 1      |
+//! | `camera_matrix`     | `mat4`    | Maps world space to camera space               |
+//! | `projection_matrix` | `mat4`    | Perspective projection for the widget's aspect |
+//! | `camera_position`   | `vec3`    | Position of the camera in world space          |
+//!
+//! Custom vertex shaders get `vertexCount`, the number of vertices drawn as
+//! a `float`, along with `vertexId` (see [`super::vertex`]). Particle
+//! shaders get `particle_count`, an `int` (see [`super::particles`]).
+//!
+//! Shadertoy's uniforms are filled under their own names as well: `iTime`,
+//! `iTimeDelta`, `iFrame` and `iMouse` alias the builtins above, while
+//! `iResolution` is a `vec3` (resolution and pixel aspect ratio),
+//! `iFrameRate` a `float`, and `iDate` is `date` with the month counted from
+//! 0, like JavaScript's `getMonth()`.
+//!
+//! ISF's `TIME`, `TIMEDELTA`, `FRAMEINDEX`, `RENDERSIZE` and `DATE` alias
+//! `time`, `time_delta`, `frame`, `resolution` and `date`, and so do
+//! glslCanvas' `u_time`, `u_delta`, `u_frame`, `u_resolution` and `u_date`,
+//! while `u_mouse` aliases `pointer`.
+//!
+//! Values set by the user with the same name take precedence.
+
+use gtk::glib;
+
+use super::{
+    Uniform,
+    input::{MAX_TOUCHES, PointerInfo},
+};
+
+/// A uniform whose value is supplied by the widget rather than the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Builtin {
+    /// `time`
+    Time,
+    /// `time_delta`
+    TimeDelta,
+    /// `frame`
+    Frame,
+    /// `resolution`
+    Resolution,
+    /// `scale_factor`
+    ScaleFactor,
+    /// `date`
+    Date,
+    /// `texN_size`
+    TextureSize(usize),
+    /// `pointer`
+    Pointer,
+    /// `pointer_uv`
+    PointerUv,
+    /// `pointer_buttons`
+    PointerButtons,
+    /// `click`
+    Click,
+    /// `drag_delta`
+    DragDelta,
+    /// `mouse`
+    Mouse,
+    /// `touches[N]`
+    Touch(usize),
+    /// `touch_count`
+    TouchCount,
+    /// `view`
+    View,
+    /// `view_matrix`
+    ViewMatrix,
+    /// `iResolution`
+    ShadertoyResolution,
+    /// `iFrameRate`
+    FrameRate,
+    /// `iDate`
+    ShadertoyDate,
+    /// `vertexCount`
+    VertexCount,
+    /// `particle_count`
+    ParticleCount,
+    /// `model_matrix`
+    ModelMatrix,
+    /// `camera_matrix`
+    CameraMatrix,
+    /// `projection_matrix`
+    ProjectionMatrix,
+    /// `camera_position`
+    CameraPosition,
+}
+
+impl Builtin {
+    /// Returns every builtin with its uniform name, for a program with
+    /// `texture_count` textures.
+    pub fn all(texture_count: usize) -> impl Iterator<Item = (String, Self)> {
+        [
+            ("time", Self::Time),
+            ("time_delta", Self::TimeDelta),
+            ("frame", Self::Frame),
+            ("resolution", Self::Resolution),
+            ("scale_factor", Self::ScaleFactor),
+            ("date", Self::Date),
+            ("pointer", Self::Pointer),
+            ("pointer_uv", Self::PointerUv),
+            ("pointer_buttons", Self::PointerButtons),
+            ("click", Self::Click),
+            ("drag_delta", Self::DragDelta),
+            ("mouse", Self::Mouse),
+            ("touch_count", Self::TouchCount),
+            ("view", Self::View),
+            ("view_matrix", Self::ViewMatrix),
+            ("vertexCount", Self::VertexCount),
+            ("particle_count", Self::ParticleCount),
+            ("model_matrix", Self::ModelMatrix),
+            ("camera_matrix", Self::CameraMatrix),
+            ("projection_matrix", Self::ProjectionMatrix),
+            ("camera_position", Self::CameraPosition),
+            ("iTime", Self::Time),
+            ("iTimeDelta", Self::TimeDelta),
+            ("iFrame", Self::Frame),
+            ("iMouse", Self::Mouse),
+            ("iDate", Self::ShadertoyDate),
+            ("iResolution", Self::ShadertoyResolution),
+            ("iFrameRate", Self::FrameRate),
+            ("TIME", Self::Time),
+            ("TIMEDELTA", Self::TimeDelta),
+            ("FRAMEINDEX", Self::Frame),
+            ("RENDERSIZE", Self::Resolution),
+            ("DATE", Self::Date),
+            ("u_time", Self::Time),
+            ("u_delta", Self::TimeDelta),
+            ("u_frame", Self::Frame),
+            ("u_resolution", Self::Resolution),
+            ("u_mouse", Self::Pointer),
+            ("u_date", Self::Date),
+        ]
+        .into_iter()
+        .map(|(name, builtin)| (name.to_owned(), builtin))
+        .chain((0..texture_count).map(|i| (format!("tex{i}_size"), Self::TextureSize(i))))
+        // Array elements are resolved one by one
+        .chain((0..MAX_TOUCHES).map(|i| (format!("touches[{i}]"), Self::Touch(i))))
+    }
+
+    /// Returns the value of the builtin for the current frame.
+    pub fn value(self, frame: &FrameInfo) -> Uniform {
+        match self {
+            Self::Time => Uniform::Float(frame.time),
+            Self::TimeDelta => Uniform::Float(frame.time_delta),
+            Self::Frame => Uniform::Int(frame.frame),
+            Self::Resolution => Uniform::Vec2(frame.resolution),
+            Self::ScaleFactor => Uniform::Float(frame.scale_factor),
+            Self::Date => Uniform::Vec4(frame.date),
+            Self::TextureSize(i) => {
+                Uniform::Vec2(frame.texture_sizes.get(i).copied().unwrap_or_default())
+            }
+            Self::Pointer => Uniform::Vec2(frame.pointer.position),
+            Self::PointerUv => Uniform::Vec2(frame.pointer.position_uv),
+            Self::PointerButtons => Uniform::Int(frame.pointer.buttons),
+            Self::Click => Uniform::Vec2(frame.pointer.click),
+            Self::DragDelta => Uniform::Vec2(frame.pointer.drag_delta),
+            Self::Mouse => Uniform::Vec4(frame.pointer.mouse),
+            Self::Touch(i) => {
+                Uniform::Vec3(frame.pointer.touches.get(i).copied().unwrap_or_default())
+            }
+            Self::TouchCount => Uniform::Int(frame.pointer.touches.len() as i32),
+            Self::View => Uniform::Vec4(frame.view),
+            Self::ViewMatrix => Uniform::Mat3(frame.view_matrix),
+            Self::ShadertoyResolution => {
+                Uniform::Vec3([frame.resolution[0], frame.resolution[1], 1.0])
+            }
+            Self::FrameRate => Uniform::Float(if frame.time_delta > 0.0 {
+                1.0 / frame.time_delta
+            } else {
+                0.0
+            }),
+            Self::ShadertoyDate => {
+                let [year, month, day, seconds] = frame.date;
+                Uniform::Vec4([year, month - 1.0, day, seconds])
+            }
+            Self::VertexCount => Uniform::Float(frame.vertex_count),
+            Self::ParticleCount => Uniform::Int(frame.particle_count),
+            Self::ModelMatrix => Uniform::Mat4(frame.model_matrix),
+            Self::CameraMatrix => Uniform::Mat4(frame.camera_matrix),
+            Self::ProjectionMatrix => Uniform::Mat4(frame.projection_matrix),
+            Self::CameraPosition => Uniform::Vec3(frame.camera_position),
+        }
+    }
+}
+
+/// Keeps track of frame timings across renders.
+#[derive(Debug, Default)]
+pub struct FrameTimer {
+    /// Frame time of the first frame, in microseconds
+    start: Option<i64>,
+    /// Frame time of the previous frame, in microseconds
+    last: Option<i64>,
+    /// Index of the next frame
+    frame: i32,
+}
+
+impl FrameTimer {
+    /// Advances to a new frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The frame time in microseconds, as given by `GdkFrameClock`
+    ///
+    /// # Returns
+    ///
+    /// The elapsed time, the time since the previous frame (both in seconds)
+    /// and the index of the new frame.
+    pub fn tick(&mut self, now: i64) -> (f32, f32, i32) {
+        let start = *self.start.get_or_insert(now);
+        let delta = self.last.map_or(0, |last| now - last);
+        self.last = Some(now);
+
+        let frame = self.frame;
+        self.frame = self.frame.wrapping_add(1);
+
+        ((now - start) as f32 / 1e6, delta.max(0) as f32 / 1e6, frame)
+    }
+}
+
+/// Values used to fill builtin uniforms for one frame.
+#[derive(Debug, Default)]
+pub struct FrameInfo {
+    /// Seconds since the first frame
+    pub time: f32,
+    /// Seconds since the previous frame
+    pub time_delta: f32,
+    /// Index of the current frame
+    pub frame: i32,
+    /// Size of the framebuffer in device pixels
+    pub resolution: [f32; 2],
+    /// Ratio between device pixels and logical pixels
+    pub scale_factor: f32,
+    /// Year, month, day and seconds since midnight
+    pub date: [f32; 4],
+    /// Size of each texture in pixels, indexed by texture unit
+    pub texture_sizes: Vec<[f32; 2]>,
+    /// Pointer and touch values
+    pub pointer: PointerInfo,
+    /// View transform as center, zoom and rotation
+    pub view: [f32; 4],
+    /// View transform as a column-major matrix
+    pub view_matrix: [f32; 9],
+    /// Number of vertices drawn
+    pub vertex_count: f32,
+    /// Number of particles
+    pub particle_count: i32,
+    /// Column-major matrix placing the mesh in world space
+    pub model_matrix: [f32; 16],
+    /// Column-major view matrix of the orbit camera
+    pub camera_matrix: [f32; 16],
+    /// Column-major projection matrix of the orbit camera
+    pub projection_matrix: [f32; 16],
+    /// Position of the orbit camera
+    pub camera_position: [f32; 3],
+}
+
+impl FrameInfo {
+    /// Returns the local date as year, month (1-12), day and seconds since
+    /// midnight.
+    pub fn local_date() -> [f32; 4] {
+        let Ok(now) = glib::DateTime::now_local() else {
+            return [0.0; 4];
+        };
+        let seconds = f64::from(now.hour() * 3600 + now.minute() * 60) + now.seconds();
+        [
+            now.year() as f32,
+            now.month() as f32,
+            now.day_of_month() as f32,
+            seconds as f32,
+        ]
+    }
+}
@@ -0,0 +1,136 @@
+//! Orbit camera for the mesh builtin uniforms.
+//!
+//! The camera circles the origin, where meshes are centered, looking at it
+//! from a distance. Dragging changes its yaw and pitch, and scrolling its
+//! distance.
+
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+/// Closest and farthest distance of the camera to the origin.
+const DISTANCE_LIMITS: [f64; 2] = [0.5, 50.0];
+
+/// Near and far clipping planes.
+const CLIP_PLANES: [f64; 2] = [0.05, 100.0];
+
+/// The column-major 4×4 identity matrix.
+#[rustfmt::skip]
+pub const IDENTITY: [f32; 16] = [
+    1.0, 0.0, 0.0, 0.0,
+    0.0, 1.0, 0.0, 0.0,
+    0.0, 0.0, 1.0, 0.0,
+    0.0, 0.0, 0.0, 1.0,
+];
+
+/// A camera orbiting the origin.
+#[derive(Debug, Clone, Copy)]
+pub struct OrbitCamera {
+    /// Rotation around the vertical axis in radians
+    pub yaw: f64,
+    /// Elevation above the horizontal plane in radians
+    pub pitch: f64,
+    /// Distance to the origin
+    pub distance: f64,
+}
+
+impl Default for OrbitCamera {
+    fn default() -> Self {
+        Self {
+            yaw: 0.0,
+            pitch: 0.0,
+            distance: 3.0,
+        }
+    }
+}
+
+impl OrbitCamera {
+    /// Orbits the camera for a pointer moving by `delta` in uv space, so that
+    /// dragging across the whole widget turns half a revolution.
+    pub fn rotate(&mut self, [dx, dy]: [f64; 2]) {
+        // Stop short of the poles, where the up vector is undefined
+        let limit = FRAC_PI_2 - 1e-3;
+        self.yaw -= dx * PI;
+        self.pitch = (self.pitch - dy * PI).clamp(-limit, limit);
+    }
+
+    /// Moves the camera closer by `factor`, or away for a factor below 1.
+    pub fn zoom(&mut self, factor: f64) {
+        self.distance = (self.distance / factor).clamp(DISTANCE_LIMITS[0], DISTANCE_LIMITS[1]);
+    }
+
+    /// Returns the position of the camera.
+    pub fn position(&self) -> [f64; 3] {
+        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
+        [
+            self.distance * cos_pitch * sin_yaw,
+            self.distance * sin_pitch,
+            self.distance * cos_pitch * cos_yaw,
+        ]
+    }
+
+    /// Returns the column-major view matrix, looking at the origin with the
+    /// y axis up.
+    pub fn view_matrix(&self) -> [f32; 16] {
+        let eye = self.position();
+        let forward = normalize(eye.map(|v| -v));
+        let side = normalize(cross(forward, [0.0, 1.0, 0.0]));
+        let up = cross(side, forward);
+        [
+            side[0] as f32,
+            up[0] as f32,
+            -forward[0] as f32,
+            0.0,
+            side[1] as f32,
+            up[1] as f32,
+            -forward[1] as f32,
+            0.0,
+            side[2] as f32,
+            up[2] as f32,
+            -forward[2] as f32,
+            0.0,
+            -dot(side, eye) as f32,
+            -dot(up, eye) as f32,
+            dot(forward, eye) as f32,
+            1.0,
+        ]
+    }
+
+    /// Returns the column-major perspective projection matrix, with a 45°
+    /// vertical field of view.
+    ///
+    /// # Arguments
+    ///
+    /// * `aspect` - Width of the widget divided by its height
+    pub fn projection_matrix(aspect: f64) -> [f32; 16] {
+        let f = 1.0 / (FRAC_PI_4 / 2.0).tan();
+        let [near, far] = CLIP_PLANES;
+        let mut matrix = [0.0; 16];
+        matrix[0] = (f / aspect) as f32;
+        matrix[5] = f as f32;
+        matrix[10] = ((far + near) / (near - far)) as f32;
+        matrix[11] = -1.0;
+        matrix[14] = (2.0 * far * near / (near - far)) as f32;
+        matrix
+    }
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn normalize(v: [f64; 3]) -> [f64; 3] {
+    let length = dot(v, v).sqrt();
+    if length > 0.0 {
+        v.map(|c| c / length)
+    } else {
+        v
+    }
+}
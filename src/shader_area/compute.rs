@@ -0,0 +1,339 @@
+//! Compute shaders and storage objects.
+//!
+//! On OpenGL 4.3 and later, compute passes are dispatched at the start of
+//! every frame, before the particles, the render passes and the widget's
+//! shader. They read and write storage objects declared by the application:
+//!
+//! - Storage buffers, bound by block name: a compute shader declares
+//!   `layout(std430) buffer name { … };` for a buffer called `name`.
+//! - Storage images, bound by uniform name: a compute shader declares
+//!   `layout(rgba32f) uniform image2D name;` (with the qualifier matching the
+//!   image's format), while render shaders sample the same image through
+//!   `uniform sampler2D name;`.
+//!
+//! Both can be initialized from bytes and read back once a frame rendered.
+//! Compute shaders get the same builtins and uniforms as the other shaders.
+
+use gtk::glib;
+
+use super::{PassFormat, ShaderError, imp::append_user_source, pass::is_identifier};
+
+/// Minimum OpenGL version with compute shaders and storage buffers.
+pub const MIN_VERSION: (i32, i32) = (4, 3);
+
+/// GLSL version header of compute shaders.
+pub const GLSL_VERSION: &str = "#version 430 core\n";
+
+/// When a compute pass is dispatched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, glib::Enum)]
+#[enum_type(name = "GtkGlShadersComputeSchedule")]
+pub enum ComputeSchedule {
+    /// On every frame the widget renders
+    #[default]
+    EveryFrame,
+    /// Once on the next frame, for every call to `run_compute`
+    OnDemand,
+}
+
+/// A compute shader dispatched before rendering.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComputePass {
+    /// Name of the pass, used to run it on demand and in errors
+    pub name: String,
+    /// GLSL compute shader source code, without the `local_size` layout
+    pub shader: String,
+    /// Size of a work group, declared by the widget
+    pub local_size: [u32; 3],
+    /// Number of work groups dispatched
+    pub groups: [u32; 3],
+    /// When the pass is dispatched
+    pub schedule: ComputeSchedule,
+}
+
+impl ComputePass {
+    /// Creates a pass dispatching `groups` work groups of 8×8 invocations on
+    /// every frame.
+    pub fn new(name: impl Into<String>, shader: impl Into<String>, groups: [u32; 3]) -> Self {
+        Self {
+            name: name.into(),
+            shader: shader.into(),
+            local_size: [8, 8, 1],
+            groups,
+            schedule: ComputeSchedule::EveryFrame,
+        }
+    }
+
+    /// Checks that the name of the pass is a valid identifier that isn't
+    /// used by another compute pass, and that it dispatches invocations.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError::Pass`] otherwise.
+    pub fn validate<'a>(
+        &self,
+        others: impl IntoIterator<Item = &'a ComputePass>,
+    ) -> Result<(), glib::Error> {
+        if !is_identifier(&self.name) {
+            return Err(glib::Error::new(
+                ShaderError::Pass,
+                &format!("Invalid compute pass name `{}`", self.name),
+            ));
+        }
+        if others.into_iter().any(|other| other.name == self.name) {
+            return Err(glib::Error::new(
+                ShaderError::Pass,
+                &format!("A compute pass named `{}` already exists", self.name),
+            ));
+        }
+        if self.local_size.contains(&0) || self.groups.contains(&0) {
+            return Err(glib::Error::new(
+                ShaderError::Pass,
+                &format!("Compute pass `{}` has an empty work group", self.name),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the shader with its work group size declared.
+    pub fn wrap(&self) -> String {
+        let [x, y, z] = self.local_size;
+        let mut shader =
+            format!("layout(local_size_x = {x}, local_size_y = {y}, local_size_z = {z}) in;\n");
+        append_user_source(&mut shader, &self.shader);
+        shader
+    }
+}
+
+/// A shader storage buffer shared by the compute and render shaders.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageBuffer {
+    /// Name of the buffer block in the shaders
+    pub name: String,
+    /// Initial contents, which also give the size of the buffer
+    pub data: glib::Bytes,
+}
+
+impl StorageBuffer {
+    /// Checks that the buffer has a valid name and isn't empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError::Pass`] otherwise.
+    pub fn validate(&self) -> Result<(), glib::Error> {
+        if !is_identifier(&self.name) {
+            return Err(glib::Error::new(
+                ShaderError::Pass,
+                &format!("Invalid storage buffer name `{}`", self.name),
+            ));
+        }
+        if self.data.is_empty() {
+            return Err(glib::Error::new(
+                ShaderError::Pass,
+                &format!("Storage buffer `{}` is empty", self.name),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Creates the buffer, or replaces the contents of `id` if it isn't 0.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// supporting compute shaders must be bound before calling.
+    ///
+    /// # Returns
+    ///
+    /// The buffer ID.
+    pub unsafe fn upload(&self, mut id: u32) -> u32 {
+        let data: &[u8] = &self.data;
+        unsafe {
+            if id == 0 {
+                epoxy::GenBuffers(1, &raw mut id);
+            }
+            epoxy::BindBuffer(epoxy::SHADER_STORAGE_BUFFER, id);
+            epoxy::BufferData(
+                epoxy::SHADER_STORAGE_BUFFER,
+                data.len() as isize,
+                data.as_ptr().cast(),
+                epoxy::DYNAMIC_COPY,
+            );
+        }
+        id
+    }
+}
+
+/// An image read and written by compute shaders, and sampled by render
+/// shaders.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageImage {
+    /// Name of the image uniform in the shaders
+    pub name: String,
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+    /// Pixel format, matched by the `layout` qualifier of the `image2D`
+    pub format: PassFormat,
+    /// Initial pixels, row by row from the bottom, or `None` for a cleared
+    /// image
+    pub data: Option<glib::Bytes>,
+}
+
+impl StorageImage {
+    /// Returns the size of the pixels in bytes.
+    pub fn byte_size(&self) -> usize {
+        self.width as usize * self.height as usize * self.format.bytes_per_pixel()
+    }
+
+    /// Checks that the image has a valid name and size, and that its
+    /// initial pixels match that size.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError::Pass`] otherwise.
+    pub fn validate(&self) -> Result<(), glib::Error> {
+        if !is_identifier(&self.name) {
+            return Err(glib::Error::new(
+                ShaderError::Pass,
+                &format!("Invalid storage image name `{}`", self.name),
+            ));
+        }
+        if self.width == 0 || self.height == 0 {
+            return Err(glib::Error::new(
+                ShaderError::Pass,
+                &format!("Storage image `{}` has an empty size", self.name),
+            ));
+        }
+        if let Some(data) = &self.data
+            && data.len() != self.byte_size()
+        {
+            return Err(glib::Error::new(
+                ShaderError::Pass,
+                &format!(
+                    "Storage image `{}` needs {} bytes of pixels, got {}",
+                    self.name,
+                    self.byte_size(),
+                    data.len()
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Creates the texture, or redefines `id` if it isn't 0.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// supporting compute shaders must be bound before calling.
+    ///
+    /// # Returns
+    ///
+    /// The texture ID.
+    pub unsafe fn upload(&self, mut id: u32) -> u32 {
+        let (internal_format, pixel_type) = self.format.gl();
+        let zeroes;
+        let pixels: &[u8] = match &self.data {
+            Some(data) => data,
+            // Image load and store leave new textures undefined otherwise
+            None => {
+                zeroes = vec![0u8; self.byte_size()];
+                &zeroes
+            }
+        };
+        unsafe {
+            if id == 0 {
+                epoxy::GenTextures(1, &raw mut id);
+            }
+            epoxy::BindTexture(epoxy::TEXTURE_2D, id);
+            super::imp::ShaderArea::set_texture_parameters(epoxy::NEAREST);
+            epoxy::TexImage2D(
+                epoxy::TEXTURE_2D,
+                0,
+                internal_format as i32,
+                self.width as i32,
+                self.height as i32,
+                0,
+                epoxy::RGBA,
+                pixel_type,
+                pixels.as_ptr().cast(),
+            );
+        }
+        id
+    }
+
+    /// Binds the texture `id` to an image unit for reading and writing.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// supporting compute shaders must be bound before calling.
+    pub unsafe fn bind(&self, unit: u32, id: u32) {
+        let (internal_format, _) = self.format.gl();
+        unsafe {
+            epoxy::BindImageTexture(unit, id, 0, 0, 0, epoxy::READ_WRITE, internal_format);
+        }
+    }
+
+    /// Reads back the pixels of the texture `id`.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// supporting compute shaders must be bound before calling.
+    pub unsafe fn read(&self, id: u32) -> glib::Bytes {
+        let (_, pixel_type) = self.format.gl();
+        let mut pixels = vec![0u8; self.byte_size()];
+        unsafe {
+            epoxy::MemoryBarrier(epoxy::TEXTURE_UPDATE_BARRIER_BIT);
+            epoxy::BindTexture(epoxy::TEXTURE_2D, id);
+            epoxy::GetTexImage(
+                epoxy::TEXTURE_2D,
+                0,
+                epoxy::RGBA,
+                pixel_type,
+                pixels.as_mut_ptr().cast(),
+            );
+        }
+        glib::Bytes::from_owned(pixels)
+    }
+}
+
+/// Reads back the contents of the storage buffer `id`.
+///
+/// # Safety
+///
+/// This function calls unsafe OpenGL functions. An active OpenGL context
+/// supporting compute shaders must be bound before calling.
+pub unsafe fn read_buffer(id: u32) -> glib::Bytes {
+    unsafe {
+        epoxy::MemoryBarrier(epoxy::BUFFER_UPDATE_BARRIER_BIT);
+        epoxy::BindBuffer(epoxy::SHADER_STORAGE_BUFFER, id);
+        let mut size = 0i32;
+        epoxy::GetBufferParameteriv(
+            epoxy::SHADER_STORAGE_BUFFER,
+            epoxy::BUFFER_SIZE,
+            &raw mut size,
+        );
+        let mut data = vec![0u8; size.max(0) as usize];
+        epoxy::GetBufferSubData(
+            epoxy::SHADER_STORAGE_BUFFER,
+            0,
+            data.len() as isize,
+            data.as_mut_ptr().cast(),
+        );
+        glib::Bytes::from_owned(data)
+    }
+}
+
+/// Returns the error for compute features on a context without them.
+pub fn unsupported_error() -> glib::Error {
+    glib::Error::new(ShaderError::Unsupported, &unsupported_message())
+}
+
+/// Explains that compute shaders need a newer context.
+pub fn unsupported_message() -> String {
+    let (major, minor) = MIN_VERSION;
+    format!("Compute shaders need OpenGL {major}.{minor} or later, not available in this context")
+}
@@ -0,0 +1,27 @@
+//! Source dialects accepted by the widget.
+//!
+//! Shaders written for other environments are rewritten into the GLSL the
+//! widget compiles, right before compilation, so that the stored source (and
+//! the line numbers of compile errors) stay the ones the user wrote.
+
+use gtk::glib;
+
+/// The dialect shader sources are written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, glib::Enum)]
+#[enum_type(name = "GtkGlShadersShaderDialect")]
+pub enum ShaderDialect {
+    /// GLSL with a `main` function writing to an `out vec4`. Legacy GLSL
+    /// sources, writing `gl_FragColor`, are detected and handled like
+    /// [`Self::Legacy`] ones
+    #[default]
+    Glsl,
+    /// Shadertoy code defining `mainImage(out vec4, in vec2)`
+    Shadertoy,
+    /// Interactive Shader Format: a JSON header followed by code writing
+    /// `gl_FragColor`
+    Isf,
+    /// A gl-transitions transition defining `vec4 transition(vec2 uv)`
+    Transition,
+    /// Legacy GLSL (ES 1.00, glslCanvas) writing `gl_FragColor`
+    Legacy,
+}
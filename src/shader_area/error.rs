@@ -0,0 +1,231 @@
+//! Shader compilation and linking errors.
+//!
+//! Drivers report errors as free-form info logs. This module turns them into
+//! structured [`CompileError`]s. Line numbers refer to the source the user
+//! wrote, as the compiled source resets them with a `#line` directive after
+//! anything prepended to it.
+
+use std::fmt;
+
+use gtk::glib::{self, Variant, prelude::*};
+
+/// Error domain for shader errors reported through `GError`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, glib::ErrorDomain)]
+#[error_domain(name = "gtk-gl-shaders-shader-error-quark")]
+pub enum ShaderError {
+    /// A shader stage failed to compile
+    Compile,
+    /// The shader program failed to link
+    Link,
+    /// No OpenGL context was available to compile the shader
+    Context,
+    /// A render or compute pass, or a storage object, has an invalid name or
+    /// size
+    Pass,
+    /// A shader description, such as a Shadertoy export, is malformed
+    Parse,
+    /// The OpenGL context lacks a feature, such as compute shaders
+    Unsupported,
+    /// A uniform value is malformed
+    Uniform,
+}
+
+/// The stage of the shader pipeline an error comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, glib::Enum)]
+#[enum_type(name = "GtkGlShadersShaderStage")]
+pub enum ShaderStage {
+    /// The vertex shader
+    Vertex,
+    /// The fragment shader
+    Fragment,
+    /// Linking of the program
+    Link,
+    /// A compute shader
+    Compute,
+}
+
+impl fmt::Display for ShaderStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Vertex => "Vertex shader",
+            Self::Fragment => "Fragment shader",
+            Self::Link => "Program",
+            Self::Compute => "Compute shader",
+        })
+    }
+}
+
+/// A single message parsed from a driver info log.
+#[derive(Debug, Clone)]
+pub struct CompileMessage {
+    /// Line in the user's source, if the driver reported one
+    pub line: Option<u32>,
+    /// Column in the user's source, if the driver reported one
+    pub column: Option<u32>,
+    /// The message itself, without its location prefix
+    pub message: String,
+}
+
+/// A shader compilation or linking failure.
+#[derive(Debug, Clone)]
+pub struct CompileError {
+    /// The stage that failed
+    pub stage: ShaderStage,
+    /// The raw info log returned by the driver
+    pub log: String,
+    /// Messages parsed from `log`
+    pub messages: Vec<CompileMessage>,
+    /// Name of the render pass the shader belongs to, `None` for the
+    /// widget's own shader
+    pub pass: Option<String>,
+}
+
+impl CompileError {
+    /// Builds an error from a driver info log.
+    ///
+    /// # Arguments
+    ///
+    /// * `stage` - The stage that failed
+    /// * `log` - The driver info log
+    pub fn from_log(stage: ShaderStage, log: String) -> Self {
+        let messages = log
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(parse_log_line)
+            .collect();
+
+        Self {
+            stage,
+            log,
+            messages,
+            pass: None,
+        }
+    }
+
+    /// Attributes the error to a render pass.
+    pub fn in_pass(mut self, name: &str) -> Self {
+        self.pass = Some(name.to_owned());
+        self
+    }
+
+    /// Returns the parsed messages as a `GVariant` of type `a(iis)`.
+    ///
+    /// Each entry is `(line, column, message)`, with `-1` for an unknown line
+    /// or column.
+    pub fn messages_variant(&self) -> Variant {
+        self.messages
+            .iter()
+            .map(|m| {
+                (
+                    m.line.map_or(-1, |l| l as i32),
+                    m.column.map_or(-1, |c| c as i32),
+                    m.message.clone(),
+                )
+            })
+            .collect::<Vec<_>>()
+            .to_variant()
+    }
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let action = match self.stage {
+            ShaderStage::Link => "link",
+            _ => "compile",
+        };
+        write!(f, "{}", self.stage)?;
+        if let Some(pass) = &self.pass {
+            write!(f, " of pass `{pass}`")?;
+        }
+        write!(f, " failed to {action}: {}", self.log.trim_end())
+    }
+}
+
+impl From<CompileError> for glib::Error {
+    fn from(error: CompileError) -> Self {
+        let code = match error.stage {
+            ShaderStage::Link => ShaderError::Link,
+            _ => ShaderError::Compile,
+        };
+        glib::Error::new(code, &error.to_string())
+    }
+}
+
+/// Parses one line of a driver info log.
+///
+/// The following location formats are recognized, with an optional
+/// `ERROR:`/`WARNING:` prefix:
+///
+/// - `0:12(5): error: …` (Mesa)
+/// - `0(12) : error C0000: …` (NVIDIA)
+/// - `0:12: '…' : …` (ANGLE, Mali, Adreno, Apple)
+///
+/// Messages on line 0 are reported without a line.
+fn parse_log_line(line: &str) -> CompileMessage {
+    let unparsed = || CompileMessage {
+        line: None,
+        column: None,
+        message: line.to_owned(),
+    };
+
+    let (severity, rest) = ["ERROR:", "WARNING:"]
+        .iter()
+        .find_map(|prefix| {
+            line.strip_prefix(prefix)
+                .map(|rest| (Some(&prefix[..prefix.len() - 1]), rest.trim_start()))
+        })
+        .unwrap_or((None, line));
+
+    let Some((_, rest)) = split_number(rest) else {
+        return unparsed();
+    };
+
+    let (line_number, column, rest) = if let Some(rest) = rest.strip_prefix(':') {
+        // `0:12(5):` or `0:12:`
+        let Some((line_number, rest)) = split_number(rest) else {
+            return unparsed();
+        };
+        let (column, rest) = match rest
+            .strip_prefix('(')
+            .and_then(split_number)
+            .and_then(|(column, rest)| Some((column, rest.strip_prefix(')')?)))
+        {
+            Some((column, rest)) => (Some(column), rest),
+            None => (None, rest),
+        };
+        (line_number, column, rest)
+    } else if let Some(rest) = rest.strip_prefix('(') {
+        // `0(12) :`
+        let Some((line_number, rest)) = split_number(rest) else {
+            return unparsed();
+        };
+        let Some(rest) = rest.strip_prefix(')') else {
+            return unparsed();
+        };
+        (line_number, None, rest)
+    } else {
+        return unparsed();
+    };
+
+    let Some(rest) = rest.trim_start().strip_prefix(':') else {
+        return unparsed();
+    };
+    let message = match severity {
+        Some(severity) => format!("{}: {}", severity.to_lowercase(), rest.trim()),
+        None => rest.trim().to_owned(),
+    };
+
+    CompileMessage {
+        line: Some(line_number).filter(|&l| l > 0),
+        column,
+        message,
+    }
+}
+
+/// Splits a leading decimal number from a string.
+fn split_number(s: &str) -> Option<(u32, &str)> {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let number = s[..end].parse().ok()?;
+    Some((number, &s[end..]))
+}
@@ -11,120 +11,1034 @@
 //! guarantees. Callers must ensure pointers are valid and strings are
 //! null-terminated.
 
-use std::{collections::HashMap, ffi::c_char, path::PathBuf};
+use std::{
+    collections::HashMap,
+    ffi::c_char,
+    path::{Path, PathBuf},
+    ptr,
+};
 
 use glib::{
     GString, Variant,
-    ffi::{GType, GVariant},
+    error::ErrorDomain,
+    ffi::{GBytes, GError, GQuark, GType, GVariant, gboolean},
     subclass::types::ObjectSubclass,
-    translate::{FromGlibPtrNone, IntoGlib, ToGlibPtr},
+    translate::{FromGlibPtrNone, IntoGlib, IntoGlibPtr, ToGlibPtr, TryFromGlib},
     types::StaticType,
 };
+use gtk::{gdk, graphene};
 use log::error;
 
-use super::Uniform;
+use super::{
+    BlendMode, BlockContents, ComputePass, ComputeSchedule, Easing, Particles, Pass, PassFilter,
+    PassFormat, PassSize, Primitive, ShaderDialect, ShaderError, ShaderStage, StorageBuffer,
+    StorageImage, Uniform, UniformBlock, UniformInfo, UniformSource, UniformType,
+};
 
 pub type ShaderArea = <super::imp::ShaderArea as ObjectSubclass>::Instance;
 
-/// Returns the `GType` for `ShaderArea`.
-///
-/// This function is called by g-ir-scanner during introspection generation.
+/// Returns the `GType` for `ShaderArea`.
+///
+/// This function is called by g-ir-scanner during introspection generation.
+///
+/// # Safety
+///
+/// This function is safe to call from C.
+#[unsafe(no_mangle)]
+pub extern "C" fn gtk_gl_shaders_shader_area_get_type() -> GType {
+    <super::ShaderArea as StaticType>::static_type().into_glib()
+}
+
+/// Returns the `GType` for `ShaderStage`.
+///
+/// # Safety
+///
+/// This function is safe to call from C.
+#[unsafe(no_mangle)]
+pub extern "C" fn gtk_gl_shaders_shader_stage_get_type() -> GType {
+    <ShaderStage as StaticType>::static_type().into_glib()
+}
+
+/// Returns the `GType` for `ShaderDialect`.
+///
+/// # Safety
+///
+/// This function is safe to call from C.
+#[unsafe(no_mangle)]
+pub extern "C" fn gtk_gl_shaders_shader_dialect_get_type() -> GType {
+    <ShaderDialect as StaticType>::static_type().into_glib()
+}
+
+/// Returns the `GType` for `PassFormat`.
+///
+/// # Safety
+///
+/// This function is safe to call from C.
+#[unsafe(no_mangle)]
+pub extern "C" fn gtk_gl_shaders_pass_format_get_type() -> GType {
+    <PassFormat as StaticType>::static_type().into_glib()
+}
+
+/// Returns the `GType` for `PassFilter`.
+///
+/// # Safety
+///
+/// This function is safe to call from C.
+#[unsafe(no_mangle)]
+pub extern "C" fn gtk_gl_shaders_pass_filter_get_type() -> GType {
+    <PassFilter as StaticType>::static_type().into_glib()
+}
+
+/// Returns the `GType` for `Easing`.
+///
+/// # Safety
+///
+/// This function is safe to call from C.
+#[unsafe(no_mangle)]
+pub extern "C" fn gtk_gl_shaders_easing_get_type() -> GType {
+    <Easing as StaticType>::static_type().into_glib()
+}
+
+/// Returns the `GType` for `Primitive`.
+///
+/// # Safety
+///
+/// This function is safe to call from C.
+#[unsafe(no_mangle)]
+pub extern "C" fn gtk_gl_shaders_primitive_get_type() -> GType {
+    <Primitive as StaticType>::static_type().into_glib()
+}
+
+/// Returns the `GType` for `BlendMode`.
+///
+/// # Safety
+///
+/// This function is safe to call from C.
+#[unsafe(no_mangle)]
+pub extern "C" fn gtk_gl_shaders_blend_mode_get_type() -> GType {
+    <BlendMode as StaticType>::static_type().into_glib()
+}
+
+/// Returns the `GType` for `ComputeSchedule`.
+///
+/// # Safety
+///
+/// This function is safe to call from C.
+#[unsafe(no_mangle)]
+pub extern "C" fn gtk_gl_shaders_compute_schedule_get_type() -> GType {
+    <ComputeSchedule as StaticType>::static_type().into_glib()
+}
+
+/// Returns the `GType` for `UniformSource`.
+///
+/// # Safety
+///
+/// This function is safe to call from C.
+#[unsafe(no_mangle)]
+pub extern "C" fn gtk_gl_shaders_uniform_source_get_type() -> GType {
+    <UniformSource as StaticType>::static_type().into_glib()
+}
+
+/// Returns the `GType` for `UniformType`.
+///
+/// # Safety
+///
+/// This function is safe to call from C.
+#[unsafe(no_mangle)]
+pub extern "C" fn gtk_gl_shaders_uniform_type_get_type() -> GType {
+    <UniformType as StaticType>::static_type().into_glib()
+}
+
+/// Returns the error quark for `ShaderError`.
+///
+/// # Safety
+///
+/// This function is safe to call from C.
+#[unsafe(no_mangle)]
+pub extern "C" fn gtk_gl_shaders_shader_error_quark() -> GQuark {
+    ShaderError::domain().into_glib()
+}
+
+/// Creates a new `ShaderArea` widget.
+///
+/// # Safety
+///
+/// - `shader` must be a valid null-terminated C string
+/// - `textures` must be a valid array of `textures_count` null-terminated C strings (or null)
+/// - `uniforms` must be a valid `GVariant` of type `a{sv}` (or null)
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_new(
+    shader: *const c_char,
+    textures: *const *const c_char,
+    textures_count: u32,
+    uniforms: *mut GVariant,
+) -> *mut ShaderArea {
+    let shader = unsafe { GString::from_glib_none(shader) }
+        .as_str()
+        .to_owned();
+    let textures = unsafe { parse_textures(textures, textures_count) };
+    let uniforms = unsafe { parse_optional_uniforms(uniforms) };
+
+    super::ShaderArea::new(shader, textures, uniforms).to_glib_full()
+}
+
+/// Creates a new `ShaderArea` widget, checking that the shader compiles.
+///
+/// Returns null and sets `error` if the shader fails to compile or link.
+///
+/// # Safety
+///
+/// - `shader` must be a valid null-terminated C string
+/// - `textures` must be a valid array of `textures_count` null-terminated C strings (or null)
+/// - `uniforms` must be a valid `GVariant` of type `a{sv}` (or null)
+/// - `error` must be a valid location for a `GError` (or null)
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_new_checked(
+    shader: *const c_char,
+    textures: *const *const c_char,
+    textures_count: u32,
+    uniforms: *mut GVariant,
+    error: *mut *mut GError,
+) -> *mut ShaderArea {
+    let shader = unsafe { GString::from_glib_none(shader) }
+        .as_str()
+        .to_owned();
+    let textures = unsafe { parse_textures(textures, textures_count) };
+    let uniforms = unsafe { parse_optional_uniforms(uniforms) };
+
+    match super::ShaderArea::new_checked(shader, textures, uniforms) {
+        Ok(this) => this.to_glib_full(),
+        Err(e) => {
+            unsafe { set_error(error, e) };
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Creates a new `ShaderArea` widget from a shader file, hot-reloading the
+/// shader and textures when their files change.
+///
+/// Returns null and sets `error` if the shader file can't be read.
+///
+/// # Safety
+///
+/// - `shader_path` must be a valid null-terminated C string
+/// - `textures` must be a valid array of `textures_count` null-terminated C strings (or null)
+/// - `uniforms` must be a valid `GVariant` of type `a{sv}` (or null)
+/// - `error` must be a valid location for a `GError` (or null)
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_new_from_files(
+    shader_path: *const c_char,
+    textures: *const *const c_char,
+    textures_count: u32,
+    uniforms: *mut GVariant,
+    error: *mut *mut GError,
+) -> *mut ShaderArea {
+    let shader_path = PathBuf::from(
+        unsafe { GString::from_glib_none(shader_path) }
+            .as_str()
+            .to_owned(),
+    );
+    let textures = unsafe { parse_textures(textures, textures_count) };
+    let uniforms = unsafe { parse_optional_uniforms(uniforms) };
+
+    match super::ShaderArea::new_from_files(shader_path, textures, uniforms) {
+        Ok(this) => this.to_glib_full(),
+        Err(e) => {
+            unsafe { set_error(error, e) };
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Creates a new `ShaderArea` widget running a Shadertoy shader.
+///
+/// # Safety
+///
+/// - `shader` must be a valid null-terminated C string
+/// - `textures` must be a valid array of `textures_count` null-terminated C strings (or null)
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_new_shadertoy(
+    shader: *const c_char,
+    textures: *const *const c_char,
+    textures_count: u32,
+) -> *mut ShaderArea {
+    let shader = unsafe { GString::from_glib_none(shader) }
+        .as_str()
+        .to_owned();
+    let textures = unsafe { parse_textures(textures, textures_count) };
+
+    super::ShaderArea::new_shadertoy(shader, textures).to_glib_full()
+}
+
+/// Creates a new `ShaderArea` widget from a Shadertoy JSON export.
+///
+/// Returns null and sets `error` if the export is malformed.
+///
+/// # Safety
+///
+/// - `json` must be a valid null-terminated C string
+/// - `media_dir` must be a valid null-terminated C string (or null)
+/// - `error` must be a valid location for a `GError` (or null)
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_new_from_shadertoy_json(
+    json: *const c_char,
+    media_dir: *const c_char,
+    error: *mut *mut GError,
+) -> *mut ShaderArea {
+    let json = unsafe { GString::from_glib_none(json) };
+    let media_dir: Option<GString> = unsafe { FromGlibPtrNone::from_glib_none(media_dir) };
+
+    match super::ShaderArea::new_from_shadertoy_json(
+        json.as_str(),
+        media_dir.as_ref().map(|dir| Path::new(dir.as_str())),
+    ) {
+        Ok(this) => this.to_glib_full(),
+        Err(e) => {
+            unsafe { set_error(error, e) };
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Creates a new `ShaderArea` widget running an ISF shader.
+///
+/// Returns null and sets `error` if the ISF header is malformed.
+///
+/// # Safety
+///
+/// - `shader` must be a valid null-terminated C string
+/// - `textures` must be a valid array of `textures_count` null-terminated C strings (or null)
+/// - `uniforms` must be a valid `GVariant` of type `a{sv}` (or null)
+/// - `error` must be a valid location for a `GError` (or null)
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_new_isf(
+    shader: *const c_char,
+    textures: *const *const c_char,
+    textures_count: u32,
+    uniforms: *mut GVariant,
+    error: *mut *mut GError,
+) -> *mut ShaderArea {
+    let shader = unsafe { GString::from_glib_none(shader) }
+        .as_str()
+        .to_owned();
+    let textures = unsafe { parse_textures(textures, textures_count) };
+    let uniforms = unsafe { parse_optional_uniforms(uniforms) };
+
+    match super::ShaderArea::new_isf(shader, textures, uniforms) {
+        Ok(this) => this.to_glib_full(),
+        Err(e) => {
+            unsafe { set_error(error, e) };
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Returns the inputs declared by the ISF shader of a `ShaderArea`, as a
+/// `GVariant` of type `aa{sv}`.
+///
+/// # Safety
+///
+/// - `this` must be a valid `ShaderArea` pointer
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_get_isf_inputs(
+    this: *mut ShaderArea,
+) -> *mut GVariant {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    this.isf_inputs().to_glib_full()
+}
+
+/// Creates a new `ShaderArea` widget playing a gl-transitions transition
+/// between two images.
+///
+/// # Safety
+///
+/// - `transition`, `from` and `to` must be valid null-terminated C strings
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_new_transition(
+    transition: *const c_char,
+    from: *const c_char,
+    to: *const c_char,
+) -> *mut ShaderArea {
+    let transition = unsafe { GString::from_glib_none(transition) }
+        .as_str()
+        .to_owned();
+    let from = unsafe { GString::from_glib_none(from) };
+    let to = unsafe { GString::from_glib_none(to) };
+
+    super::ShaderArea::new_transition(
+        transition,
+        PathBuf::from(from.as_str()),
+        PathBuf::from(to.as_str()),
+    )
+    .to_glib_full()
+}
+
+/// Starts the transition of a `ShaderArea` from the beginning.
+///
+/// # Safety
+///
+/// - `this` must be a valid `ShaderArea` pointer
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_start_transition(this: *mut ShaderArea) {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    this.start_transition();
+}
+
+/// Queues an image to transition to after the current transition.
+///
+/// # Safety
+///
+/// - `this` must be a valid `ShaderArea` pointer
+/// - `path` must be a valid null-terminated C string
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_queue_image(
+    this: *mut ShaderArea,
+    path: *const c_char,
+) {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    let path = unsafe { GString::from_glib_none(path) };
+    this.queue_image(PathBuf::from(path.as_str()));
+}
+
+/// Binds a Shadertoy channel of a `ShaderArea` to a sampler.
+///
+/// # Safety
+///
+/// - `this` must be a valid `ShaderArea` pointer
+/// - `pass` and `sampler` must be valid null-terminated C strings (or null)
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_set_channel(
+    this: *mut ShaderArea,
+    pass: *const c_char,
+    channel: u32,
+    sampler: *const c_char,
+) {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    let pass: Option<GString> = unsafe { FromGlibPtrNone::from_glib_none(pass) };
+    let sampler: Option<GString> = unsafe { FromGlibPtrNone::from_glib_none(sampler) };
+
+    this.set_channel(pass.as_deref(), channel, sampler.as_deref());
+}
+
+/// Replaces the fragment shader of a `ShaderArea`.
+///
+/// Returns `false` and sets `error` if the shader fails to compile or link.
+///
+/// # Safety
+///
+/// - `this` must be a valid `ShaderArea` pointer
+/// - `source` must be a valid null-terminated C string
+/// - `error` must be a valid location for a `GError` (or null)
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_set_shader(
+    this: *mut ShaderArea,
+    source: *const c_char,
+    error: *mut *mut GError,
+) -> gboolean {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    let source = unsafe { GString::from_glib_none(source) }
+        .as_str()
+        .to_owned();
+
+    match this.set_shader(source) {
+        Ok(()) => true.into_glib(),
+        Err(e) => {
+            unsafe { set_error(error, e) };
+            false.into_glib()
+        }
+    }
+}
+
+/// Replaces the vertex shader of a `ShaderArea`, or restores the fullscreen
+/// quad if `source` is null.
+///
+/// Returns `false` and sets `error` if the shaders fail to compile or link.
+///
+/// # Safety
+///
+/// - `this` must be a valid `ShaderArea` pointer
+/// - `source` must be a valid null-terminated C string (or null)
+/// - `error` must be a valid location for a `GError` (or null)
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_set_vertex_shader(
+    this: *mut ShaderArea,
+    source: *const c_char,
+    error: *mut *mut GError,
+) -> gboolean {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    let source: Option<GString> = unsafe { FromGlibPtrNone::from_glib_none(source) };
+
+    match this.set_vertex_shader(source.map(Into::into)) {
+        Ok(()) => true.into_glib(),
+        Err(e) => {
+            unsafe { set_error(error, e) };
+            false.into_glib()
+        }
+    }
+}
+
+/// Sets the particle system of a `ShaderArea`, or removes it if `update` is
+/// null.
+///
+/// Returns `false` and sets `error` if the shaders fail to compile or link.
+///
+/// # Safety
+///
+/// - `this` must be a valid `ShaderArea` pointer
+/// - `update`, `vertex` and `fragment` must be valid null-terminated C
+///   strings (or null)
+/// - `error` must be a valid location for a `GError` (or null)
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_set_particles(
+    this: *mut ShaderArea,
+    update: *const c_char,
+    vertex: *const c_char,
+    fragment: *const c_char,
+    buffers: u32,
+    error: *mut *mut GError,
+) -> gboolean {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    let update: Option<GString> = unsafe { FromGlibPtrNone::from_glib_none(update) };
+    let vertex: Option<GString> = unsafe { FromGlibPtrNone::from_glib_none(vertex) };
+    let fragment: Option<GString> = unsafe { FromGlibPtrNone::from_glib_none(fragment) };
+
+    let particles = update.map(|update| Particles {
+        buffers,
+        ..Particles::new(
+            update,
+            vertex.unwrap_or_default(),
+            fragment.unwrap_or_default(),
+        )
+    });
+    match this.set_particles(particles) {
+        Ok(()) => true.into_glib(),
+        Err(e) => {
+            unsafe { set_error(error, e) };
+            false.into_glib()
+        }
+    }
+}
+
+/// Adds an offscreen render pass to a `ShaderArea`.
+///
+/// The pass has a fixed size of `width`×`height` pixels, or `scale` times the
+/// size of the widget if either is 0. Returns `false` and sets `error` if the
+/// pass is invalid or fails to compile or link.
+///
+/// # Safety
+///
+/// - `this` must be a valid `ShaderArea` pointer
+/// - `name` and `shader` must be valid null-terminated C strings
+/// - `error` must be a valid location for a `GError` (or null)
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_add_pass(
+    this: *mut ShaderArea,
+    name: *const c_char,
+    shader: *const c_char,
+    width: u32,
+    height: u32,
+    scale: f32,
+    format: i32,
+    filter: i32,
+    error: *mut *mut GError,
+) -> gboolean {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    let name = unsafe { GString::from_glib_none(name) };
+    let shader = unsafe { GString::from_glib_none(shader) };
+
+    let format = unsafe { PassFormat::try_from_glib(format) };
+    let filter = unsafe { PassFilter::try_from_glib(filter) };
+    let (Ok(format), Ok(filter)) = (format, filter) else {
+        let e = glib::Error::new(ShaderError::Pass, "Invalid pass format or filter");
+        unsafe { set_error(error, e) };
+        return false.into_glib();
+    };
+
+    let mut pass = Pass::new(name.as_str(), shader.as_str());
+    pass.size = if width > 0 && height > 0 {
+        PassSize::Fixed(width, height)
+    } else {
+        PassSize::Scale(scale)
+    };
+    pass.format = format;
+    pass.filter = filter;
+
+    match this.add_pass(pass) {
+        Ok(()) => true.into_glib(),
+        Err(e) => {
+            unsafe { set_error(error, e) };
+            false.into_glib()
+        }
+    }
+}
+
+/// Removes a render pass from a `ShaderArea`, returning whether it existed.
+///
+/// # Safety
+///
+/// - `this` must be a valid `ShaderArea` pointer
+/// - `name` must be a valid null-terminated C string
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_remove_pass(
+    this: *mut ShaderArea,
+    name: *const c_char,
+) -> gboolean {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    let name = unsafe { GString::from_glib_none(name) };
+    this.remove_pass(name.as_str()).into_glib()
+}
+
+/// Adds a compute pass to a `ShaderArea`.
+///
+/// The pass dispatches `groups_x`×`groups_y`×`groups_z` work groups of
+/// `local_x`×`local_y`×`local_z` invocations. Returns `false` and sets
+/// `error` if the pass is invalid, unsupported or fails to compile.
+///
+/// # Safety
+///
+/// - `this` must be a valid `ShaderArea` pointer
+/// - `name` and `shader` must be valid null-terminated C strings
+/// - `error` must be a valid location for a `GError` (or null)
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_add_compute(
+    this: *mut ShaderArea,
+    name: *const c_char,
+    shader: *const c_char,
+    local_x: u32,
+    local_y: u32,
+    local_z: u32,
+    groups_x: u32,
+    groups_y: u32,
+    groups_z: u32,
+    schedule: i32,
+    error: *mut *mut GError,
+) -> gboolean {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    let name = unsafe { GString::from_glib_none(name) };
+    let shader = unsafe { GString::from_glib_none(shader) };
+
+    let Ok(schedule) = (unsafe { ComputeSchedule::try_from_glib(schedule) }) else {
+        let e = glib::Error::new(ShaderError::Pass, "Invalid compute schedule");
+        unsafe { set_error(error, e) };
+        return false.into_glib();
+    };
+
+    let pass = ComputePass {
+        local_size: [local_x, local_y, local_z],
+        schedule,
+        ..ComputePass::new(
+            name.as_str(),
+            shader.as_str(),
+            [groups_x, groups_y, groups_z],
+        )
+    };
+    match this.add_compute(pass) {
+        Ok(()) => true.into_glib(),
+        Err(e) => {
+            unsafe { set_error(error, e) };
+            false.into_glib()
+        }
+    }
+}
+
+/// Removes a compute pass from a `ShaderArea`, returning whether it existed.
+///
+/// # Safety
+///
+/// - `this` must be a valid `ShaderArea` pointer
+/// - `name` must be a valid null-terminated C string
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_remove_compute(
+    this: *mut ShaderArea,
+    name: *const c_char,
+) -> gboolean {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    let name = unsafe { GString::from_glib_none(name) };
+    this.remove_compute(name.as_str()).into_glib()
+}
+
+/// Dispatches an on-demand compute pass of a `ShaderArea` on the next frame.
+///
+/// # Safety
+///
+/// - `this` must be a valid `ShaderArea` pointer
+/// - `name` must be a valid null-terminated C string
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_run_compute(
+    this: *mut ShaderArea,
+    name: *const c_char,
+) -> gboolean {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    let name = unsafe { GString::from_glib_none(name) };
+    this.run_compute(name.as_str()).into_glib()
+}
+
+/// Creates a storage buffer on a `ShaderArea`, or replaces its contents.
+///
+/// Returns `false` and sets `error` if the buffer is invalid or unsupported.
+///
+/// # Safety
+///
+/// - `this` must be a valid `ShaderArea` pointer
+/// - `name` must be a valid null-terminated C string
+/// - `data` must be a valid `GBytes`
+/// - `error` must be a valid location for a `GError` (or null)
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_set_storage_buffer(
+    this: *mut ShaderArea,
+    name: *const c_char,
+    data: *mut GBytes,
+    error: *mut *mut GError,
+) -> gboolean {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    let name = unsafe { GString::from_glib_none(name) };
+    let data = unsafe { glib::Bytes::from_glib_none(data) };
+
+    let buffer = StorageBuffer {
+        name: name.into(),
+        data,
+    };
+    match this.set_storage_buffer(buffer) {
+        Ok(()) => true.into_glib(),
+        Err(e) => {
+            unsafe { set_error(error, e) };
+            false.into_glib()
+        }
+    }
+}
+
+/// Creates a storage image on a `ShaderArea`, or replaces it.
+///
+/// Returns `false` and sets `error` if the image is invalid or unsupported.
+///
+/// # Safety
+///
+/// - `this` must be a valid `ShaderArea` pointer
+/// - `name` must be a valid null-terminated C string
+/// - `data` must be a valid `GBytes` (or null)
+/// - `error` must be a valid location for a `GError` (or null)
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_set_storage_image(
+    this: *mut ShaderArea,
+    name: *const c_char,
+    width: u32,
+    height: u32,
+    format: i32,
+    data: *mut GBytes,
+    error: *mut *mut GError,
+) -> gboolean {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    let name = unsafe { GString::from_glib_none(name) };
+    let data: Option<glib::Bytes> = unsafe { FromGlibPtrNone::from_glib_none(data) };
+
+    let Ok(format) = (unsafe { PassFormat::try_from_glib(format) }) else {
+        let e = glib::Error::new(ShaderError::Pass, "Invalid storage image format");
+        unsafe { set_error(error, e) };
+        return false.into_glib();
+    };
+
+    let image = StorageImage {
+        name: name.into(),
+        width,
+        height,
+        format,
+        data,
+    };
+    match this.set_storage_image(image) {
+        Ok(()) => true.into_glib(),
+        Err(e) => {
+            unsafe { set_error(error, e) };
+            false.into_glib()
+        }
+    }
+}
+
+/// Reads back the contents of a storage buffer of a `ShaderArea`.
+///
+/// Returns null if the widget isn't realized with that buffer.
+///
+/// # Safety
+///
+/// - `this` must be a valid `ShaderArea` pointer
+/// - `name` must be a valid null-terminated C string
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_read_storage_buffer(
+    this: *mut ShaderArea,
+    name: *const c_char,
+) -> *mut GBytes {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    let name = unsafe { GString::from_glib_none(name) };
+    this.read_storage_buffer(name.as_str())
+        .map_or(ptr::null_mut(), |bytes| bytes.to_glib_full())
+}
+
+/// Reads back the pixels of a storage image of a `ShaderArea`.
+///
+/// Returns null if the widget isn't realized with that image.
+///
+/// # Safety
+///
+/// - `this` must be a valid `ShaderArea` pointer
+/// - `name` must be a valid null-terminated C string
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_read_storage_image(
+    this: *mut ShaderArea,
+    name: *const c_char,
+) -> *mut GBytes {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    let name = unsafe { GString::from_glib_none(name) };
+    this.read_storage_image(name.as_str())
+        .map_or(ptr::null_mut(), |bytes| bytes.to_glib_full())
+}
+
+/// Loads the mesh of a `ShaderArea` from an OBJ, glTF or GLB file, or
+/// removes it if `path` is null.
+///
+/// Returns `false` and sets `error` if the file can't be read or parsed.
+///
+/// # Safety
+///
+/// - `this` must be a valid `ShaderArea` pointer
+/// - `path` must be a valid null-terminated C string (or null)
+/// - `error` must be a valid location for a `GError` (or null)
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_load_mesh(
+    this: *mut ShaderArea,
+    path: *const c_char,
+    error: *mut *mut GError,
+) -> gboolean {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    let path: Option<GString> = unsafe { FromGlibPtrNone::from_glib_none(path) };
+
+    let Some(path) = path else {
+        this.set_mesh(None);
+        return true.into_glib();
+    };
+    match this.load_mesh(Path::new(path.as_str())) {
+        Ok(()) => true.into_glib(),
+        Err(e) => {
+            unsafe { set_error(error, e) };
+            false.into_glib()
+        }
+    }
+}
+
+/// Resets the orbit camera of a `ShaderArea`.
+///
+/// # Safety
+///
+/// `this` must be a valid `ShaderArea` pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_reset_camera(this: *mut ShaderArea) {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    this.reset_camera();
+}
+
+/// Resets the view transform of a `ShaderArea`.
 ///
 /// # Safety
 ///
-/// This function is safe to call from C.
+/// `this` must be a valid `ShaderArea` pointer.
 #[unsafe(no_mangle)]
-pub extern "C" fn gtk_gl_shaders_shader_area_get_type() -> GType {
-    <super::ShaderArea as StaticType>::static_type().into_glib()
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_reset_view(this: *mut ShaderArea) {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    this.reset_view();
 }
 
-/// Creates a new `ShaderArea` widget.
+/// Stores a `glib::Error` into a `GError` out-parameter, if one was given.
 ///
 /// # Safety
 ///
-/// - `shader` must be a valid null-terminated C string
-/// - `textures` must be a valid array of `textures_count` null-terminated C strings (or null)
-/// - `uniforms` must be a valid `GVariant` of type `a{sv}` (or null)
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn gtk_gl_shaders_shader_area_new(
-    shader: *const c_char,
-    textures: *const *const c_char,
-    textures_count: u32,
-    uniforms: *mut GVariant,
-) -> *mut ShaderArea {
-    let shader = unsafe { GString::from_glib_none(shader) }
-        .as_str()
-        .to_owned();
+/// `error` must be a valid location for a `GError` (or null).
+unsafe fn set_error(error: *mut *mut GError, e: glib::Error) {
+    if !error.is_null() {
+        unsafe { *error = e.into_glib_ptr() };
+    }
+}
 
-    let textures = if textures.is_null() {
-        Vec::new()
-    } else {
-        unsafe {
-            (0..textures_count)
-                .map(|i| {
-                    let path = *textures.add(i as usize);
-                    PathBuf::from(GString::from_glib_none(path).as_str().to_owned())
-                })
-                .collect()
-        }
-    };
+/// Converts a C array of paths into a `Vec` of `PathBuf`s.
+///
+/// # Safety
+///
+/// `textures` must be a valid array of `textures_count` null-terminated C strings (or null).
+unsafe fn parse_textures(textures: *const *const c_char, textures_count: u32) -> Vec<PathBuf> {
+    if textures.is_null() {
+        return Vec::new();
+    }
+
+    unsafe {
+        (0..textures_count)
+            .map(|i| {
+                let path = *textures.add(i as usize);
+                PathBuf::from(GString::from_glib_none(path).as_str().to_owned())
+            })
+            .collect()
+    }
+}
 
-    let uniforms = if uniforms.is_null() {
+/// Parses an optional `GVariant` dictionary into a `HashMap` of uniforms.
+///
+/// # Safety
+///
+/// `uniforms` must be a valid `GVariant` of type `a{sv}` (or null).
+unsafe fn parse_optional_uniforms(uniforms: *mut GVariant) -> HashMap<String, Uniform> {
+    if uniforms.is_null() {
         HashMap::new()
     } else {
         parse_uniforms(unsafe { Variant::from_glib_none(uniforms) })
-    };
-
-    super::ShaderArea::new(shader, textures, uniforms).to_glib_full()
+    }
 }
 
-/// Parses a `GVariant` dictionary into a `HashMap` of uniforms.
+/// Parses a `GVariant` dictionary into a `HashMap` of uniforms, logging
+/// and skipping the values that can't be parsed.
 fn parse_uniforms(variant: Variant) -> HashMap<String, Uniform> {
-    let mut result = HashMap::new();
-
     let Some(uniforms) = variant.get::<HashMap<String, Variant>>() else {
         error!("Invalid value passed to `uniforms` - expected a{{sv}} dictionary");
-        return result;
-    };
-
-    for (name, value) in uniforms {
-        let uniform = if let Some(v) = value.get::<f64>() {
-            // Variant can't contain f32, so we cast from f64
-            Uniform::Float(v as f32)
-        } else if let Some(v) = value.get::<Vec<f64>>() {
-            match v.len() {
-                2 => Uniform::Vec2([v[0] as f32, v[1] as f32]),
-                3 => Uniform::Vec3([v[0] as f32, v[1] as f32, v[2] as f32]),
-                4 => Uniform::Vec4([v[0] as f32, v[1] as f32, v[2] as f32, v[3] as f32]),
-                n => {
-                    error!(
-                        "Uniform '{name}' has invalid number of elements: {n} (expected 2, 3, or 4)"
-                    );
-                    continue;
-                }
-            }
-        } else if let Some(v) = value.get::<i32>() {
-            Uniform::Int(v)
-        } else if let Some(v) = value.get::<Vec<i32>>() {
-            match v.len() {
-                2 => Uniform::IVec2([v[0], v[1]]),
-                3 => Uniform::IVec3([v[0], v[1], v[2]]),
-                4 => Uniform::IVec4([v[0], v[1], v[2], v[3]]),
-                n => {
-                    error!(
-                        "Uniform '{name}' has invalid number of elements: {n} (expected 2, 3, or 4)"
-                    );
-                    continue;
-                }
+        return HashMap::new();
+    };
+
+    uniforms
+        .into_iter()
+        .filter_map(|(name, value)| match parse_uniform(&name, &value) {
+            Ok(uniform) => Some((name, uniform)),
+            Err(e) => {
+                error!("{e}");
+                None
             }
-        } else {
-            error!("Uniform '{name}' has unsupported type");
-            continue;
+        })
+        .collect()
+}
+
+/// Parses a `GVariant` dictionary into a `HashMap` of uniforms, failing on
+/// the first value that can't be parsed.
+fn try_parse_uniforms(variant: &Variant) -> Result<HashMap<String, Uniform>, glib::Error> {
+    let Some(uniforms) = variant.get::<HashMap<String, Variant>>() else {
+        return Err(glib::Error::new(
+            ShaderError::Uniform,
+            "Invalid uniforms - expected a{sv} dictionary",
+        ));
+    };
+
+    uniforms
+        .into_iter()
+        .map(|(name, value)| {
+            let uniform = parse_uniform(&name, &value)
+                .map_err(|e| glib::Error::new(ShaderError::Uniform, &e))?;
+            Ok((name, uniform))
+        })
+        .collect()
+}
+
+/// Parses the value of a uniform from a `GVariant`.
+fn parse_uniform(name: &str, value: &Variant) -> Result<Uniform, String> {
+    let floats = |v: &[f64]| v.iter().map(|&x| x as f32).collect::<Vec<_>>();
+    // Doubles keep their precision until they're converted to the type
+    // declared by the shader, usually `float`
+    let uniform = if let Some(v) = value.get::<f64>() {
+        Uniform::Double(v)
+    } else if let Some(v) = value.get::<Vec<f64>>() {
+        // Four doubles are a dvec4; a mat2 is passed as nested arrays, and
+        // other lengths are double arrays
+        match v.len() {
+            0 => return Err(format!("Uniform '{name}' is an empty array")),
+            2 => Uniform::DVec2([v[0], v[1]]),
+            3 => Uniform::DVec3([v[0], v[1], v[2]]),
+            4 => Uniform::DVec4([v[0], v[1], v[2], v[3]]),
+            9 => Uniform::Mat3(floats(&v).try_into().expect("length checked")),
+            16 => Uniform::Mat4(floats(&v).try_into().expect("length checked")),
+            _ => Uniform::DoubleArray(v),
+        }
+    } else if let Some(elements) = value.get::<Vec<Vec<f64>>>() {
+        // Square arrays are matrices, given as their columns
+        let Some(uniform) = parse_matrix(&elements).or_else(|| {
+            let (components, flat) = flatten(&elements)?;
+            Uniform::double_array(components, &flat)
+        }) else {
+            return Err(format!(
+                "Uniform '{name}' is neither a matrix nor an array of vectors of 1 to 4 components"
+            ));
+        };
+        uniform
+    } else if let Some(v) = value.get::<i32>() {
+        Uniform::Int(v)
+    } else if let Some(v) = value.get::<Vec<i32>>() {
+        match v.len() {
+            0 => return Err(format!("Uniform '{name}' is an empty array")),
+            2 => Uniform::IVec2([v[0], v[1]]),
+            3 => Uniform::IVec3([v[0], v[1], v[2]]),
+            4 => Uniform::IVec4([v[0], v[1], v[2], v[3]]),
+            _ => Uniform::IntArray(v),
+        }
+    } else if let Some(elements) = value.get::<Vec<Vec<i32>>>() {
+        let Some(uniform) =
+            flatten(&elements).and_then(|(components, flat)| Uniform::int_array(components, &flat))
+        else {
+            return Err(format!(
+                "Uniform '{name}' is not an array of vectors of 1 to 4 components"
+            ));
+        };
+        uniform
+    } else if let Some(v) = value.get::<bool>() {
+        Uniform::Bool(v)
+    } else if let Some(v) = value.get::<u32>() {
+        Uniform::UInt(v)
+    } else if let Some(v) = value.get::<Vec<u32>>() {
+        match v.len() {
+            0 => return Err(format!("Uniform '{name}' is an empty array")),
+            2 => Uniform::UVec2([v[0], v[1]]),
+            3 => Uniform::UVec3([v[0], v[1], v[2]]),
+            4 => Uniform::UVec4([v[0], v[1], v[2], v[3]]),
+            _ => Uniform::UIntArray(v),
+        }
+    } else if let Some(elements) = value.get::<Vec<Vec<u32>>>() {
+        let Some(uniform) = flatten(&elements)
+            .and_then(|(components, flat)| Uniform::uint_array(components, &flat))
+        else {
+            return Err(format!(
+                "Uniform '{name}' is not an array of vectors of 1 to 4 components"
+            ));
         };
-        result.insert(name, uniform);
+        uniform
+    } else if let Some(v) = value.get::<Vec<bool>>() {
+        // Boolean vectors and arrays are set like integer ones
+        let v: Vec<i32> = v.into_iter().map(i32::from).collect();
+        match v.len() {
+            0 => return Err(format!("Uniform '{name}' is an empty array")),
+            2 => Uniform::IVec2([v[0], v[1]]),
+            3 => Uniform::IVec3([v[0], v[1], v[2]]),
+            4 => Uniform::IVec4([v[0], v[1], v[2], v[3]]),
+            _ => Uniform::IntArray(v),
+        }
+    } else {
+        return Err(format!("Uniform '{name}' has unsupported type"));
+    };
+    Ok(uniform)
+}
+
+/// Parses a matrix given as an array of columns.
+fn parse_matrix(columns: &[Vec<f64>]) -> Option<Uniform> {
+    let size = columns.len();
+    if !(2..=4).contains(&size) || columns.iter().any(|column| column.len() != size) {
+        return None;
     }
+    let v: Vec<f32> = columns.iter().flatten().map(|&x| x as f32).collect();
+    Some(match size {
+        2 => Uniform::Mat2(v.try_into().ok()?),
+        3 => Uniform::Mat3(v.try_into().ok()?),
+        _ => Uniform::Mat4(v.try_into().ok()?),
+    })
+}
 
-    result
+/// Flattens an array of vectors of the same length, returning that length.
+fn flatten<T: Copy>(elements: &[Vec<T>]) -> Option<(usize, Vec<T>)> {
+    let components = elements.first()?.len();
+    elements
+        .iter()
+        .all(|element| element.len() == components)
+        .then(|| (components, elements.concat()))
 }
 
 /// Macro to generate uniform setter FFI functions.
@@ -188,3 +1102,463 @@ pub unsafe extern "C" fn gtk_gl_shaders_shader_area_set_uniform_int(
 generate_uniform_setter!(gtk_gl_shaders_shader_area_set_uniform_ivec2, IVec2, a: i32, b: i32);
 generate_uniform_setter!(gtk_gl_shaders_shader_area_set_uniform_ivec3, IVec3, a: i32, b: i32, c: i32);
 generate_uniform_setter!(gtk_gl_shaders_shader_area_set_uniform_ivec4, IVec4, a: i32, b: i32, c: i32, d: i32);
+
+// Bool setter
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_set_uniform_bool(
+    this: *mut ShaderArea,
+    name: *const c_char,
+    value: gboolean,
+) {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    let name = unsafe { GString::from_glib_none(name) }.as_str().to_owned();
+    this.set_uniform(name, Uniform::Bool(value != glib::ffi::GFALSE));
+}
+
+// Unsigned int setter (single value)
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_set_uniform_uint(
+    this: *mut ShaderArea,
+    name: *const c_char,
+    value: u32,
+) {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    let name = unsafe { GString::from_glib_none(name) }.as_str().to_owned();
+    this.set_uniform(name, Uniform::UInt(value));
+}
+
+// Unsigned integer vector setters
+generate_uniform_setter!(gtk_gl_shaders_shader_area_set_uniform_uvec2, UVec2, a: u32, b: u32);
+generate_uniform_setter!(gtk_gl_shaders_shader_area_set_uniform_uvec3, UVec3, a: u32, b: u32, c: u32);
+generate_uniform_setter!(gtk_gl_shaders_shader_area_set_uniform_uvec4, UVec4, a: u32, b: u32, c: u32, d: u32);
+
+// Double setter (single value)
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_set_uniform_double(
+    this: *mut ShaderArea,
+    name: *const c_char,
+    value: f64,
+) {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    let name = unsafe { GString::from_glib_none(name) }.as_str().to_owned();
+    this.set_uniform(name, Uniform::Double(value));
+}
+
+// Double vector setters
+generate_uniform_setter!(gtk_gl_shaders_shader_area_set_uniform_dvec2, DVec2, a: f64, b: f64);
+generate_uniform_setter!(gtk_gl_shaders_shader_area_set_uniform_dvec3, DVec3, a: f64, b: f64, c: f64);
+generate_uniform_setter!(gtk_gl_shaders_shader_area_set_uniform_dvec4, DVec4, a: f64, b: f64, c: f64, d: f64);
+
+/// Macro to generate matrix uniform setter FFI functions, taking the matrix
+/// as a C array in column-major order.
+macro_rules! generate_matrix_setter {
+    ($name:ident, $variant:ident, $len:literal) => {
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn $name(
+            this: *mut ShaderArea,
+            name: *const c_char,
+            values: *const f32,
+        ) {
+            let this = unsafe { super::ShaderArea::from_glib_none(this) };
+            let name = unsafe { GString::from_glib_none(name) }.as_str().to_owned();
+            let values = unsafe { *values.cast::<[f32; $len]>() };
+            this.set_uniform(name, Uniform::$variant(values));
+        }
+    };
+}
+
+// Matrix setters
+generate_matrix_setter!(gtk_gl_shaders_shader_area_set_uniform_mat2, Mat2, 4);
+generate_matrix_setter!(gtk_gl_shaders_shader_area_set_uniform_mat3, Mat3, 9);
+generate_matrix_setter!(gtk_gl_shaders_shader_area_set_uniform_mat4, Mat4, 16);
+
+/// Macro to generate FFI setters taking a GTK or graphene value, converted
+/// with the matching `From` implementation of `Uniform`.
+macro_rules! generate_boxed_setter {
+    ($name:ident, $ty:ty, $ffi:ty) => {
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn $name(
+            this: *mut ShaderArea,
+            name: *const c_char,
+            value: *const $ffi,
+        ) {
+            let this = unsafe { super::ShaderArea::from_glib_none(this) };
+            let name = unsafe { GString::from_glib_none(name) }.as_str().to_owned();
+            let value: $ty = unsafe { FromGlibPtrNone::from_glib_none(value) };
+            this.set_uniform(name, value.into());
+        }
+    };
+}
+
+// GTK and graphene setters
+generate_boxed_setter!(
+    gtk_gl_shaders_shader_area_set_uniform_matrix,
+    graphene::Matrix,
+    graphene::ffi::graphene_matrix_t
+);
+generate_boxed_setter!(
+    gtk_gl_shaders_shader_area_set_uniform_graphene_vec2,
+    graphene::Vec2,
+    graphene::ffi::graphene_vec2_t
+);
+generate_boxed_setter!(
+    gtk_gl_shaders_shader_area_set_uniform_graphene_vec3,
+    graphene::Vec3,
+    graphene::ffi::graphene_vec3_t
+);
+generate_boxed_setter!(
+    gtk_gl_shaders_shader_area_set_uniform_graphene_vec4,
+    graphene::Vec4,
+    graphene::ffi::graphene_vec4_t
+);
+generate_boxed_setter!(
+    gtk_gl_shaders_shader_area_set_uniform_point,
+    graphene::Point,
+    graphene::ffi::graphene_point_t
+);
+generate_boxed_setter!(
+    gtk_gl_shaders_shader_area_set_uniform_rect,
+    graphene::Rect,
+    graphene::ffi::graphene_rect_t
+);
+generate_boxed_setter!(
+    gtk_gl_shaders_shader_area_set_uniform_rgba,
+    gdk::RGBA,
+    gdk::ffi::GdkRGBA
+);
+
+/// Sets an array uniform of a `ShaderArea` from packed components.
+///
+/// Returns `false` and sets `error` if `data` doesn't hold a whole number of
+/// elements.
+///
+/// # Safety
+///
+/// - `this` must be a valid `ShaderArea` pointer
+/// - `name` must be a valid null-terminated C string
+/// - `data` must be a valid `GBytes`
+/// - `error` must be a valid location for a `GError` (or null)
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_set_uniform_array(
+    this: *mut ShaderArea,
+    name: *const c_char,
+    ty: i32,
+    data: *mut GBytes,
+    error: *mut *mut GError,
+) -> gboolean {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    let name = unsafe { GString::from_glib_none(name) };
+    let data = unsafe { glib::Bytes::from_glib_none(data) };
+
+    let uniform = unsafe { UniformType::try_from_glib(ty) }
+        .ok()
+        .and_then(|ty| Uniform::array_from_bytes(ty, &data));
+    unsafe { set_array(&this, name, uniform, error) }
+}
+
+/// Sets a `float` or `vecN` array uniform of a `ShaderArea`.
+///
+/// Returns `false` and sets `error` if `values` doesn't hold a whole number
+/// of elements of `components` components.
+///
+/// # Safety
+///
+/// - `this` must be a valid `ShaderArea` pointer
+/// - `name` must be a valid null-terminated C string
+/// - `values` must point to `n_values` floats
+/// - `error` must be a valid location for a `GError` (or null)
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_set_uniform_float_array(
+    this: *mut ShaderArea,
+    name: *const c_char,
+    components: u32,
+    values: *const f32,
+    n_values: u32,
+    error: *mut *mut GError,
+) -> gboolean {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    let name = unsafe { GString::from_glib_none(name) };
+    let values = unsafe { slice_or_empty(values, n_values) };
+
+    let uniform = Uniform::float_array(components as usize, values);
+    unsafe { set_array(&this, name, uniform, error) }
+}
+
+/// Sets an `int` or `ivecN` array uniform of a `ShaderArea`.
+///
+/// Returns `false` and sets `error` if `values` doesn't hold a whole number
+/// of elements of `components` components.
+///
+/// # Safety
+///
+/// - `this` must be a valid `ShaderArea` pointer
+/// - `name` must be a valid null-terminated C string
+/// - `values` must point to `n_values` integers
+/// - `error` must be a valid location for a `GError` (or null)
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_set_uniform_int_array(
+    this: *mut ShaderArea,
+    name: *const c_char,
+    components: u32,
+    values: *const i32,
+    n_values: u32,
+    error: *mut *mut GError,
+) -> gboolean {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    let name = unsafe { GString::from_glib_none(name) };
+    let values = unsafe { slice_or_empty(values, n_values) };
+
+    let uniform = Uniform::int_array(components as usize, values);
+    unsafe { set_array(&this, name, uniform, error) }
+}
+
+/// Sets an array uniform built by one of the array setters, or reports that
+/// its values don't form whole elements or don't fit in the array declared
+/// by the shaders.
+///
+/// # Safety
+///
+/// `error` must be a valid location for a `GError` (or null).
+unsafe fn set_array(
+    this: &super::ShaderArea,
+    name: GString,
+    uniform: Option<Uniform>,
+    error: *mut *mut GError,
+) -> gboolean {
+    let Some(uniform) = uniform else {
+        let e = glib::Error::new(
+            ShaderError::Uniform,
+            &format!("Values of uniform array `{name}` don't form whole elements"),
+        );
+        unsafe { set_error(error, e) };
+        return false.into_glib();
+    };
+    match this.set_uniforms(HashMap::from([(name.into(), uniform)])) {
+        Ok(()) => true.into_glib(),
+        Err(e) => {
+            unsafe { set_error(error, e) };
+            false.into_glib()
+        }
+    }
+}
+
+/// Borrows a C array as a slice, empty if the pointer is null.
+///
+/// # Safety
+///
+/// `values` must point to `len` elements (or be null).
+unsafe fn slice_or_empty<'a, T>(values: *const T, len: u32) -> &'a [T] {
+    if values.is_null() {
+        &[]
+    } else {
+        unsafe { std::slice::from_raw_parts(values, len as usize) }
+    }
+}
+
+/// Sets several uniforms of a `ShaderArea` at once from a `GVariant` of
+/// type `a{sv}`, so that they're applied to the same frame.
+///
+/// Returns `false` and sets `error`, without setting any uniform, if a value
+/// can't be parsed or converted to the type declared by the shaders.
+///
+/// # Safety
+///
+/// - `this` must be a valid `ShaderArea` pointer
+/// - `uniforms` must be a valid `GVariant` of type `a{sv}`
+/// - `error` must be a valid location for a `GError` (or null)
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_set_uniforms(
+    this: *mut ShaderArea,
+    uniforms: *mut GVariant,
+    error: *mut *mut GError,
+) -> gboolean {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    let uniforms = unsafe { Variant::from_glib_none(uniforms) };
+
+    match try_parse_uniforms(&uniforms).and_then(|uniforms| this.set_uniforms(uniforms)) {
+        Ok(()) => true.into_glib(),
+        Err(e) => {
+            unsafe { set_error(error, e) };
+            false.into_glib()
+        }
+    }
+}
+
+/// Starts a uniform transaction on a `ShaderArea`: uniforms set until the
+/// matching commit are applied together.
+///
+/// # Safety
+///
+/// - `this` must be a valid `ShaderArea` pointer
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_begin_uniforms(this: *mut ShaderArea) {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    this.begin_uniforms();
+}
+
+/// Commits a uniform transaction of a `ShaderArea`.
+///
+/// Returns `false` and sets `error`, without setting any uniform, if a value
+/// can't be converted to the type declared by the shaders.
+///
+/// # Safety
+///
+/// - `this` must be a valid `ShaderArea` pointer
+/// - `error` must be a valid location for a `GError` (or null)
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_commit_uniforms(
+    this: *mut ShaderArea,
+    error: *mut *mut GError,
+) -> gboolean {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    match this.commit_uniforms() {
+        Ok(()) => true.into_glib(),
+        Err(e) => {
+            unsafe { set_error(error, e) };
+            false.into_glib()
+        }
+    }
+}
+
+/// Aborts the uniform transactions of a `ShaderArea`, dropping the uniforms
+/// set since the outermost one was started.
+///
+/// # Safety
+///
+/// - `this` must be a valid `ShaderArea` pointer
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_abort_uniforms(this: *mut ShaderArea) {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    this.abort_uniforms();
+}
+
+/// Sets the contents of a uniform block of a `ShaderArea` from bytes laid
+/// out as in the shader.
+///
+/// Returns `false` and sets `error` if the block name is invalid or `data`
+/// is empty.
+///
+/// # Safety
+///
+/// - `this` must be a valid `ShaderArea` pointer
+/// - `name` must be a valid null-terminated C string
+/// - `data` must be a valid `GBytes`
+/// - `error` must be a valid location for a `GError` (or null)
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_set_uniform_block(
+    this: *mut ShaderArea,
+    name: *const c_char,
+    data: *mut GBytes,
+    error: *mut *mut GError,
+) -> gboolean {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    let name = unsafe { GString::from_glib_none(name) };
+    let data = unsafe { glib::Bytes::from_glib_none(data) };
+
+    let block = UniformBlock {
+        name: name.into(),
+        contents: BlockContents::Bytes(data),
+    };
+    unsafe { set_block(&this, block, error) }
+}
+
+/// Sets the contents of a uniform block of a `ShaderArea` from the values of
+/// its members, packed at the offsets reported by OpenGL.
+///
+/// Returns `false` and sets `error` if the block name is invalid or `members`
+/// is empty.
+///
+/// # Safety
+///
+/// - `this` must be a valid `ShaderArea` pointer
+/// - `name` must be a valid null-terminated C string
+/// - `members` must be a valid `GVariant` of type `a{sv}`
+/// - `error` must be a valid location for a `GError` (or null)
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_set_uniform_block_members(
+    this: *mut ShaderArea,
+    name: *const c_char,
+    members: *mut GVariant,
+    error: *mut *mut GError,
+) -> gboolean {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    let name = unsafe { GString::from_glib_none(name) };
+    let members = parse_uniforms(unsafe { Variant::from_glib_none(members) });
+
+    let block = UniformBlock {
+        name: name.into(),
+        contents: BlockContents::Members(members),
+    };
+    unsafe { set_block(&this, block, error) }
+}
+
+/// Removes a uniform block from a `ShaderArea`, returning whether it existed.
+///
+/// # Safety
+///
+/// - `this` must be a valid `ShaderArea` pointer
+/// - `name` must be a valid null-terminated C string
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_remove_uniform_block(
+    this: *mut ShaderArea,
+    name: *const c_char,
+) -> gboolean {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    let name = unsafe { GString::from_glib_none(name) };
+    this.remove_uniform_block(name.as_str()).into_glib()
+}
+
+/// Sets a uniform block built by one of the block setters.
+///
+/// # Safety
+///
+/// `error` must be a valid location for a `GError` (or null).
+unsafe fn set_block(
+    this: &super::ShaderArea,
+    block: UniformBlock,
+    error: *mut *mut GError,
+) -> gboolean {
+    match this.set_uniform_block(block) {
+        Ok(()) => true.into_glib(),
+        Err(e) => {
+            unsafe { set_error(error, e) };
+            false.into_glib()
+        }
+    }
+}
+
+/// Lists the uniforms used by the shaders of a `ShaderArea`, samplers and
+/// images excluded, as a `GVariant` of type `aa{sv}`.
+///
+/// # Safety
+///
+/// - `this` must be a valid `ShaderArea` pointer
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_list_uniforms(
+    this: *mut ShaderArea,
+) -> *mut GVariant {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    info_variant(&this.list_uniforms()).to_glib_full()
+}
+
+/// Lists the samplers and images used by the shaders of a `ShaderArea`, as a
+/// `GVariant` of type `aa{sv}`.
+///
+/// # Safety
+///
+/// - `this` must be a valid `ShaderArea` pointer
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gtk_gl_shaders_shader_area_list_samplers(
+    this: *mut ShaderArea,
+) -> *mut GVariant {
+    let this = unsafe { super::ShaderArea::from_glib_none(this) };
+    info_variant(&this.list_samplers()).to_glib_full()
+}
+
+/// Returns a list of uniforms as a `GVariant` of type `aa{sv}`.
+fn info_variant(list: &[UniformInfo]) -> Variant {
+    Variant::array_from_iter_with_type(
+        glib::VariantTy::VARDICT,
+        list.iter().map(UniformInfo::to_variant),
+    )
+}
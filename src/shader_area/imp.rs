@@ -5,43 +5,327 @@
 //! uniform management.
 
 use std::{
-    cell::RefCell,
-    collections::HashMap,
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet, VecDeque},
     ffi::c_void,
     path::{Path, PathBuf},
+    sync::OnceLock,
 };
 
-use glib::Propagation;
-use gtk::{glib, prelude::*, subclass::prelude::*};
+use glib::{Propagation, subclass::Signal};
+use gtk::{gdk, gio, glib, graphene, prelude::*, subclass::prelude::*};
 use image::GenericImageView;
 use log::{error, warn};
 
-use super::Uniform;
+use super::{
+    Uniform,
+    active::{self, ActiveUniform, UniformInfo, UniformSource},
+    block::UniformBlock,
+    builtins::{Builtin, FrameInfo, FrameTimer},
+    camera::{self, OrbitCamera},
+    compute::{self, ComputePass, ComputeSchedule, StorageBuffer, StorageImage},
+    dialect::ShaderDialect,
+    error::{CompileError, ShaderError, ShaderStage},
+    input::{KEYBOARD_ROWS, KEYBOARD_WIDTH, KeyboardState, PointerState},
+    isf, legacy,
+    mesh::{self, Mesh, MeshBuffers},
+    particles::{self, BlendMode, ParticleTarget, Particles},
+    pass::{Pass, PassTarget},
+    shadertoy::{self, CHANNELS, Channels},
+    transition::{self, Easing},
+    uniform,
+    vertex::{self, Primitive},
+    view::{Inertia, ViewTransform},
+};
+
+/// Frames rendered with a uniform transaction open before warning that it
+/// was likely never committed.
+const STALE_TRANSACTION_FRAMES: u32 = 300;
+
+/// Appends the user's code to generated shader source.
+///
+/// The line numbers restart at the user's code, so that driver errors point
+/// into it rather than past the version header and declarations the user
+/// never wrote.
+pub(super) fn append_user_source(shader: &mut String, source: &str) {
+    shader.push_str("#line 1\n");
+    shader.push_str(source);
+}
+
+/// A texture loaded from an image file.
+#[derive(Debug, Clone, Copy, Default)]
+struct Texture {
+    /// OpenGL texture ID (0 if loading failed)
+    id: u32,
+    /// Size of the image in pixels
+    size: [u32; 2],
+}
+
+/// A linked shader program with the locations of its uniforms.
+#[derive(Debug, Default)]
+struct Program {
+    /// OpenGL program ID
+    id: u32,
+    /// Uniform locations and values: name -> (location, value)
+    uniforms: HashMap<String, (i32, Uniform)>,
+    /// Locations and declared types of the builtin uniforms used by the
+    /// shader
+    builtins: Vec<(Builtin, i32, Option<ActiveUniform>)>,
+    /// Uniforms used by the shader, with their declared type
+    active: HashMap<String, ActiveUniform>,
+}
+
+/// An offscreen render pass with its program and output textures.
+#[derive(Debug)]
+struct PassState {
+    /// Configuration of the pass
+    pass: Pass,
+    /// Program of the pass's shader
+    program: Program,
+    /// Framebuffer and output textures
+    target: PassTarget,
+}
+
+/// A compute pass with its program.
+#[derive(Debug)]
+struct ComputeState {
+    /// Configuration of the pass
+    pass: ComputePass,
+    /// Program of the pass's compute shader
+    program: Program,
+    /// Whether an on-demand pass runs on the next frame
+    pending: bool,
+}
+
+/// A particle system with its programs and state textures.
+#[derive(Debug)]
+struct ParticleState {
+    /// Program of the update shader
+    update: Program,
+    /// Program of the sprite shaders
+    sprites: Program,
+    /// Framebuffer and state textures
+    target: ParticleTarget,
+}
 
 /// OpenGL state shared across rendering callbacks.
 #[derive(Debug)]
 struct GLState {
-    /// Linked shader program
-    program: u32,
+    /// Program of the widget's own shader, rendered to the screen
+    program: Program,
+    /// Offscreen passes, rendered in order before `program`
+    passes: Vec<PassState>,
     /// Vertex array object for the fullscreen quad
     vao: u32,
-    /// Loaded texture IDs
-    textures: Vec<u32>,
-    /// Uniform locations and values: name -> (location, value)
-    uniforms: HashMap<String, (i32, Uniform)>,
+    /// Loaded textures, indexed by texture unit
+    textures: Vec<Texture>,
+    /// Frame timings used for the time-based builtins
+    timer: FrameTimer,
+    /// Keyboard texture, created on the first frame with `keyboard-input`
+    /// enabled (0 until then)
+    keyboard_texture: u32,
+    /// Particle system, rendered after `program`
+    particles: Option<ParticleState>,
+    /// Compute passes, dispatched in order before everything else (empty
+    /// if the context doesn't support them)
+    computes: Vec<ComputeState>,
+    /// Storage buffer IDs, indexed by binding point
+    storage_buffers: Vec<u32>,
+    /// Storage image texture IDs, indexed by image unit
+    storage_images: Vec<u32>,
+    /// Uniform buffer IDs, indexed by binding point
+    uniform_blocks: Vec<u32>,
+    /// Mesh drawn by `program` instead of the fullscreen quad
+    mesh: Option<MeshBuffers>,
+    /// Names of the `event` inputs of an ISF shader, reset after each frame
+    isf_events: Vec<String>,
+}
+
+impl GLState {
+    /// Returns the widget's program followed by the program of each pass,
+    /// the programs of the particle system and those of the compute passes.
+    fn programs(&self) -> impl Iterator<Item = &Program> {
+        std::iter::once(&self.program)
+            .chain(self.passes.iter().map(|p| &p.program))
+            .chain(self.particles.iter().flat_map(|p| [&p.update, &p.sprites]))
+            .chain(self.computes.iter().map(|c| &c.program))
+    }
+
+    /// Mutable version of [`Self::programs`].
+    fn programs_mut(&mut self) -> impl Iterator<Item = &mut Program> {
+        std::iter::once(&mut self.program)
+            .chain(self.passes.iter_mut().map(|p| &mut p.program))
+            .chain(
+                self.particles
+                    .iter_mut()
+                    .flat_map(|p| [&mut p.update, &mut p.sprites]),
+            )
+            .chain(self.computes.iter_mut().map(|c| &mut c.program))
+    }
+
+    /// Returns the texture unit of a pass output, after the user's textures
+    /// and the keyboard texture.
+    fn pass_unit(&self, index: usize) -> u32 {
+        (self.textures.len() + 1 + index) as u32
+    }
+
+    /// Returns the texture unit of a particle state buffer, after the pass
+    /// outputs.
+    fn particle_unit(&self, index: usize) -> u32 {
+        self.pass_unit(self.passes.len()) + index as u32
+    }
+
+    /// Returns the texture unit a storage image is sampled from, after the
+    /// particle state.
+    fn storage_unit(&self, index: usize) -> u32 {
+        let particle_buffers = self.particles.as_ref().map_or(0, |p| p.target.buffers());
+        self.particle_unit(particle_buffers) + index as u32
+    }
 }
 
 /// Internal state for the `ShaderArea` widget.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, glib::Properties)]
+#[properties(wrapper_type = super::ShaderArea)]
 pub struct ShaderArea {
     /// OpenGL state (initialized on realize, cleaned up on unrealize)
     gl_state: RefCell<Option<GLState>>,
-    /// Shader source code (set before realize)
-    shader_source: RefCell<Option<String>>,
-    /// Texture paths (set before realize)
-    texture_paths: RefCell<Option<Vec<PathBuf>>>,
-    /// Initial uniforms (set before realize)
-    initial_uniforms: RefCell<Option<HashMap<String, Uniform>>>,
+    /// Shader source code (kept across realize/unrealize cycles)
+    shader_source: RefCell<String>,
+    /// Custom vertex shader source code, `None` for the fullscreen quad
+    vertex_source: RefCell<Option<String>>,
+    /// Primitive type the vertices of a custom vertex shader are drawn as
+    #[property(get, set = Self::set_primitive, builder(Primitive::TriangleStrip))]
+    primitive: Cell<Primitive>,
+    /// Number of vertices drawn by a custom vertex shader
+    #[property(get, set = Self::set_vertex_count, maximum = i32::MAX as u32, default = 4)]
+    vertex_count: Cell<u32>,
+    /// Texture paths (kept across realize/unrealize cycles)
+    texture_paths: RefCell<Vec<PathBuf>>,
+    /// Offscreen render passes (kept across realize/unrealize cycles)
+    passes: RefCell<Vec<Pass>>,
+    /// Dialect of the shader sources
+    #[property(get, set = Self::set_dialect, builder(ShaderDialect::Glsl))]
+    dialect: Cell<ShaderDialect>,
+    /// Shadertoy channels by pass name, with `""` for the widget's shader
+    channels: RefCell<HashMap<String, Channels>>,
+    /// Current uniform values, including those set before the widget was
+    /// realized. This is the source of truth used to rebuild
+    /// `GLState::uniforms` on every realize.
+    uniforms: RefCell<HashMap<String, Uniform>>,
+    /// File monitors for hot-reloading the shader and textures from disk
+    monitors: RefCell<Vec<gio::FileMonitor>>,
+    /// Whether the widget renders on every frame of the frame clock
+    #[property(get, set = Self::set_animate)]
+    animate: Cell<bool>,
+    /// Maximum number of frames per second while animating (0 for no limit)
+    #[property(get, set = Self::set_max_fps)]
+    max_fps: Cell<u32>,
+    /// Whether the widget only renders when a uniform changes
+    #[property(get, set = Self::set_on_demand)]
+    on_demand: Cell<bool>,
+    /// Tick callback driving the animation, while it is running
+    tick_callback: RefCell<Option<gtk::TickCallbackId>>,
+    /// Frame time of the last animation frame, in microseconds
+    last_tick: Cell<Option<i64>>,
+    /// Handler watching the toplevel state for suspension, while mapped
+    toplevel_handler: RefCell<Option<(gdk::Toplevel, glib::SignalHandlerId)>>,
+    /// Whether pointer and touch events update the input builtins
+    #[property(get, set = Self::set_pointer_input)]
+    pointer_input: Cell<bool>,
+    /// Event controllers installed for `pointer-input`
+    pointer_controllers: RefCell<Vec<gtk::EventController>>,
+    /// Pointer and touch state for the input builtins
+    pointer: RefCell<PointerState>,
+    /// Whether key events update the keyboard texture
+    #[property(get, set = Self::set_keyboard_input)]
+    keyboard_input: Cell<bool>,
+    /// Event controllers installed for `keyboard-input`
+    keyboard_controllers: RefCell<Vec<gtk::EventController>>,
+    /// Keyboard state for the keyboard texture
+    keyboard: RefCell<KeyboardState>,
+    /// Whether scroll, drag and touch gestures drive the view transform
+    #[property(get, set = Self::set_view_controls)]
+    view_controls: Cell<bool>,
+    /// Minimum zoom factor of the view (0 for no limit)
+    #[property(get, set = Self::set_min_zoom, minimum = 0.0)]
+    min_zoom: Cell<f64>,
+    /// Maximum zoom factor of the view (0 for no limit)
+    #[property(get, set = Self::set_max_zoom, minimum = 0.0)]
+    max_zoom: Cell<f64>,
+    /// Whether the view keeps moving after a drag is released
+    #[property(get, set)]
+    inertia: Cell<bool>,
+    /// Event controllers installed for `view-controls`
+    view_controllers: RefCell<Vec<gtk::EventController>>,
+    /// Current view transform
+    view: Cell<ViewTransform>,
+    /// Last pointer position over the widget in uv space, used as the anchor
+    /// for scroll zooming
+    view_pointer: Cell<[f64; 2]>,
+    /// Drag offset at the previous drag update
+    view_drag_offset: Cell<[f64; 2]>,
+    /// Scale at the previous pinch update
+    view_zoom_scale: Cell<f64>,
+    /// Velocity of the view after a drag
+    view_inertia: Cell<Inertia>,
+    /// Tick callback moving the view after a drag, while it is running
+    view_inertia_tick: RefCell<Option<gtk::TickCallbackId>>,
+    /// Duration of a transition in seconds
+    #[property(get, set, minimum = 0.0, default = 1.0)]
+    transition_duration: Cell<f64>,
+    /// Easing curve applied to the `progress` of a transition
+    #[property(get, set, builder(Easing::Linear))]
+    transition_easing: Cell<Easing>,
+    /// Images to transition to once the current transition finishes
+    transition_queue: RefCell<VecDeque<PathBuf>>,
+    /// Linear progress of the current transition, from 0 to 1
+    transition_time: Cell<f64>,
+    /// Tick callback driving the transition, while it is running
+    transition_tick: RefCell<Option<gtk::TickCallbackId>>,
+    /// Particle system shaders (kept across realize/unrealize cycles)
+    particles: RefCell<Option<Particles>>,
+    /// Number of particles
+    #[property(get, set = Self::set_particle_count, maximum = i32::MAX as u32, default = 1000)]
+    particle_count: Cell<u32>,
+    /// How particle sprites are blended over the widget's shader
+    #[property(get, set = Self::set_particle_blend, builder(BlendMode::Alpha))]
+    particle_blend: Cell<BlendMode>,
+    /// Whether the OpenGL context supports compute shaders, checked on
+    /// realize
+    #[property(get)]
+    compute_supported: Cell<bool>,
+    /// Whether the OpenGL context supports `double` uniforms, checked on
+    /// realize
+    #[property(get)]
+    fp64_supported: Cell<bool>,
+    /// Compute passes (kept across realize/unrealize cycles)
+    computes: RefCell<Vec<ComputePass>>,
+    /// Storage buffers, with their initial contents
+    storage_buffers: RefCell<Vec<StorageBuffer>>,
+    /// Storage images, with their initial pixels
+    storage_images: RefCell<Vec<StorageImage>>,
+    /// Uniform blocks, with their current contents
+    uniform_blocks: RefCell<Vec<UniformBlock>>,
+    /// Whether `uniforms-changed` is queued
+    uniforms_changed_pending: Cell<bool>,
+    /// Uniform values set inside a transaction, applied when it's committed
+    uniform_batch: RefCell<HashMap<String, Uniform>>,
+    /// Number of `begin_uniforms` calls not committed yet
+    uniform_batch_depth: Cell<u32>,
+    /// Frames rendered since the outermost transaction was started
+    uniform_batch_frames: Cell<u32>,
+    /// Mesh drawn instead of the fullscreen quad (kept across
+    /// realize/unrealize cycles)
+    mesh: RefCell<Option<Mesh>>,
+    /// Whether pointer drags and scrolls move the orbit camera
+    #[property(get, set = Self::set_orbit_camera)]
+    orbit_camera: Cell<bool>,
+    /// Event controllers installed for `orbit-camera`
+    camera_controllers: RefCell<Vec<gtk::EventController>>,
+    /// Current orbit camera
+    camera: Cell<OrbitCamera>,
+    /// Drag offset at the previous camera drag update
+    camera_drag_offset: Cell<[f64; 2]>,
 }
 
 #[glib::object_subclass]
@@ -51,9 +335,37 @@ impl ObjectSubclass for ShaderArea {
     type ParentType = gtk::GLArea;
 }
 
+#[glib::derived_properties]
 impl ObjectImpl for ShaderArea {
     fn constructed(&self) {
         self.parent_constructed();
+        self.transition_duration.set(1.0);
+        self.vertex_count.set(4);
+        self.particle_count.set(1000);
+    }
+
+    fn signals() -> &'static [Signal] {
+        static SIGNALS: OnceLock<Vec<Signal>> = OnceLock::new();
+        SIGNALS.get_or_init(|| {
+            vec![
+                // Emitted when the shader fails to compile or link, with the
+                // stage, the raw driver log, the parsed `a(iis)` messages and
+                // the name of the pass (empty for the widget's own shader)
+                Signal::builder("compile-error")
+                    .param_types([
+                        ShaderStage::static_type(),
+                        String::static_type(),
+                        glib::Variant::static_type(),
+                        String::static_type(),
+                    ])
+                    .build(),
+                // Emitted when a transition reaches the second image
+                Signal::builder("finished").build(),
+                // Emitted after the programs are linked or relinked, when the
+                // uniforms they use may have changed
+                Signal::builder("uniforms-changed").build(),
+            ]
+        })
     }
 }
 
@@ -67,190 +379,2807 @@ impl WidgetImpl for ShaderArea {
             return;
         }
 
-        // Get initialization data
-        let Some(shader) = self.shader_source.borrow_mut().take() else {
-            error!("Shader source not set");
+        // OpenGL ES 3.1 has compute shaders too, but not the readbacks
+        let supported = !self.uses_es()
+            && self
+                .obj()
+                .context()
+                .is_some_and(|context| context.version() >= compute::MIN_VERSION);
+        if self.compute_supported.replace(supported) != supported {
+            self.obj().notify_compute_supported();
+        }
+        let fp64 = !self.uses_es()
+            && self
+                .obj()
+                .context()
+                .is_some_and(|context| unsafe { uniform::fp64_supported(&context) });
+        if self.fp64_supported.replace(fp64) != fp64 {
+            self.obj().notify_fp64_supported();
+        }
+
+        // The configuration is kept (not taken) so that the GL objects can be
+        // rebuilt if the widget is unrealized and realized again
+        let shader = self.shader_source.borrow().clone();
+        match self.create_gl_state(&shader) {
+            Ok(state) => {
+                self.gl_state.replace(Some(state));
+            }
+            Err(e) => self.report_compile_error(&e),
+        }
+
+        if !supported {
+            let computes: Vec<String> = self
+                .computes
+                .borrow()
+                .iter()
+                .map(|pass| pass.name.clone())
+                .collect();
+            for name in computes {
+                let e =
+                    CompileError::from_log(ShaderStage::Compute, compute::unsupported_message())
+                        .in_pass(&name);
+                self.report_compile_error(&e);
+            }
+            if !self.storage_buffers.borrow().is_empty() || !self.storage_images.borrow().is_empty()
+            {
+                warn!(
+                    "Storage objects ignored: {}",
+                    compute::unsupported_message()
+                );
+            }
+        }
+    }
+
+    fn map(&self) {
+        self.parent_map();
+
+        // Wayland compositors suspend windows that are fully obscured
+        if let Some(toplevel) = self
+            .obj()
+            .native()
+            .and_then(|native| native.surface())
+            .and_downcast::<gdk::Toplevel>()
+        {
+            let this = self.obj().downgrade();
+            let handler = toplevel.connect_state_notify(move |_| {
+                if let Some(this) = this.upgrade() {
+                    this.imp().update_animation();
+                }
+            });
+            self.toplevel_handler.replace(Some((toplevel, handler)));
+        }
+
+        self.update_animation();
+    }
+
+    fn unmap(&self) {
+        if let Some((toplevel, handler)) = self.toplevel_handler.take() {
+            toplevel.disconnect(handler);
+        }
+
+        self.parent_unmap();
+        self.update_animation();
+    }
+
+    fn state_flags_changed(&self, old_flags: &gtk::StateFlags) {
+        self.parent_state_flags_changed(old_flags);
+
+        // Covers both the backdrop and insensitive states
+        self.update_animation();
+    }
+
+    fn unrealize(&self) {
+        // Always drop the state so that the next realize starts from scratch,
+        // even if the GL objects can't be deleted
+        let state = self.gl_state.borrow_mut().take();
+
+        self.obj().make_current();
+        if let Some(e) = self.obj().error() {
+            error!("Failed to switch OpenGL context: {e}");
+        } else if let Some(state) = state {
+            unsafe { Self::delete_gl_state(&state) };
+        }
+
+        self.parent_unrealize();
+    }
+}
+
+impl GLAreaImpl for ShaderArea {
+    fn render(&self, _ctx: &gtk::gdk::GLContext) -> Propagation {
+        if let Some(e) = self.obj().error() {
+            error!("Failed to switch OpenGL context: {e}");
+            return Propagation::Stop;
+        }
+
+        if self.uniform_batch_depth.get() > 0 {
+            let frames = self.uniform_batch_frames.get() + 1;
+            self.uniform_batch_frames.set(frames);
+            if frames == STALE_TRANSACTION_FRAMES {
+                warn!(
+                    "Uniform transaction open for {frames} frames, its values aren't applied \
+                     until it's committed or aborted"
+                );
+            }
+        }
+
+        let mut events: Vec<String> = Vec::new();
+        if let Some(state) = self.gl_state.borrow_mut().as_mut() {
+            unsafe {
+                let mut frame = self.frame_info(state);
+                let resolution = frame.resolution;
+                epoxy::BindVertexArray(state.vao);
+
+                // Allocating pass textures changes the texture bindings, so
+                // it's done before binding anything
+                let viewport = resolution.map(|v| v as i32);
+                for pass in &mut state.passes {
+                    let size = pass.pass.size.resolve(viewport);
+                    pass.target
+                        .resize(&pass.pass.name, size, pass.pass.format, pass.pass.filter);
+                }
+                if let Some(particles) = &mut state.particles {
+                    let size = particles::grid_size(self.particle_count.get());
+                    particles.target.resize(size);
+                }
+
+                // Bind textures to their respective texture units
+                for (i, texture) in state.textures.iter().enumerate() {
+                    epoxy::ActiveTexture(epoxy::TEXTURE0 + i as u32);
+                    epoxy::BindTexture(epoxy::TEXTURE_2D, texture.id);
+                }
+                if self.keyboard_input.get() {
+                    self.update_keyboard_texture(state);
+                }
+                self.bind_storage(state);
+                for (i, &buffer) in state.uniform_blocks.iter().enumerate() {
+                    epoxy::BindBufferBase(epoxy::UNIFORM_BUFFER, i as u32, buffer);
+                }
+
+                if !state.computes.is_empty() {
+                    Self::dispatch_computes(state, &frame);
+                }
+                if state.particles.is_some() {
+                    Self::update_particles(state, &mut frame);
+                }
+                if !state.passes.is_empty() {
+                    Self::render_passes(state, &mut frame);
+                }
+                if !state.passes.is_empty() || state.particles.is_some() {
+                    // Back to the widget's framebuffer
+                    self.obj().attach_buffers();
+                    epoxy::Viewport(0, 0, viewport[0], viewport[1]);
+                    frame.resolution = resolution;
+                    Self::bind_pass_outputs(state);
+                    Self::bind_particle_outputs(state);
+                }
+
+                epoxy::ClearColor(0.0, 0.0, 0.0, 0.0);
+                epoxy::Clear(epoxy::COLOR_BUFFER_BIT | epoxy::DEPTH_BUFFER_BIT);
+                Self::apply_uniforms(&state.program, &frame);
+
+                if let Some(mesh) = &state.mesh {
+                    mesh.draw();
+                    epoxy::BindVertexArray(state.vao);
+                } else if self.vertex_source.borrow().is_none() {
+                    // By default, a fullscreen quad using TRIANGLE_STRIP
+                    // 4 vertices: (0,0), (1,0), (0,1), (1,1)
+                    epoxy::DrawArrays(epoxy::TRIANGLE_STRIP, 0, 4);
+                } else {
+                    let primitive = self.primitive.get();
+                    if primitive == Primitive::Points && !self.uses_es() {
+                        // OpenGL ES always sizes points by `gl_PointSize`
+                        epoxy::Enable(epoxy::PROGRAM_POINT_SIZE);
+                    }
+                    epoxy::DrawArrays(primitive.gl(), 0, self.vertex_count.get() as i32);
+                }
+
+                if let Some(particles) = &state.particles {
+                    // One quad per particle, over the widget's shader
+                    Self::apply_uniforms(&particles.sprites, &frame);
+                    self.particle_blend.get().enable();
+                    epoxy::DrawArraysInstanced(
+                        epoxy::TRIANGLE_STRIP,
+                        0,
+                        4,
+                        self.particle_count.get() as i32,
+                    );
+                    epoxy::Disable(epoxy::BLEND);
+                }
+
+                self.keyboard.borrow_mut().end_frame();
+
+                // ISF events last for the frame they're triggered in
+                let uniforms = self.uniforms.borrow();
+                events.extend(
+                    state
+                        .isf_events
+                        .iter()
+                        .filter(|name| {
+                            !matches!(
+                                uniforms.get(name.as_str()),
+                                None | Some(Uniform::Bool(false) | Uniform::Int(0))
+                            )
+                        })
+                        .cloned(),
+                );
+
+                epoxy::Flush();
+            }
+        }
+        for name in events {
+            self.set_uniform(name, Uniform::Bool(false));
+        }
+
+        Propagation::Stop
+    }
+}
+
+impl ShaderArea {
+    /// Stores the widget configuration, used every time the widget is realized.
+    ///
+    /// # Arguments
+    ///
+    /// * `shader` - GLSL fragment shader source code
+    /// * `textures` - Paths to image files to load as textures
+    /// * `uniforms` - Initial uniform values
+    pub fn initialize(
+        &self,
+        shader: String,
+        textures: Vec<PathBuf>,
+        uniforms: HashMap<String, Uniform>,
+    ) {
+        *self.shader_source.borrow_mut() = shader;
+        *self.texture_paths.borrow_mut() = textures;
+        *self.uniforms.borrow_mut() = uniforms;
+    }
+
+    /// Sets a uniform value on the shader program.
+    ///
+    /// If the widget isn't realized yet, the value is stored and applied
+    /// when it is.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the uniform variable
+    /// * `value` - The new value to set
+    pub fn set_uniform(&self, name: String, value: Uniform) {
+        if self.uniform_batch_depth.get() > 0 {
+            self.uniform_batch.borrow_mut().insert(name, value);
+            return;
+        }
+
+        if self.on_demand.get() && self.uniforms.borrow().get(&name) == Some(&value) {
             return;
-        };
-        let Some(textures) = self.texture_paths.borrow_mut().take() else {
-            error!("Texture paths not set");
+        }
+
+        let mut state = self.gl_state.borrow_mut();
+        let Some(state) = state.as_mut() else {
+            self.uniforms.borrow_mut().insert(name, value);
             return;
         };
-        let Some(uniforms) = self.initial_uniforms.borrow_mut().take() else {
-            error!("Uniforms not set");
+
+        self.obj().make_current();
+        if let Some(e) = self.obj().error() {
+            error!("Failed to switch OpenGL context: {e}");
+            self.uniforms.borrow_mut().insert(name, value);
+            return;
+        }
+
+        // Convert the value for every program first, so that a rejected value
+        // is neither applied nor stored
+        let mut updates = Vec::new();
+        for program in state.programs() {
+            let location = if let Some((location, _)) = program.uniforms.get(&name) {
+                *location
+            } else {
+                unsafe { Self::uniform_location(program.id, &name) }
+            };
+
+            let update = if location >= 0 {
+                match Self::fit_uniform(program, &name, &value) {
+                    Ok(value) => Some((location, value)),
+                    Err(e) => {
+                        error!("{e}");
+                        return;
+                    }
+                }
+            } else {
+                None
+            };
+            updates.push(update);
+        }
+
+        let used = updates.iter().any(Option::is_some);
+        for (program, update) in state.programs_mut().zip(updates) {
+            if let Some(update) = update {
+                program.uniforms.insert(name.clone(), update);
+            }
+        }
+        self.uniforms.borrow_mut().insert(name.clone(), value);
+
+        if !used {
+            warn!("Uniform not used in shader: {name}");
             return;
+        }
+
+        // Queue a redraw to apply the new uniform
+        self.obj().queue_render();
+    }
+
+    /// Sets several uniform values at once, so that a frame never renders
+    /// with only some of them.
+    ///
+    /// Inside a transaction, the values are kept until it's committed.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError::Uniform`] if a value can't be converted to
+    /// the type declared by the shaders, in which case none are set.
+    pub fn set_uniforms(&self, values: HashMap<String, Uniform>) -> Result<(), glib::Error> {
+        if self.uniform_batch_depth.get() > 0 {
+            self.uniform_batch.borrow_mut().extend(values);
+            return Ok(());
+        }
+        self.update_uniforms(values)
+    }
+
+    /// Starts a transaction: uniform values are kept until the matching
+    /// [`Self::commit_uniforms`]. Transactions can be nested, in which case
+    /// the values are applied when the outermost one is committed.
+    pub fn begin_uniforms(&self) {
+        let depth = self.uniform_batch_depth.get();
+        if depth == 0 {
+            self.uniform_batch_frames.set(0);
+        }
+        self.uniform_batch_depth.set(depth + 1);
+    }
+
+    /// Ends every open transaction, dropping the uniform values set since the
+    /// outermost one was started.
+    pub fn abort_uniforms(&self) {
+        if self.uniform_batch_depth.replace(0) == 0 {
+            warn!("Uniform transaction aborted without being started");
+        }
+        self.uniform_batch.take();
+    }
+
+    /// Ends a transaction started by [`Self::begin_uniforms`], applying the
+    /// uniform values set since if it is the outermost one.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError::Uniform`] if a value can't be converted to
+    /// the type declared by the shaders, in which case none are set.
+    pub fn commit_uniforms(&self) -> Result<(), glib::Error> {
+        match self.uniform_batch_depth.get() {
+            0 => {
+                warn!("Uniform transaction committed without being started");
+                Ok(())
+            }
+            1 => {
+                self.uniform_batch_depth.set(0);
+                let values = self.uniform_batch.take();
+                self.update_uniforms(values)
+            }
+            depth => {
+                self.uniform_batch_depth.set(depth - 1);
+                Ok(())
+            }
+        }
+    }
+
+    /// Applies several uniform values to every program, converting all of
+    /// them before applying any.
+    fn update_uniforms(&self, values: HashMap<String, Uniform>) -> Result<(), glib::Error> {
+        let changed = {
+            let uniforms = self.uniforms.borrow();
+            values
+                .iter()
+                .any(|(name, value)| uniforms.get(name) != Some(value))
         };
+        if !changed && (values.is_empty() || self.on_demand.get()) {
+            return Ok(());
+        }
 
-        // GTK can use either OpenGL or OpenGL ES depending on the platform.
-        // The GLSL version header differs between the two.
-        let glsl_version = if self.obj().uses_es() {
-            "#version 300 es\nprecision highp float;\n"
-        } else {
-            "#version 330 core\n"
+        let mut state = self.gl_state.borrow_mut();
+        let Some(state) = state.as_mut() else {
+            self.uniforms.borrow_mut().extend(values);
+            return Ok(());
         };
 
-        let vertex_shader = Self::build_vertex_shader(glsl_version);
-        let fragment_shader = format!("{glsl_version}{shader}");
+        self.obj().make_current();
+        if let Some(e) = self.obj().error() {
+            error!("Failed to switch OpenGL context: {e}");
+            self.uniforms.borrow_mut().extend(values);
+            return Ok(());
+        }
+
+        // Convert the values for every program first, so that nothing is
+        // applied if one of them is rejected
+        let mut updates = Vec::new();
+        let mut used = HashSet::new();
+        for program in state.programs() {
+            let mut program_updates = Vec::new();
+            for (name, value) in &values {
+                let location = if let Some((location, _)) = program.uniforms.get(name) {
+                    *location
+                } else {
+                    unsafe { Self::uniform_location(program.id, name) }
+                };
+                if location >= 0 {
+                    let value = Self::fit_uniform(program, name, value)?;
+                    program_updates.push((name.clone(), (location, value)));
+                    used.insert(name.as_str());
+                }
+            }
+            updates.push(program_updates);
+        }
+        for name in values.keys().filter(|name| !used.contains(name.as_str())) {
+            warn!("Uniform not used in shader: {name}");
+        }
+        let used = !used.is_empty();
+
+        for (program, program_updates) in state.programs_mut().zip(updates) {
+            program.uniforms.extend(program_updates);
+        }
+        self.uniforms.borrow_mut().extend(values);
+
+        // Queue a single redraw for all the values
+        if used {
+            self.obj().queue_render();
+        }
+        Ok(())
+    }
+
+    /// Sets the contents of a uniform block, replacing those of the block
+    /// with the same name.
+    ///
+    /// If the widget isn't realized yet, the contents are stored and
+    /// uploaded when it is.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError::Uniform`] if the block is invalid.
+    pub fn set_uniform_block(&self, block: UniformBlock) -> Result<(), glib::Error> {
+        block.validate()?;
+
+        let mut blocks = self.uniform_blocks.borrow_mut();
+        let index = blocks.iter().position(|b| b.name == block.name);
+        match index {
+            Some(index) => blocks[index] = block,
+            None => blocks.push(block),
+        }
+        drop(blocks);
 
+        let mut state = self.gl_state.borrow_mut();
+        let Some(state) = state.as_mut() else {
+            return Ok(());
+        };
+        self.obj().make_current();
+        if let Some(e) = self.obj().error() {
+            error!("Failed to switch OpenGL context: {e}");
+            return Err(e);
+        }
         unsafe {
-            let program = Self::link_program(&vertex_shader, &fragment_shader);
+            match index {
+                // Only the contents changed (blocks past the last binding
+                // point have no buffer)
+                Some(index) => {
+                    if let Some(&buffer) = state.uniform_blocks.get(index) {
+                        let programs = state.programs().map(|p| p.id);
+                        self.uniform_blocks.borrow()[index].upload(buffer, programs);
+                    }
+                }
+                None => self.bind_uniform_blocks(state),
+            }
+        }
+        self.obj().queue_render();
+        Ok(())
+    }
 
-            // Core profile requires a VAO even when no vertex attributes are used
-            let mut vao = 0u32;
-            epoxy::GenVertexArrays(1, &raw mut vao);
-            epoxy::BindVertexArray(vao);
+    /// Removes the uniform block called `name`.
+    ///
+    /// # Returns
+    ///
+    /// Whether a uniform block with that name existed.
+    pub fn remove_uniform_block(&self, name: &str) -> bool {
+        let Some(index) = self
+            .uniform_blocks
+            .borrow()
+            .iter()
+            .position(|b| b.name == name)
+        else {
+            return false;
+        };
+        self.uniform_blocks.borrow_mut().remove(index);
+
+        if let Some(state) = self.gl_state.borrow_mut().as_mut()
+            && index < state.uniform_blocks.len()
+        {
+            let buffer = state.uniform_blocks.remove(index);
+
+            self.obj().make_current();
+            if let Some(e) = self.obj().error() {
+                error!("Failed to switch OpenGL context: {e}");
+            } else {
+                unsafe {
+                    epoxy::DeleteBuffers(1, &raw const buffer);
+                    // The following blocks moved to another binding point
+                    self.bind_uniform_blocks(state);
+                }
+            }
+        }
+
+        self.obj().queue_render();
+        true
+    }
+
+    /// Sets whether the widget renders on every frame.
+    fn set_animate(&self, animate: bool) {
+        self.animate.set(animate);
+        self.update_animation();
+    }
+
+    /// Sets the maximum number of frames per second while animating.
+    fn set_max_fps(&self, max_fps: u32) {
+        self.max_fps.set(max_fps);
+    }
+
+    /// Sets whether the widget only renders when a uniform changes.
+    fn set_on_demand(&self, on_demand: bool) {
+        self.on_demand.set(on_demand);
+        self.obj().set_auto_render(!on_demand);
+    }
+
+    /// Installs or removes the pointer and touch event controllers.
+    fn set_pointer_input(&self, enabled: bool) {
+        if self.pointer_input.replace(enabled) == enabled {
+            return;
+        }
+
+        let obj = self.obj();
+        for controller in self.pointer_controllers.take() {
+            obj.remove_controller(&controller);
+        }
+        if !enabled {
+            return;
+        }
+
+        // Every handler records the event and renders a new frame
+        let motion = gtk::EventControllerMotion::new();
+        let this = obj.downgrade();
+        motion.connect_motion(move |_, x, y| {
+            if let Some(this) = this.upgrade() {
+                this.imp().pointer.borrow_mut().motion(x, y);
+                this.queue_render();
+            }
+        });
+
+        let click = gtk::GestureClick::new();
+        click.set_button(0);
+        let this = obj.downgrade();
+        click.connect_pressed(move |gesture, _, x, y| {
+            if let Some(this) = this.upgrade() {
+                this.imp()
+                    .pointer
+                    .borrow_mut()
+                    .press(gesture.current_button(), x, y);
+                this.queue_render();
+            }
+        });
+        let this = obj.downgrade();
+        click.connect_released(move |gesture, _, _, _| {
+            if let Some(this) = this.upgrade() {
+                this.imp()
+                    .pointer
+                    .borrow_mut()
+                    .release(gesture.current_button());
+                this.queue_render();
+            }
+        });
+
+        let drag = gtk::GestureDrag::new();
+        drag.set_button(0);
+        let this = obj.downgrade();
+        drag.connect_drag_update(move |_, dx, dy| {
+            if let Some(this) = this.upgrade() {
+                this.imp().pointer.borrow_mut().drag(dx, dy);
+                this.queue_render();
+            }
+        });
+
+        // Gestures only follow a single touch sequence, so every touch point
+        // is tracked from the raw events
+        let touch = gtk::EventControllerLegacy::new();
+        let this = obj.downgrade();
+        touch.connect_event(move |_, event| {
+            let Some(this) = this.upgrade() else {
+                return glib::Propagation::Proceed;
+            };
+            let imp = this.imp();
+            match event.event_type() {
+                gdk::EventType::TouchBegin | gdk::EventType::TouchUpdate => {
+                    if let Some(position) = imp.event_position(event) {
+                        imp.pointer
+                            .borrow_mut()
+                            .touch_update(event.event_sequence(), position);
+                    }
+                }
+                gdk::EventType::TouchEnd | gdk::EventType::TouchCancel => {
+                    imp.pointer.borrow_mut().touch_end(&event.event_sequence());
+                }
+                _ => return glib::Propagation::Proceed,
+            }
+            this.queue_render();
+            glib::Propagation::Proceed
+        });
+
+        let controllers: Vec<gtk::EventController> = vec![
+            motion.upcast(),
+            click.upcast(),
+            drag.upcast(),
+            touch.upcast(),
+        ];
+        for controller in &controllers {
+            obj.add_controller(controller.clone());
+        }
+        self.pointer_controllers.replace(controllers);
+    }
+
+    /// Installs or removes the keyboard event controllers.
+    ///
+    /// The widget is made focusable while keyboard input is enabled, and
+    /// grabs the focus when clicked.
+    fn set_keyboard_input(&self, enabled: bool) {
+        if self.keyboard_input.replace(enabled) == enabled {
+            return;
+        }
+
+        let obj = self.obj();
+        for controller in self.keyboard_controllers.take() {
+            obj.remove_controller(&controller);
+        }
+        self.keyboard.borrow_mut().release_all();
+        obj.set_focusable(enabled);
+        if !enabled {
+            return;
+        }
+
+        let keys = gtk::EventControllerKey::new();
+        let this = obj.downgrade();
+        keys.connect_key_pressed(move |_, key, _, _| {
+            if let Some(this) = this.upgrade() {
+                this.imp().keyboard.borrow_mut().press(key);
+                this.queue_render();
+            }
+            glib::Propagation::Proceed
+        });
+        let this = obj.downgrade();
+        keys.connect_key_released(move |_, key, _, _| {
+            if let Some(this) = this.upgrade() {
+                this.imp().keyboard.borrow_mut().release(key);
+                this.queue_render();
+            }
+        });
+
+        // Release events are lost once the focus moves elsewhere
+        let focus = gtk::EventControllerFocus::new();
+        let this = obj.downgrade();
+        focus.connect_leave(move |_| {
+            if let Some(this) = this.upgrade() {
+                this.imp().keyboard.borrow_mut().release_all();
+                this.queue_render();
+            }
+        });
+
+        let click = gtk::GestureClick::new();
+        let this = obj.downgrade();
+        click.connect_pressed(move |_, _, _, _| {
+            if let Some(this) = this.upgrade() {
+                this.grab_focus();
+            }
+        });
+
+        let controllers: Vec<gtk::EventController> =
+            vec![keys.upcast(), focus.upcast(), click.upcast()];
+        for controller in &controllers {
+            obj.add_controller(controller.clone());
+        }
+        self.keyboard_controllers.replace(controllers);
+    }
+
+    /// Creates the keyboard texture if needed, uploads pending changes and
+    /// binds it to its texture unit, right after the user's textures.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    unsafe fn update_keyboard_texture(&self, state: &mut GLState) {
+        unsafe {
+            epoxy::ActiveTexture(epoxy::TEXTURE0 + state.textures.len() as u32);
+
+            if state.keyboard_texture == 0 {
+                epoxy::GenTextures(1, &raw mut state.keyboard_texture);
+                epoxy::BindTexture(epoxy::TEXTURE_2D, state.keyboard_texture);
+                Self::set_texture_parameters(epoxy::NEAREST);
+                epoxy::TexImage2D(
+                    epoxy::TEXTURE_2D,
+                    0,
+                    epoxy::R8 as i32,
+                    KEYBOARD_WIDTH as i32,
+                    KEYBOARD_ROWS as i32,
+                    0,
+                    epoxy::RED,
+                    epoxy::UNSIGNED_BYTE,
+                    std::ptr::null(),
+                );
+            } else {
+                epoxy::BindTexture(epoxy::TEXTURE_2D, state.keyboard_texture);
+            }
+
+            if let Some(data) = self.keyboard.borrow_mut().take_changes() {
+                epoxy::PixelStorei(epoxy::UNPACK_ALIGNMENT, 1);
+                epoxy::TexSubImage2D(
+                    epoxy::TEXTURE_2D,
+                    0,
+                    0,
+                    0,
+                    KEYBOARD_WIDTH as i32,
+                    KEYBOARD_ROWS as i32,
+                    epoxy::RED,
+                    epoxy::UNSIGNED_BYTE,
+                    data.as_ptr().cast::<c_void>(),
+                );
+                epoxy::PixelStorei(epoxy::UNPACK_ALIGNMENT, 4);
+            }
+        }
+    }
+
+    /// Installs or removes the view controls event controllers.
+    fn set_view_controls(&self, enabled: bool) {
+        if self.view_controls.replace(enabled) == enabled {
+            return;
+        }
+
+        let obj = self.obj();
+        for controller in self.view_controllers.take() {
+            obj.remove_controller(&controller);
+        }
+        self.stop_view_inertia();
+        if !enabled {
+            return;
+        }
+
+        let motion = gtk::EventControllerMotion::new();
+        let this = obj.downgrade();
+        motion.connect_motion(move |_, x, y| {
+            if let Some(this) = this.upgrade() {
+                let imp = this.imp();
+                imp.view_pointer.set(imp.to_uv(x, y));
+            }
+        });
+
+        // Scrolling zooms around the pointer
+        let scroll = gtk::EventControllerScroll::new(gtk::EventControllerScrollFlags::VERTICAL);
+        let this = obj.downgrade();
+        scroll.connect_scroll(move |_, _, dy| {
+            let Some(this) = this.upgrade() else {
+                return glib::Propagation::Proceed;
+            };
+            let imp = this.imp();
+            let zoom = imp.view.get().zoom * 1.1f64.powf(-dy);
+            imp.update_view(|view, limits, aspect| {
+                view.zoom_at(zoom, imp.view_pointer.get(), limits, aspect);
+            });
+            glib::Propagation::Stop
+        });
+
+        let drag = gtk::GestureDrag::new();
+        let this = obj.downgrade();
+        drag.connect_drag_begin(move |_, _, _| {
+            if let Some(this) = this.upgrade() {
+                let imp = this.imp();
+                imp.stop_view_inertia();
+                imp.view_drag_offset.set([0.0, 0.0]);
+                imp.view_inertia.set(Inertia::default());
+            }
+        });
+        let this = obj.downgrade();
+        drag.connect_drag_update(move |_, x, y| {
+            let Some(this) = this.upgrade() else {
+                return;
+            };
+            let imp = this.imp();
+            let [last_x, last_y] = imp.view_drag_offset.replace([x, y]);
+            let delta = [
+                (x - last_x) / f64::from(this.width().max(1)),
+                (last_y - y) / f64::from(this.height().max(1)),
+            ];
+
+            let mut inertia = imp.view_inertia.get();
+            inertia.track(delta, glib::monotonic_time());
+            imp.view_inertia.set(inertia);
+
+            imp.update_view(|view, _, aspect| view.pan(delta, aspect));
+        });
+        let this = obj.downgrade();
+        drag.connect_drag_end(move |_, _, _| {
+            if let Some(this) = this.upgrade()
+                && this.imp().inertia.get()
+            {
+                this.imp().start_view_inertia();
+            }
+        });
+
+        // Pinching zooms and rotating turns around the center of the fingers
+        let zoom = gtk::GestureZoom::new();
+        let this = obj.downgrade();
+        zoom.connect_begin(move |_, _| {
+            if let Some(this) = this.upgrade() {
+                this.imp().view_zoom_scale.set(1.0);
+            }
+        });
+        let this = obj.downgrade();
+        zoom.connect_scale_changed(move |gesture, scale| {
+            let Some(this) = this.upgrade() else {
+                return;
+            };
+            let imp = this.imp();
+            let last = imp.view_zoom_scale.replace(scale);
+            let anchor = imp.gesture_anchor(gesture.upcast_ref());
+            let zoom = imp.view.get().zoom * scale / last;
+            imp.update_view(|view, limits, aspect| view.zoom_at(zoom, anchor, limits, aspect));
+        });
+
+        let rotate = gtk::GestureRotate::new();
+        let this = obj.downgrade();
+        rotate.connect_angle_changed(move |gesture, _, delta| {
+            let Some(this) = this.upgrade() else {
+                return;
+            };
+            let imp = this.imp();
+            let anchor = imp.gesture_anchor(gesture.upcast_ref());
+            let rotation = imp.view.get().rotation + delta;
+            imp.update_view(|view, _, aspect| view.rotate_at(rotation, anchor, aspect));
+        });
+
+        let controllers: Vec<gtk::EventController> = vec![
+            motion.upcast(),
+            scroll.upcast(),
+            drag.upcast(),
+            zoom.upcast(),
+            rotate.upcast(),
+        ];
+        for controller in &controllers {
+            obj.add_controller(controller.clone());
+        }
+        self.view_controllers.replace(controllers);
+    }
+
+    /// Sets the minimum zoom factor, zooming in if the view is below it.
+    fn set_min_zoom(&self, zoom: f64) {
+        self.min_zoom.set(zoom);
+        self.clamp_view_zoom();
+    }
+
+    /// Sets the maximum zoom factor, zooming out if the view is above it.
+    fn set_max_zoom(&self, zoom: f64) {
+        self.max_zoom.set(zoom);
+        self.clamp_view_zoom();
+    }
+
+    /// Brings the zoom factor of the view within the limits, around the
+    /// center of the widget.
+    fn clamp_view_zoom(&self) {
+        self.update_view(|view, limits, aspect| {
+            view.zoom_at(view.zoom, [0.5, 0.5], limits, aspect);
+        });
+    }
+
+    /// Resets the view transform.
+    pub fn reset_view(&self) {
+        self.stop_view_inertia();
+        self.view.set(ViewTransform::default());
+        self.obj().queue_render();
+    }
+
+    /// Modifies the view transform and renders a new frame.
+    ///
+    /// The closure receives the zoom limits and the aspect ratio of the
+    /// widget along with the transform.
+    fn update_view(&self, f: impl FnOnce(&mut ViewTransform, [f64; 2], f64)) {
+        let min = self.min_zoom.get();
+        let max = self.max_zoom.get();
+        let limits = [
+            if min > 0.0 { min } else { f64::MIN_POSITIVE },
+            if max > 0.0 { max } else { f64::INFINITY },
+        ];
+
+        let mut view = self.view.get();
+        f(&mut view, limits, self.aspect());
+        self.view.set(view);
+        self.obj().queue_render();
+    }
+
+    /// Starts moving the view with the velocity of the last drag.
+    fn start_view_inertia(&self) {
+        self.stop_view_inertia();
+
+        let mut inertia = self.view_inertia.get();
+        inertia.last = None;
+        self.view_inertia.set(inertia);
+
+        let id = self.obj().add_tick_callback(|obj, clock| {
+            let imp = obj.imp();
+            let mut inertia = imp.view_inertia.get();
+            if inertia.last.is_none() {
+                inertia.last = Some(clock.frame_time());
+            }
+            let step = inertia.step(clock.frame_time());
+            imp.view_inertia.set(inertia);
+
+            match step {
+                Some(delta) => {
+                    imp.update_view(|view, _, aspect| view.pan(delta, aspect));
+                    glib::ControlFlow::Continue
+                }
+                None => {
+                    imp.view_inertia_tick.take();
+                    glib::ControlFlow::Break
+                }
+            }
+        });
+        self.view_inertia_tick.replace(Some(id));
+    }
+
+    /// Stops the motion started by [`Self::start_view_inertia`].
+    fn stop_view_inertia(&self) {
+        if let Some(id) = self.view_inertia_tick.take() {
+            id.remove();
+        }
+    }
+
+    /// Replaces the mesh drawn by the widget's shader, or restores the
+    /// fullscreen quad for `None`.
+    ///
+    /// Enables the depth buffer of the GLArea, and rebuilds the OpenGL
+    /// objects if the widget is realized, reporting errors through
+    /// `compile-error`.
+    pub fn set_mesh(&self, mesh: Option<Mesh>) {
+        if mesh.is_some() {
+            self.obj().set_has_depth_buffer(true);
+        }
+        self.mesh.replace(mesh);
+        self.rebuild();
+    }
+
+    /// Returns the vertex shader of the widget's program: the custom one if
+    /// set, else the mesh vertex shader if a mesh is loaded.
+    fn widget_vertex_source(&self) -> Option<String> {
+        self.vertex_source.borrow().clone().or_else(|| {
+            self.mesh
+                .borrow()
+                .is_some()
+                .then(|| mesh::VERTEX_SHADER.to_owned())
+        })
+    }
+
+    /// Installs or removes the orbit camera event controllers.
+    fn set_orbit_camera(&self, enabled: bool) {
+        if self.orbit_camera.replace(enabled) == enabled {
+            return;
+        }
+
+        let obj = self.obj();
+        for controller in self.camera_controllers.take() {
+            obj.remove_controller(&controller);
+        }
+        if !enabled {
+            return;
+        }
+
+        // Scrolling moves the camera closer or away
+        let scroll = gtk::EventControllerScroll::new(gtk::EventControllerScrollFlags::VERTICAL);
+        let this = obj.downgrade();
+        scroll.connect_scroll(move |_, _, dy| {
+            let Some(this) = this.upgrade() else {
+                return glib::Propagation::Proceed;
+            };
+            this.imp()
+                .update_camera(|camera| camera.zoom(1.1f64.powf(-dy)));
+            glib::Propagation::Stop
+        });
+
+        // Dragging orbits around the mesh
+        let drag = gtk::GestureDrag::new();
+        let this = obj.downgrade();
+        drag.connect_drag_begin(move |_, _, _| {
+            if let Some(this) = this.upgrade() {
+                this.imp().camera_drag_offset.set([0.0, 0.0]);
+            }
+        });
+        let this = obj.downgrade();
+        drag.connect_drag_update(move |_, x, y| {
+            let Some(this) = this.upgrade() else {
+                return;
+            };
+            let imp = this.imp();
+            let [last_x, last_y] = imp.camera_drag_offset.replace([x, y]);
+            let delta = [
+                (x - last_x) / f64::from(this.width().max(1)),
+                (last_y - y) / f64::from(this.height().max(1)),
+            ];
+            imp.update_camera(|camera| camera.rotate(delta));
+        });
+
+        let controllers: Vec<gtk::EventController> = vec![scroll.upcast(), drag.upcast()];
+        for controller in &controllers {
+            obj.add_controller(controller.clone());
+        }
+        self.camera_controllers.replace(controllers);
+    }
+
+    /// Resets the orbit camera.
+    pub fn reset_camera(&self) {
+        self.camera.set(OrbitCamera::default());
+        self.obj().queue_render();
+    }
+
+    /// Modifies the orbit camera and renders a new frame.
+    fn update_camera(&self, f: impl FnOnce(&mut OrbitCamera)) {
+        let mut camera = self.camera.get();
+        f(&mut camera);
+        self.camera.set(camera);
+        self.obj().queue_render();
+    }
+
+    /// Returns the center of the touch points of a gesture in uv space.
+    fn gesture_anchor(&self, gesture: &gtk::Gesture) -> [f64; 2] {
+        gesture
+            .bounding_box_center()
+            .map_or([0.5, 0.5], |(x, y)| self.to_uv(x, y))
+    }
+
+    /// Converts widget coordinates to uv space.
+    fn to_uv(&self, x: f64, y: f64) -> [f64; 2] {
+        let obj = self.obj();
+        [
+            x / f64::from(obj.width().max(1)),
+            1.0 - y / f64::from(obj.height().max(1)),
+        ]
+    }
+
+    /// Returns the aspect ratio of the widget.
+    fn aspect(&self) -> f64 {
+        let obj = self.obj();
+        f64::from(obj.width().max(1)) / f64::from(obj.height().max(1))
+    }
+
+    /// Returns the position of an event in widget coordinates.
+    ///
+    /// Raw events are relative to the surface, unlike gesture signals.
+    fn event_position(&self, event: &gdk::Event) -> Option<[f32; 2]> {
+        let (x, y) = event.position()?;
+        let native = self.obj().native()?;
+        let (dx, dy) = native.surface_transform();
+        let point = native.compute_point(
+            &*self.obj(),
+            &graphene::Point::new((x - dx) as f32, (y - dy) as f32),
+        )?;
+        Some([point.x(), point.y()])
+    }
+
+    /// Returns whether the animation should currently run.
+    ///
+    /// It is paused while the widget is unmapped or insensitive, and while
+    /// its window is in the backdrop or suspended.
+    fn should_animate(&self) -> bool {
+        let obj = self.obj();
+        let suspended = self
+            .toplevel_handler
+            .borrow()
+            .as_ref()
+            .is_some_and(|(toplevel, _)| toplevel.state().contains(gdk::ToplevelState::SUSPENDED));
+
+        self.animate.get()
+            && obj.is_mapped()
+            && obj.is_sensitive()
+            && !obj.state_flags().contains(gtk::StateFlags::BACKDROP)
+            && !suspended
+    }
+
+    /// Starts or stops the tick callback to match [`Self::should_animate`].
+    fn update_animation(&self) {
+        let running = self.tick_callback.borrow().is_some();
+
+        if self.should_animate() && !running {
+            self.last_tick.set(None);
+            let id = self.obj().add_tick_callback(|obj, clock| {
+                obj.imp().tick(clock.frame_time());
+                glib::ControlFlow::Continue
+            });
+            self.tick_callback.replace(Some(id));
+        } else if !self.should_animate()
+            && let Some(id) = self.tick_callback.take()
+        {
+            id.remove();
+        }
+    }
+
+    /// Queues a render for an animation frame, unless it would exceed
+    /// `max-fps`.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The frame time in microseconds
+    fn tick(&self, now: i64) {
+        let max_fps = self.max_fps.get();
+        if max_fps > 0
+            && let Some(last) = self.last_tick.get()
+        {
+            // Frame times are quantized to the refresh rate, so allow some
+            // slack to avoid skipping every other frame at an exact divisor
+            let interval = 1e6 / f64::from(max_fps);
+            if ((now - last) as f64) < interval * 0.9 {
+                return;
+            }
+        }
+
+        self.last_tick.set(Some(now));
+        self.obj().queue_render();
+    }
+
+    /// Plays the transition from the first texture to the second one, from
+    /// the start.
+    pub fn start_transition(&self) {
+        self.stop_transition();
+        self.set_transition_time(0.0);
+
+        let start = Cell::new(None);
+        let id = self.obj().add_tick_callback(move |obj, clock| {
+            let imp = obj.imp();
+            let now = clock.frame_time();
+            let first = start.get().unwrap_or(now);
+            start.set(Some(first));
+            let elapsed = (now - first) as f64 / 1e6;
+            let duration = imp.transition_duration.get();
+            let t = if duration > 0.0 {
+                (elapsed / duration).min(1.0)
+            } else {
+                1.0
+            };
+            imp.set_transition_time(t);
+            if t < 1.0 {
+                return glib::ControlFlow::Continue;
+            }
+
+            imp.transition_tick.take();
+            obj.emit_by_name::<()>("finished", &[]);
+            if !imp.transition_queue.borrow().is_empty() {
+                imp.next_transition();
+            }
+            glib::ControlFlow::Break
+        });
+        self.transition_tick.replace(Some(id));
+    }
+
+    /// Stops the transition started by [`Self::start_transition`], leaving
+    /// `progress` where it is.
+    fn stop_transition(&self) {
+        if let Some(id) = self.transition_tick.take() {
+            id.remove();
+        }
+    }
+
+    /// Queues an image to transition to after the current transition.
+    ///
+    /// If no transition is running, the next one starts right away: from
+    /// the first texture to the second if it hasn't played yet, or else from
+    /// the second texture to the queued image.
+    pub fn queue_image(&self, path: PathBuf) {
+        if self.texture_paths.borrow().len() < 2 {
+            warn!("Images can only be queued for transitions between two textures");
+            return;
+        }
+
+        self.transition_queue.borrow_mut().push_back(path);
+        if self.transition_tick.borrow().is_none() {
+            self.next_transition();
+        }
+    }
+
+    /// Starts the next transition, first moving to the next queued image if
+    /// the previous transition finished.
+    fn next_transition(&self) {
+        if self.transition_time.get() >= 1.0 {
+            let Some(next) = self.transition_queue.borrow_mut().pop_front() else {
+                return;
+            };
+
+            // The image transitioned to becomes the one transitioned from
+            {
+                let mut paths = self.texture_paths.borrow_mut();
+                paths.swap(0, 1);
+                paths[1] = next;
+            }
+            if let Some(state) = self.gl_state.borrow_mut().as_mut() {
+                state.textures.swap(0, 1);
+            }
+            self.reload_texture(1);
+        }
+        self.start_transition();
+    }
+
+    /// Sets the linear progress of the transition, and the eased `progress`
+    /// uniform.
+    fn set_transition_time(&self, t: f64) {
+        self.transition_time.set(t);
+        let progress = self.transition_easing.get().apply(t);
+        self.set_uniform("progress".to_owned(), Uniform::Float(progress as f32));
+    }
+
+    /// Watches the shader file and the texture files for changes.
+    ///
+    /// When the shader file changes it is recompiled through
+    /// [`Self::set_shader`], and when a texture changes it is uploaded again.
+    /// If anything fails, the previous state stays on screen.
+    ///
+    /// # Arguments
+    ///
+    /// * `shader_path` - Path of the fragment shader file
+    pub fn watch_files(&self, shader_path: &Path) {
+        let mut monitors = Vec::new();
+
+        let path = shader_path.to_owned();
+        if let Some(monitor) = self.monitor_file(shader_path, move |this| {
+            match Self::read_shader(&path) {
+                Ok(source) => {
+                    // Failures are already reported through `compile-error`
+                    let _ = this.set_shader(source);
+                }
+                Err(e) => error!("Failed to reload shader: {e}"),
+            }
+        }) {
+            monitors.push(monitor);
+        }
+
+        for (i, path) in self.texture_paths.borrow().iter().enumerate() {
+            if let Some(monitor) = self.monitor_file(path, move |this| this.reload_texture(i)) {
+                monitors.push(monitor);
+            }
+        }
+
+        *self.monitors.borrow_mut() = monitors;
+    }
+
+    /// Creates a file monitor calling `on_change` once a file has been
+    /// written or replaced.
+    fn monitor_file(
+        &self,
+        path: &Path,
+        on_change: impl Fn(&Self) + 'static,
+    ) -> Option<gio::FileMonitor> {
+        let monitor = match gio::File::for_path(path)
+            .monitor_file(gio::FileMonitorFlags::NONE, gio::Cancellable::NONE)
+        {
+            Ok(monitor) => monitor,
+            Err(e) => {
+                warn!("Can't watch {}: {e}", path.display());
+                return None;
+            }
+        };
+
+        let this = self.obj().downgrade();
+        monitor.connect_changed(move |_, _, _, event| {
+            // Editors either write in place (ending with a changes-done hint)
+            // or replace the file, which is reported as a creation
+            if matches!(
+                event,
+                gio::FileMonitorEvent::ChangesDoneHint | gio::FileMonitorEvent::Created
+            ) && let Some(this) = this.upgrade()
+            {
+                on_change(this.imp());
+            }
+        });
+
+        Some(monitor)
+    }
+
+    /// Reads a fragment shader from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GIOError` if the file can't be read or isn't valid UTF-8.
+    pub fn read_shader(path: &Path) -> Result<String, glib::Error> {
+        let (contents, _) = gio::File::for_path(path).load_contents(gio::Cancellable::NONE)?;
+        String::from_utf8(contents.to_vec()).map_err(|e| {
+            glib::Error::new(
+                gio::IOErrorEnum::InvalidData,
+                &format!("{} is not valid UTF-8: {e}", path.display()),
+            )
+        })
+    }
+
+    /// Loads a texture again from its file, replacing the one in its texture
+    /// unit.
+    ///
+    /// If loading fails, the previous texture is kept.
+    fn reload_texture(&self, index: usize) {
+        let mut state = self.gl_state.borrow_mut();
+        let Some(state) = state.as_mut() else {
+            // Loaded from the file on the next realize anyway
+            return;
+        };
+        let Some(path) = self.texture_paths.borrow().get(index).cloned() else {
+            return;
+        };
+
+        self.obj().make_current();
+        if let Some(e) = self.obj().error() {
+            error!("Failed to switch OpenGL context: {e}");
+            return;
+        }
+
+        unsafe {
+            let Some(texture) = Self::load_texture(index as u32, &path) else {
+                return;
+            };
+            let old = std::mem::replace(&mut state.textures[index], texture);
+            if old.id != 0 {
+                epoxy::DeleteTextures(1, &raw const old.id);
+            }
+        }
+
+        self.obj().queue_render();
+    }
+
+    /// Replaces the fragment shader.
+    ///
+    /// If the widget is realized, the new program is compiled immediately
+    /// and all uniform locations and texture samplers are resolved again,
+    /// keeping the current uniform values. If compilation fails, the
+    /// previous program keeps running.
+    ///
+    /// If the widget isn't realized yet, the source is stored and compiled
+    /// when it is, with errors reported through `compile-error`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError`] if the shader fails to compile or link.
+    pub fn set_shader(&self, source: String) -> Result<(), glib::Error> {
+        if self.dialect.get() == ShaderDialect::Isf
+            && let Ok((header, _)) = isf::Header::parse(&source)
+        {
+            return self.set_isf_shader(source, &header);
+        }
+
+        if !self.obj().is_realized() {
+            *self.shader_source.borrow_mut() = source;
+            return Ok(());
+        }
+
+        self.obj().make_current();
+        if let Some(e) = self.obj().error() {
+            error!("Failed to switch OpenGL context: {e}");
+            return Err(e);
+        }
+
+        let has_state = self.gl_state.borrow().is_some();
+        let result = if has_state {
+            self.prepare_source(&source, None)
+                .and_then(|shader| unsafe {
+                    Self::build_program(
+                        self.widget_vertex_source().as_deref(),
+                        &shader,
+                        self.uses_es(),
+                    )
+                })
+                .map(|program| {
+                    let mut state = self.gl_state.borrow_mut();
+                    let state = state.as_mut().expect("GL state checked above");
+                    unsafe {
+                        epoxy::DeleteProgram(state.program.id);
+                        state.program.id = program;
+                        self.bind_programs(state);
+                    }
+                })
+        } else {
+            // The previous shader never compiled, so nothing was set up yet
+            self.create_gl_state(&source).map(|state| {
+                self.gl_state.replace(Some(state));
+            })
+        };
+
+        match result {
+            Ok(()) => {
+                *self.shader_source.borrow_mut() = source;
+                self.obj().queue_render();
+                Ok(())
+            }
+            Err(e) => {
+                // Emitted once no borrow is held, so handlers can call back
+                // into the widget
+                self.report_compile_error(&e);
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Replaces an ISF shader along with the passes declared by its header,
+    /// rebuilding every program.
+    ///
+    /// Inputs that are new in the header start from their default. Like with
+    /// [`Self::set_shader`], the previous shader and passes keep running if
+    /// the new ones fail to compile.
+    fn set_isf_shader(&self, source: String, header: &isf::Header) -> Result<(), glib::Error> {
+        // Passes generated from the previous header run its whole source,
+        // unlike those added by the application
+        let kept: Vec<Pass> = {
+            let previous = self.shader_source.borrow();
+            self.passes
+                .borrow()
+                .iter()
+                .filter(|pass| pass.shader != *previous)
+                .cloned()
+                .collect()
+        };
+        let mut passes = header.render_passes(&source);
+        for (i, pass) in passes.iter().enumerate() {
+            pass.validate(passes[..i].iter().chain(&kept))?;
+        }
+        passes.extend(kept);
+
+        let mut uniforms = self.uniforms.borrow_mut();
+        for input in &header.inputs {
+            if let Some(value) = input.default_value() {
+                uniforms.entry(input.name.clone()).or_insert(value);
+            }
+        }
+        drop(uniforms);
+
+        let previous_passes = self.passes.replace(passes);
+        let previous_source = self.shader_source.replace(source.clone());
+        if !self.obj().is_realized() {
+            return Ok(());
+        }
+
+        self.obj().make_current();
+        let result = match self.obj().error() {
+            Some(e) => {
+                error!("Failed to switch OpenGL context: {e}");
+                Err(e)
+            }
+            None => self.create_gl_state(&source).map_err(|e| {
+                // Emitted once no borrow is held, so handlers can call back
+                // into the widget
+                self.report_compile_error(&e);
+                e.into()
+            }),
+        };
+        match result {
+            Ok(state) => {
+                if let Some(previous) = self.gl_state.replace(Some(state)) {
+                    unsafe { Self::delete_gl_state(&previous) };
+                }
+                self.obj().queue_render();
+                Ok(())
+            }
+            Err(e) => {
+                *self.passes.borrow_mut() = previous_passes;
+                *self.shader_source.borrow_mut() = previous_source;
+                Err(e)
+            }
+        }
+    }
+
+    /// Replaces the vertex shader of the widget's program, or restores the
+    /// fullscreen quad for `None`.
+    ///
+    /// The program is rebuilt like with [`Self::set_shader`]. If it fails to
+    /// compile, the previous vertex shader keeps running.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError`] if the shaders fail to compile or link.
+    pub fn set_vertex_shader(&self, source: Option<String>) -> Result<(), glib::Error> {
+        let previous = self.vertex_source.replace(source);
+        let shader = self.shader_source.borrow().clone();
+        let result = self.set_shader(shader);
+        if result.is_err() {
+            self.vertex_source.replace(previous);
+        }
+        result
+    }
+
+    /// Sets the primitive type the vertices are drawn as.
+    fn set_primitive(&self, primitive: Primitive) {
+        self.primitive.set(primitive);
+        self.obj().queue_render();
+    }
+
+    /// Sets the number of vertices drawn.
+    fn set_vertex_count(&self, count: u32) {
+        self.vertex_count.set(count);
+        self.obj().queue_render();
+    }
+
+    /// Sets the particle system, or removes it for `None`.
+    ///
+    /// If the widget is realized, the shaders are compiled immediately. If
+    /// they fail to compile, the previous particle system keeps running.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError`] if the shaders fail to compile or link.
+    pub fn set_particles(&self, particles: Option<Particles>) -> Result<(), glib::Error> {
+        if self.gl_state.borrow().is_none() {
+            // Compiled on the next realize, or with the next working shader
+            self.particles.replace(particles);
+            return Ok(());
+        }
+
+        self.obj().make_current();
+        if let Some(e) = self.obj().error() {
+            error!("Failed to switch OpenGL context: {e}");
+            return Err(e);
+        }
+
+        let built = match &particles {
+            Some(particles) => match unsafe { self.build_particles(particles) } {
+                Ok(built) => Some(built),
+                Err(e) => {
+                    self.report_compile_error(&e);
+                    return Err(e.into());
+                }
+            },
+            None => None,
+        };
+
+        {
+            let mut state = self.gl_state.borrow_mut();
+            let state = state.as_mut().expect("GL state checked above");
+            unsafe {
+                if let Some(previous) = std::mem::replace(&mut state.particles, built) {
+                    epoxy::DeleteProgram(previous.update.id);
+                    epoxy::DeleteProgram(previous.sprites.id);
+                    previous.target.delete();
+                }
+                self.bind_programs(state);
+            }
+        }
+
+        self.particles.replace(particles);
+        self.obj().queue_render();
+        Ok(())
+    }
+
+    /// Compiles the programs of a particle system and creates its state
+    /// textures.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    unsafe fn build_particles(&self, particles: &Particles) -> Result<ParticleState, CompileError> {
+        let uses_es = self.uses_es();
+        let update = unsafe { Self::build_program(None, &particles.wrap_update(), uses_es) }
+            .map_err(|e| e.in_pass(particles::NAME))?;
+        let sprites = unsafe {
+            Self::build_program(Some(&particles.wrap_vertex()), &particles.fragment, uses_es)
+        };
+        match sprites {
+            Ok(sprites) => Ok(ParticleState {
+                update: Program {
+                    id: update,
+                    ..Program::default()
+                },
+                sprites: Program {
+                    id: sprites,
+                    ..Program::default()
+                },
+                target: unsafe { ParticleTarget::new(particles.buffer_count()) },
+            }),
+            Err(e) => {
+                unsafe { epoxy::DeleteProgram(update) };
+                Err(e.in_pass(particles::NAME))
+            }
+        }
+    }
+
+    /// Sets the number of particles. Changing it resets every particle.
+    fn set_particle_count(&self, count: u32) {
+        self.particle_count.set(count);
+        self.obj().queue_render();
+    }
+
+    /// Sets how particle sprites are blended over the widget's shader.
+    fn set_particle_blend(&self, blend: BlendMode) {
+        self.particle_blend.set(blend);
+        self.obj().queue_render();
+    }
+
+    /// Compiles the compute passes, or none if the context doesn't support
+    /// them.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    unsafe fn build_computes(&self) -> Result<Vec<ComputeState>, CompileError> {
+        let mut computes = Vec::new();
+        if !self.compute_supported.get() {
+            // Reported once on realize
+            return Ok(computes);
+        }
+
+        for pass in self.computes.borrow().iter() {
+            match unsafe { Self::build_compute_program(&pass.wrap()) } {
+                Ok(id) => computes.push(ComputeState {
+                    pass: pass.clone(),
+                    program: Program {
+                        id,
+                        ..Program::default()
+                    },
+                    pending: false,
+                }),
+                Err(e) => unsafe {
+                    for compute in &computes {
+                        epoxy::DeleteProgram(compute.program.id);
+                    }
+                    return Err(e.in_pass(&pass.name));
+                },
+            }
+        }
+        Ok(computes)
+    }
+
+    /// Makes the widget's context current for an operation on compute
+    /// shaders or storage objects.
+    ///
+    /// # Returns
+    ///
+    /// Whether the widget is realized, `false` meaning that the operation
+    /// only updates the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError::Unsupported`] if the context doesn't support
+    /// compute shaders, or the error of the context.
+    fn make_compute_current(&self) -> Result<bool, glib::Error> {
+        if self.gl_state.borrow().is_none() {
+            return Ok(false);
+        }
+        if !self.compute_supported.get() {
+            return Err(compute::unsupported_error());
+        }
+
+        self.obj().make_current();
+        if let Some(e) = self.obj().error() {
+            error!("Failed to switch OpenGL context: {e}");
+            return Err(e);
+        }
+        Ok(true)
+    }
+
+    /// Adds a compute pass, dispatched after the existing ones.
+    ///
+    /// If the widget is realized, the pass is compiled immediately.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError`] if the pass is invalid, if the context
+    /// doesn't support compute shaders or if the shader fails to compile.
+    pub fn add_compute(&self, pass: ComputePass) -> Result<(), glib::Error> {
+        pass.validate(self.computes.borrow().iter())?;
+
+        if !self.make_compute_current()? {
+            // Compiled on the next realize, or with the next working shader
+            self.computes.borrow_mut().push(pass);
+            return Ok(());
+        }
+
+        match unsafe { Self::build_compute_program(&pass.wrap()) } {
+            Ok(id) => {
+                let mut state = self.gl_state.borrow_mut();
+                let state = state.as_mut().expect("GL state checked above");
+                state.computes.push(ComputeState {
+                    pass: pass.clone(),
+                    program: Program {
+                        id,
+                        ..Program::default()
+                    },
+                    pending: false,
+                });
+                unsafe { self.bind_programs(state) };
+            }
+            Err(e) => {
+                let e = e.in_pass(&pass.name);
+                self.report_compile_error(&e);
+                return Err(e.into());
+            }
+        }
+
+        self.computes.borrow_mut().push(pass);
+        self.obj().queue_render();
+        Ok(())
+    }
+
+    /// Removes the compute pass called `name`.
+    ///
+    /// # Returns
+    ///
+    /// Whether a compute pass with that name existed.
+    pub fn remove_compute(&self, name: &str) -> bool {
+        let Some(index) = self.computes.borrow().iter().position(|p| p.name == name) else {
+            return false;
+        };
+        self.computes.borrow_mut().remove(index);
+
+        if let Some(state) = self.gl_state.borrow_mut().as_mut()
+            && let Some(index) = state.computes.iter().position(|c| c.pass.name == name)
+        {
+            let removed = state.computes.remove(index);
+
+            self.obj().make_current();
+            if let Some(e) = self.obj().error() {
+                error!("Failed to switch OpenGL context: {e}");
+            } else {
+                unsafe { epoxy::DeleteProgram(removed.program.id) };
+            }
+        }
+
+        self.obj().queue_render();
+        true
+    }
+
+    /// Runs an on-demand compute pass on the next frame.
+    ///
+    /// # Returns
+    ///
+    /// Whether the widget is realized with a compute pass called `name`.
+    pub fn run_compute(&self, name: &str) -> bool {
+        let mut state = self.gl_state.borrow_mut();
+        let Some(compute) = state
+            .as_mut()
+            .and_then(|state| state.computes.iter_mut().find(|c| c.pass.name == name))
+        else {
+            return false;
+        };
+        compute.pending = true;
+        self.obj().queue_render();
+        true
+    }
+
+    /// Creates a storage buffer, or replaces the contents of the buffer with
+    /// the same name.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError`] if the buffer is invalid or if the context
+    /// doesn't support storage buffers.
+    pub fn set_storage_buffer(&self, buffer: StorageBuffer) -> Result<(), glib::Error> {
+        buffer.validate()?;
+        let realized = self.make_compute_current()?;
+
+        let mut buffers = self.storage_buffers.borrow_mut();
+        let index = buffers.iter().position(|b| b.name == buffer.name);
+        if realized {
+            let mut state = self.gl_state.borrow_mut();
+            let state = state.as_mut().expect("GL state checked above");
+            unsafe {
+                match index {
+                    Some(index) => {
+                        buffer.upload(state.storage_buffers[index]);
+                    }
+                    None => state.storage_buffers.push(buffer.upload(0)),
+                }
+            }
+        }
+        match index {
+            Some(index) => buffers[index] = buffer,
+            None => buffers.push(buffer),
+        }
+        drop(buffers);
+
+        if realized && index.is_none() {
+            let mut state = self.gl_state.borrow_mut();
+            unsafe { self.bind_programs(state.as_mut().expect("GL state checked above")) };
+        }
+        self.obj().queue_render();
+        Ok(())
+    }
+
+    /// Creates a storage image, or replaces the image with the same name.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError`] if the image is invalid or if the context
+    /// doesn't support storage images.
+    pub fn set_storage_image(&self, image: StorageImage) -> Result<(), glib::Error> {
+        image.validate()?;
+        let realized = self.make_compute_current()?;
+
+        let mut images = self.storage_images.borrow_mut();
+        let index = images.iter().position(|i| i.name == image.name);
+        if realized {
+            let mut state = self.gl_state.borrow_mut();
+            let state = state.as_mut().expect("GL state checked above");
+            unsafe {
+                match index {
+                    Some(index) => {
+                        image.upload(state.storage_images[index]);
+                    }
+                    None => state.storage_images.push(image.upload(0)),
+                }
+            }
+        }
+        match index {
+            Some(index) => images[index] = image,
+            None => images.push(image),
+        }
+        drop(images);
+
+        if realized && index.is_none() {
+            let mut state = self.gl_state.borrow_mut();
+            unsafe { self.bind_programs(state.as_mut().expect("GL state checked above")) };
+        }
+        self.obj().queue_render();
+        Ok(())
+    }
+
+    /// Reads back the contents of a storage buffer, as of the last frame.
+    ///
+    /// # Returns
+    ///
+    /// The contents, or `None` if the widget isn't realized with a buffer
+    /// called `name`.
+    pub fn read_storage_buffer(&self, name: &str) -> Option<glib::Bytes> {
+        let index = self
+            .storage_buffers
+            .borrow()
+            .iter()
+            .position(|b| b.name == name)?;
+        let state = self.gl_state.borrow();
+        let buffer = *state.as_ref()?.storage_buffers.get(index)?;
+
+        self.obj().make_current();
+        if let Some(e) = self.obj().error() {
+            error!("Failed to switch OpenGL context: {e}");
+            return None;
+        }
+        Some(unsafe { compute::read_buffer(buffer) })
+    }
+
+    /// Reads back the pixels of a storage image, as of the last frame.
+    ///
+    /// # Returns
+    ///
+    /// The pixels, row by row from the bottom, or `None` if the widget isn't
+    /// realized with an image called `name`.
+    pub fn read_storage_image(&self, name: &str) -> Option<glib::Bytes> {
+        let images = self.storage_images.borrow();
+        let index = images.iter().position(|i| i.name == name)?;
+        let state = self.gl_state.borrow();
+        let texture = *state.as_ref()?.storage_images.get(index)?;
+
+        self.obj().make_current();
+        if let Some(e) = self.obj().error() {
+            error!("Failed to switch OpenGL context: {e}");
+            return None;
+        }
+        Some(unsafe { images[index].read(texture) })
+    }
+
+    /// Adds an offscreen render pass, rendered after the existing ones.
+    ///
+    /// If the widget is realized, the pass is compiled immediately.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError`] if the pass is invalid or if its shader
+    /// fails to compile or link.
+    pub fn add_pass(&self, pass: Pass) -> Result<(), glib::Error> {
+        pass.validate(self.passes.borrow().iter())?;
+
+        if self.gl_state.borrow().is_none() {
+            // Compiled on the next realize, or with the next working shader
+            self.passes.borrow_mut().push(pass);
+            return Ok(());
+        }
+
+        self.obj().make_current();
+        if let Some(e) = self.obj().error() {
+            error!("Failed to switch OpenGL context: {e}");
+            return Err(e);
+        }
+
+        let program = self
+            .prepare_source(&pass.shader, Some(&pass.name))
+            .and_then(|shader| unsafe { Self::build_program(None, &shader, self.uses_es()) });
+        match program {
+            Ok(program) => {
+                let mut state = self.gl_state.borrow_mut();
+                let state = state.as_mut().expect("GL state checked above");
+                unsafe {
+                    state.passes.push(PassState {
+                        pass: pass.clone(),
+                        program: Program {
+                            id: program,
+                            ..Program::default()
+                        },
+                        target: PassTarget::new(),
+                    });
+                    self.bind_programs(state);
+                }
+            }
+            Err(e) => {
+                // Emitted once no borrow is held, so handlers can call back
+                // into the widget
+                let e = e.in_pass(&pass.name);
+                self.report_compile_error(&e);
+                return Err(e.into());
+            }
+        }
+
+        self.passes.borrow_mut().push(pass);
+        self.obj().queue_render();
+        Ok(())
+    }
+
+    /// Removes the render pass called `name`.
+    ///
+    /// # Returns
+    ///
+    /// Whether a pass with that name existed.
+    pub fn remove_pass(&self, name: &str) -> bool {
+        let Some(index) = self.passes.borrow().iter().position(|p| p.name == name) else {
+            return false;
+        };
+        self.passes.borrow_mut().remove(index);
+
+        if let Some(state) = self.gl_state.borrow_mut().as_mut() {
+            let removed = state.passes.remove(index);
+
+            self.obj().make_current();
+            if let Some(e) = self.obj().error() {
+                error!("Failed to switch OpenGL context: {e}");
+            } else {
+                unsafe {
+                    epoxy::DeleteProgram(removed.program.id);
+                    removed.target.delete();
+                    // The texture units of the following passes changed
+                    self.bind_programs(state);
+                }
+            }
+        } else {
+            // The removed pass may be the one that failed to compile
+            self.rebuild();
+        }
+
+        self.obj().queue_render();
+        true
+    }
+
+    /// Deletes all OpenGL objects of a state.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    unsafe fn delete_gl_state(state: &GLState) {
+        unsafe {
+            for program in state.programs() {
+                epoxy::DeleteProgram(program.id);
+            }
+            for pass in &state.passes {
+                pass.target.delete();
+            }
+            if let Some(particles) = &state.particles {
+                particles.target.delete();
+            }
+            if let Some(mesh) = &state.mesh {
+                mesh.delete();
+            }
+            if !state.storage_buffers.is_empty() {
+                epoxy::DeleteBuffers(
+                    state.storage_buffers.len() as i32,
+                    state.storage_buffers.as_ptr(),
+                );
+            }
+            if !state.storage_images.is_empty() {
+                epoxy::DeleteTextures(
+                    state.storage_images.len() as i32,
+                    state.storage_images.as_ptr(),
+                );
+            }
+            if !state.uniform_blocks.is_empty() {
+                epoxy::DeleteBuffers(
+                    state.uniform_blocks.len() as i32,
+                    state.uniform_blocks.as_ptr(),
+                );
+            }
+            epoxy::DeleteVertexArrays(1, &raw const state.vao);
+            let textures: Vec<u32> = state
+                .textures
+                .iter()
+                .map(|t| t.id)
+                .chain([state.keyboard_texture])
+                .collect();
+            if !textures.is_empty() {
+                epoxy::DeleteTextures(textures.len() as i32, textures.as_ptr());
+            }
+        }
+    }
+
+    /// Recreates all OpenGL objects, e.g. after a change that affects how
+    /// every shader is compiled.
+    ///
+    /// Errors are reported through `compile-error`.
+    fn rebuild(&self) {
+        if !self.obj().is_realized() {
+            return;
+        }
+
+        self.obj().make_current();
+        if let Some(e) = self.obj().error() {
+            error!("Failed to switch OpenGL context: {e}");
+            return;
+        }
+
+        if let Some(state) = self.gl_state.borrow_mut().take() {
+            unsafe { Self::delete_gl_state(&state) };
+        }
+        let shader = self.shader_source.borrow().clone();
+        match self.create_gl_state(&shader) {
+            Ok(state) => {
+                self.gl_state.replace(Some(state));
+            }
+            Err(e) => self.report_compile_error(&e),
+        }
+        self.obj().queue_render();
+    }
+
+    /// Sets the dialect of the shader sources, recompiling them if the
+    /// widget is realized.
+    fn set_dialect(&self, dialect: ShaderDialect) {
+        if self.dialect.replace(dialect) != dialect {
+            self.rebuild();
+        }
+    }
+
+    /// Sets the sampler bound to a Shadertoy `iChannelN`, recompiling the
+    /// shaders if the widget is realized.
+    ///
+    /// # Arguments
+    ///
+    /// * `pass` - The name of the pass, or `None` for the widget's shader
+    /// * `channel` - The channel index, from 0 to 3
+    /// * `sampler` - The name of the sampler uniform to bind (`tex0`,
+    ///   `keyboard`, the name of a pass…), or `None` to unbind the channel
+    pub fn set_channel(&self, pass: Option<&str>, channel: usize, sampler: Option<&str>) {
+        if channel >= CHANNELS {
+            warn!("Invalid Shadertoy channel: {channel}");
+            return;
+        }
+
+        let key = pass.unwrap_or_default().to_owned();
+        let default = self.default_channels();
+        self.channels.borrow_mut().entry(key).or_insert(default)[channel] =
+            sampler.map(str::to_owned);
+
+        if self.dialect.get() == ShaderDialect::Shadertoy {
+            self.rebuild();
+        }
+    }
+
+    /// Returns the channels bound when none were set: one per texture.
+    fn default_channels(&self) -> Channels {
+        shadertoy::default_channels(self.texture_paths.borrow().len())
+    }
+
+    /// Rewrites a shader source from the widget's dialect into the GLSL
+    /// that is compiled.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The shader source
+    /// * `pass` - The name of the pass, or `None` for the widget's shader
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CompileError`] if the source can't be rewritten, e.g.
+    /// because of an invalid ISF header.
+    fn prepare_source(&self, source: &str, pass: Option<&str>) -> Result<String, CompileError> {
+        match self.dialect.get() {
+            ShaderDialect::Glsl if !legacy::is_legacy(source) => Ok(source.to_owned()),
+            ShaderDialect::Glsl | ShaderDialect::Legacy => Ok(legacy::wrap(source)),
+            ShaderDialect::Shadertoy => {
+                let channels = self
+                    .channels
+                    .borrow()
+                    .get(pass.unwrap_or_default())
+                    .cloned()
+                    .unwrap_or_else(|| self.default_channels());
+                Ok(shadertoy::wrap(source, &channels, pass.is_none()))
+            }
+            ShaderDialect::Transition => Ok(transition::wrap(source)),
+            ShaderDialect::Isf => isf::wrap(source, pass, self.texture_paths.borrow().len())
+                .map_err(|e| {
+                    CompileError::from_log(
+                        ShaderStage::Fragment,
+                        format!("Invalid ISF header: {e}"),
+                    )
+                }),
+        }
+    }
+
+    /// Returns the metadata of the inputs declared by the widget's ISF
+    /// shader.
+    pub fn isf_inputs(&self) -> glib::Variant {
+        isf::inputs_variant(&self.shader_source.borrow())
+    }
+
+    /// Creates all OpenGL objects for the given fragment shader and the
+    /// render passes.
+    ///
+    /// An active OpenGL context must be bound before calling.
+    fn create_gl_state(&self, shader: &str) -> Result<GLState, CompileError> {
+        let isf_events = match self.dialect.get() {
+            ShaderDialect::Isf => isf::Header::parse(shader)
+                .map(|(header, _)| {
+                    header
+                        .inputs
+                        .into_iter()
+                        .filter(|input| input.kind == isf::InputType::Event)
+                        .map(|input| input.name)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        let shader = self.prepare_source(shader, None)?;
+        let program = unsafe {
+            Self::build_program(
+                self.widget_vertex_source().as_deref(),
+                &shader,
+                self.uses_es(),
+            )?
+        };
+
+        let mut passes = Vec::new();
+        for pass in self.passes.borrow().iter() {
+            let id = self
+                .prepare_source(&pass.shader, Some(&pass.name))
+                .and_then(|shader| unsafe { Self::build_program(None, &shader, self.uses_es()) });
+            match id {
+                Ok(id) => passes.push(PassState {
+                    pass: pass.clone(),
+                    program: Program {
+                        id,
+                        ..Program::default()
+                    },
+                    target: unsafe { PassTarget::new() },
+                }),
+                Err(e) => unsafe {
+                    epoxy::DeleteProgram(program);
+                    for pass in &passes {
+                        epoxy::DeleteProgram(pass.program.id);
+                        pass.target.delete();
+                    }
+                    return Err(e.in_pass(&pass.name));
+                },
+            }
+        }
+
+        let computes = match unsafe { self.build_computes() } {
+            Ok(computes) => computes,
+            Err(e) => unsafe {
+                epoxy::DeleteProgram(program);
+                for pass in &passes {
+                    epoxy::DeleteProgram(pass.program.id);
+                    pass.target.delete();
+                }
+                return Err(e);
+            },
+        };
+
+        // A broken particle system is left out rather than blanking the
+        // widget, like when it's set on a realized widget
+        let particles = self
+            .particles
+            .borrow()
+            .as_ref()
+            .map(|particles| unsafe { self.build_particles(particles) });
+        let particles = match particles {
+            Some(Ok(particles)) => Some(particles),
+            Some(Err(e)) => {
+                self.report_compile_error(&e);
+                None
+            }
+            None => None,
+        };
+
+        unsafe {
+            // Core profile requires a VAO even when no vertex attributes are used
+            let mut vao = 0u32;
+            epoxy::GenVertexArrays(1, &raw mut vao);
+            epoxy::BindVertexArray(vao);
+
+            // Load textures into their texture units
+            let textures = self
+                .texture_paths
+                .borrow()
+                .iter()
+                .enumerate()
+                .map(|(i, tex)| Self::load_texture(i as u32, tex).unwrap_or_default())
+                .collect();
+
+            // Storage objects need the same support as compute shaders
+            let (storage_buffers, storage_images) = if self.compute_supported.get() {
+                (
+                    self.storage_buffers
+                        .borrow()
+                        .iter()
+                        .map(|buffer| buffer.upload(0))
+                        .collect(),
+                    self.storage_images
+                        .borrow()
+                        .iter()
+                        .map(|image| image.upload(0))
+                        .collect(),
+                )
+            } else {
+                (Vec::new(), Vec::new())
+            };
+
+            let mesh = self.mesh.borrow().as_ref().map(|mesh| {
+                let buffers = MeshBuffers::new(mesh);
+                epoxy::BindVertexArray(vao);
+                buffers
+            });
+
+            let mut state = GLState {
+                program: Program {
+                    id: program,
+                    ..Program::default()
+                },
+                passes,
+                vao,
+                textures,
+                timer: FrameTimer::default(),
+                keyboard_texture: 0,
+                particles,
+                computes,
+                storage_buffers,
+                storage_images,
+                uniform_blocks: Vec::new(),
+                mesh,
+                isf_events,
+            };
+            self.bind_programs(&mut state);
+            Ok(state)
+        }
+    }
+
+    /// Resolves the texture samplers and uniform locations of every program,
+    /// using the current uniform values.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    unsafe fn bind_programs(&self, state: &mut GLState) {
+        let texture_count = state.textures.len();
+        let samplers = self.samplers(state);
+        let uniforms = self.uniforms.borrow();
+
+        unsafe {
+            for program in state.programs_mut() {
+                epoxy::UseProgram(program.id);
+
+                for (name, unit) in &samplers {
+                    let loc = Self::uniform_location(program.id, name);
+                    if loc >= 0 {
+                        epoxy::Uniform1i(loc, *unit as i32);
+                    }
+                }
+
+                // Collect uniform locations
+                program.active = active::active_uniforms(program.id);
+                program.uniforms = uniforms
+                    .iter()
+                    .filter_map(|(name, value)| {
+                        let loc = Self::uniform_location(program.id, name);
+                        if loc < 0 {
+                            return None;
+                        }
+                        Self::fit_uniform(program, name, value)
+                            .inspect_err(|e| error!("{e}"))
+                            .ok()
+                            .map(|value| (name.clone(), (loc, value)))
+                    })
+                    .collect();
+
+                // Collect the locations and declared types of the builtins
+                // the shader declares, skipping those of an unrelated type
+                let frame = FrameInfo::default();
+                program.builtins = Builtin::all(texture_count)
+                    .filter_map(|(name, builtin)| {
+                        let loc = Self::uniform_location(program.id, &name);
+                        if loc < 0 {
+                            return None;
+                        }
+                        let active = Self::declared_uniform(program, &name);
+                        if let Some(active) = active
+                            && let Err(e) = active.coerce(&name, &builtin.value(&frame))
+                        {
+                            error!("{e}");
+                            return None;
+                        }
+                        Some((builtin, loc, active))
+                    })
+                    .collect();
+            }
+
+            self.bind_uniform_blocks(state);
+            if self.compute_supported.get() {
+                self.bind_storage_blocks(state);
+            }
+
+            // Inputs may only be used by some of the programs
+            for (i, tex) in self.texture_paths.borrow().iter().enumerate() {
+                let name = format!("tex{i}");
+                if !state
+                    .programs()
+                    .any(|program| Self::uniform_location(program.id, &name) >= 0)
+                {
+                    warn!("Texture not used in shader: {}", tex.display());
+                }
+            }
+        }
+        for name in uniforms.keys() {
+            if !state
+                .programs()
+                // Rejected values were reported when converting them
+                .any(|program| {
+                    program.uniforms.contains_key(name) || program.active.contains_key(name)
+                })
+            {
+                warn!("Uniform not used in shader: {name}");
+            }
+        }
+        self.queue_uniforms_changed();
+    }
+
+    /// Emits `uniforms-changed` once the current changes are done, so that
+    /// handlers can list the uniforms.
+    fn queue_uniforms_changed(&self) {
+        if self.uniforms_changed_pending.replace(true) {
+            return;
+        }
+        let this = self.obj().downgrade();
+        glib::idle_add_local_once(move || {
+            if let Some(this) = this.upgrade() {
+                this.imp().uniforms_changed_pending.set(false);
+                this.emit_by_name::<()>("uniforms-changed", &[]);
+            }
+        });
+    }
+
+    /// Lists the uniforms used by the shaders, samplers and images excluded.
+    pub fn list_uniforms(&self) -> Vec<UniformInfo> {
+        self.list_active(false)
+    }
+
+    /// Lists the samplers and images used by the shaders.
+    pub fn list_samplers(&self) -> Vec<UniformInfo> {
+        self.list_active(true)
+    }
+
+    /// Lists the uniforms used by any program, either the samplers and
+    /// images or the other ones, sorted by name.
+    ///
+    /// Uniforms declared by several programs are listed once, as declared
+    /// by the first one.
+    fn list_active(&self, opaque: bool) -> Vec<UniformInfo> {
+        let state = self.gl_state.borrow();
+        let Some(state) = state.as_ref() else {
+            return Vec::new();
+        };
+        let samplers: HashMap<String, u32> = self.samplers(state).into_iter().collect();
+        let builtins: HashSet<String> = Builtin::all(state.textures.len())
+            .map(|(name, _)| name)
+            .collect();
+
+        let mut list: Vec<UniformInfo> = Vec::new();
+        for program in state.programs() {
+            for (name, active) in &program.active {
+                if active.ty.is_opaque() != opaque || list.iter().any(|info| &info.name == name) {
+                    continue;
+                }
+                // Values set by the user take precedence, as when rendering
+                let (value, source) = if let Some((_, value)) = program.uniforms.get(name) {
+                    (Some(value.clone()), UniformSource::User)
+                } else if let Some(&unit) = samplers.get(name) {
+                    (Some(Uniform::Int(unit as i32)), UniformSource::Widget)
+                } else if builtins.contains(name) {
+                    (None, UniformSource::Widget)
+                } else {
+                    (None, UniformSource::Unset)
+                };
+                list.push(UniformInfo {
+                    name: name.clone(),
+                    type_name: active.ty.name(),
+                    size: active.size,
+                    value,
+                    source,
+                });
+            }
+        }
+        list.sort_by(|a, b| a.name.cmp(&b.name));
+        list
+    }
+
+    /// Returns the name of every sampler bound by the widget, with its
+    /// texture unit.
+    fn samplers(&self, state: &GLState) -> Vec<(String, u32)> {
+        let texture_count = state.textures.len();
+
+        // Textures (tex0, tex1, …) use their own unit, followed by the
+        // keyboard texture, the output of each pass, the particle state and
+        // the storage images
+        (0..texture_count)
+            .map(|i| (format!("tex{i}"), i as u32))
+            .chain([("keyboard".to_owned(), texture_count as u32)])
+            .chain(
+                state
+                    .passes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, pass)| (pass.pass.name.clone(), state.pass_unit(i))),
+            )
+            .chain(
+                state
+                    .particles
+                    .iter()
+                    .flat_map(|particles| 0..particles.target.buffers())
+                    .map(|i| (format!("particle_state{i}"), state.particle_unit(i))),
+            )
+            .chain(
+                self.storage_images
+                    .borrow()
+                    .iter()
+                    .zip(0..state.storage_images.len())
+                    .map(|(image, i)| (image.name.clone(), state.storage_unit(i))),
+            )
+            .collect()
+    }
+
+    /// Returns the location of a uniform in a program, or -1 if the program
+    /// doesn't use it.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    unsafe fn uniform_location(program: u32, name: &str) -> i32 {
+        let name_c = format!("{name}\0");
+        unsafe { epoxy::GetUniformLocation(program, name_c.as_ptr().cast::<i8>()) }
+    }
+
+    /// Returns the type a program declares for a uniform, if it uses it.
+    fn declared_uniform(program: &Program, name: &str) -> Option<ActiveUniform> {
+        program.active.get(name).copied().or_else(|| {
+            // Single array elements, like `colors[2]`, are reported as the
+            // whole array
+            let (array, _) = name.strip_suffix(']')?.rsplit_once('[')?;
+            let active = program.active.get(array)?;
+            Some(ActiveUniform {
+                size: 1,
+                array: false,
+                ..*active
+            })
+        })
+    }
+
+    /// Returns the value stored for a uniform of a program, converted to
+    /// the type the shader declares.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError::Uniform`] if the value can't be converted,
+    /// or if it is an array with more elements than the shader declares.
+    fn fit_uniform(program: &Program, name: &str, value: &Uniform) -> Result<Uniform, glib::Error> {
+        let Some(active) = Self::declared_uniform(program, name) else {
+            return Ok(value.clone());
+        };
+        let value = active.coerce(name, value)?;
+        if let Some(len) = value.array_len() {
+            let size = active.size.max(1) as usize;
+            if len > size {
+                return Err(glib::Error::new(
+                    ShaderError::Uniform,
+                    &format!(
+                        "Uniform array `{name}` has {len} elements, but the shader declares {size}"
+                    ),
+                ));
+            }
+        }
+        Ok(value)
+    }
+
+    /// Renders every pass into its output texture.
+    ///
+    /// The `resolution` builtin is set to the size of each pass while it
+    /// renders. The caller is responsible for binding the widget's
+    /// framebuffer again.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    unsafe fn render_passes(state: &mut GLState, frame: &mut FrameInfo) {
+        for i in 0..state.passes.len() {
+            unsafe {
+                // Passes that already ran give this frame's output
+                Self::bind_pass_outputs(state);
+
+                let pass = &mut state.passes[i];
+                pass.target.bind();
+                frame.resolution = pass.target.size().map(|v| v as f32);
+                Self::apply_uniforms(&pass.program, frame);
+                epoxy::DrawArrays(epoxy::TRIANGLE_STRIP, 0, 4);
+                pass.target.swap();
+            }
+        }
+    }
+
+    /// Renders the next state of the particle system into its state
+    /// textures.
+    ///
+    /// The `resolution` builtin is set to the size of the state grid while
+    /// the update shader renders. The caller is responsible for binding the
+    /// widget's framebuffer again.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    unsafe fn update_particles(state: &mut GLState, frame: &mut FrameInfo) {
+        unsafe {
+            // Passes give their output from the previous frame
+            Self::bind_pass_outputs(state);
+            Self::bind_particle_outputs(state);
+
+            let Some(particles) = &mut state.particles else {
+                return;
+            };
+            particles.target.bind();
+            frame.resolution = particles.target.size().map(|v| v as f32);
+            Self::apply_uniforms(&particles.update, frame);
+            epoxy::DrawArrays(epoxy::TRIANGLE_STRIP, 0, 4);
+            particles.target.swap();
 
-            epoxy::UseProgram(program);
+            // Passes see the new state
+            Self::bind_particle_outputs(state);
+        }
+    }
 
-            // Load textures and bind them to texture units
-            let mut texture_ids = Vec::with_capacity(textures.len());
-            for (i, tex) in textures.iter().enumerate() {
-                let Some(id) = Self::load_texture(i as u32, tex) else {
-                    continue;
-                };
-                texture_ids.push(id);
+    /// Binds the current state of the particle system to the texture units
+    /// of its buffers.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    unsafe fn bind_particle_outputs(state: &GLState) {
+        let Some(particles) = &state.particles else {
+            return;
+        };
+        for (i, texture) in particles.target.outputs().enumerate() {
+            unsafe {
+                epoxy::ActiveTexture(epoxy::TEXTURE0 + state.particle_unit(i));
+                epoxy::BindTexture(epoxy::TEXTURE_2D, texture);
+            }
+        }
+    }
 
-                // Bind the texture to its sampler uniform (tex0, tex1, …)
-                let name = format!("tex{i}\0");
-                let loc = epoxy::GetUniformLocation(program, name.as_ptr().cast::<i8>());
-                if loc >= 0 {
-                    epoxy::Uniform1i(loc, i as i32);
-                } else {
-                    warn!("Texture not used in shader: {}", tex.display());
+    /// Binds the storage buffers and images of every compute shader:
+    /// buffer blocks to their binding point and image uniforms to their
+    /// image unit.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// supporting compute shaders must be bound before calling.
+    unsafe fn bind_storage_blocks(&self, state: &GLState) {
+        unsafe {
+            for program in state.programs() {
+                for (i, buffer) in self.storage_buffers.borrow().iter().enumerate() {
+                    let name_c = format!("{}\0", buffer.name);
+                    let index = epoxy::GetProgramResourceIndex(
+                        program.id,
+                        epoxy::SHADER_STORAGE_BLOCK,
+                        name_c.as_ptr().cast::<i8>(),
+                    );
+                    if index != epoxy::INVALID_INDEX {
+                        epoxy::ShaderStorageBlockBinding(program.id, index, i as u32);
+                    }
                 }
             }
 
-            // Collect uniform locations
-            let mut uniform_map = HashMap::new();
-            for (name, value) in &uniforms {
-                let name_c = format!("{name}\0");
-                let loc = epoxy::GetUniformLocation(program, name_c.as_ptr().cast::<i8>());
-                if loc >= 0 {
-                    uniform_map.insert(name.clone(), (loc, value.clone()));
-                } else {
-                    warn!("Uniform not used in shader: {name}");
+            // Render shaders sample the images, set up with the samplers
+            for compute in &state.computes {
+                epoxy::UseProgram(compute.program.id);
+                for (i, image) in self.storage_images.borrow().iter().enumerate() {
+                    let loc = Self::uniform_location(compute.program.id, &image.name);
+                    if loc >= 0 {
+                        epoxy::Uniform1i(loc, i as i32);
+                    }
                 }
             }
-
-            self.gl_state.borrow_mut().replace(GLState {
-                program,
-                vao,
-                textures: texture_ids,
-                uniforms: uniform_map,
-            });
         }
     }
 
-    fn unrealize(&self) {
-        self.obj().make_current();
-        if let Some(e) = self.obj().error() {
-            error!("Failed to switch OpenGL context: {e}");
-            return;
-        }
+    /// Assigns each uniform block to the binding point of its index in every
+    /// program declaring it, and fills its buffer with the contents laid out
+    /// for the current programs.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    unsafe fn bind_uniform_blocks(&self, state: &mut GLState) {
+        let blocks = self.uniform_blocks.borrow();
+        unsafe {
+            // Blocks past the last binding point can't be bound
+            let mut max_bindings = 0;
+            epoxy::GetIntegerv(epoxy::MAX_UNIFORM_BUFFER_BINDINGS, &raw mut max_bindings);
+            let count = blocks.len().min(max_bindings.max(0) as usize);
+            for block in &blocks[count..] {
+                warn!(
+                    "Uniform block `{}` ignored: only {count} uniform buffer bindings are available",
+                    block.name
+                );
+            }
 
-        if let Some(state) = self.gl_state.borrow_mut().take() {
-            unsafe {
-                epoxy::DeleteProgram(state.program);
-                epoxy::DeleteVertexArrays(1, &raw const state.vao);
-                if !state.textures.is_empty() {
-                    epoxy::DeleteTextures(state.textures.len() as i32, state.textures.as_ptr());
+            while state.uniform_blocks.len() < count {
+                let mut buffer = 0;
+                epoxy::GenBuffers(1, &raw mut buffer);
+                state.uniform_blocks.push(buffer);
+            }
+
+            for (i, block) in blocks[..count].iter().enumerate() {
+                let mut used = false;
+                for program in state.programs() {
+                    used |= block.bind(program.id, i as u32);
+                }
+                if used {
+                    let programs = state.programs().map(|p| p.id);
+                    block.upload(state.uniform_blocks[i], programs);
+                } else {
+                    warn!("Uniform block not used in shader: {}", block.name);
                 }
             }
         }
-
-        self.parent_unrealize();
     }
-}
 
-impl GLAreaImpl for ShaderArea {
-    fn render(&self, _ctx: &gtk::gdk::GLContext) -> Propagation {
-        if let Some(e) = self.obj().error() {
-            error!("Failed to switch OpenGL context: {e}");
-            return Propagation::Stop;
+    /// Binds the storage buffers and images for the frame: buffers to their
+    /// binding point, and images both to their image unit and to the
+    /// texture unit render shaders sample them from.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    unsafe fn bind_storage(&self, state: &GLState) {
+        unsafe {
+            for (i, &buffer) in state.storage_buffers.iter().enumerate() {
+                epoxy::BindBufferBase(epoxy::SHADER_STORAGE_BUFFER, i as u32, buffer);
+            }
+            let images = self.storage_images.borrow();
+            for (i, (image, &texture)) in images.iter().zip(&state.storage_images).enumerate() {
+                image.bind(i as u32, texture);
+                epoxy::ActiveTexture(epoxy::TEXTURE0 + state.storage_unit(i));
+                epoxy::BindTexture(epoxy::TEXTURE_2D, texture);
+            }
         }
+    }
 
-        if let Some(state) = self.gl_state.borrow().as_ref() {
+    /// Dispatches the compute passes due this frame, in order.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// supporting compute shaders must be bound before calling.
+    unsafe fn dispatch_computes(state: &mut GLState, frame: &FrameInfo) {
+        for compute in &mut state.computes {
+            if compute.pass.schedule == ComputeSchedule::OnDemand
+                && !std::mem::take(&mut compute.pending)
+            {
+                continue;
+            }
+            let [x, y, z] = compute.pass.groups;
             unsafe {
-                epoxy::ClearColor(0.0, 0.0, 0.0, 0.0);
-                epoxy::Clear(epoxy::COLOR_BUFFER_BIT);
-
-                Self::apply_uniforms(state);
-                epoxy::BindVertexArray(state.vao);
-
-                // Bind textures to their respective texture units
-                for (i, &id) in state.textures.iter().enumerate() {
-                    epoxy::ActiveTexture(epoxy::TEXTURE0 + i as u32);
-                    epoxy::BindTexture(epoxy::TEXTURE_2D, id);
-                }
-
-                // Draw a fullscreen quad using TRIANGLE_STRIP
-                // 4 vertices: (0,0), (1,0), (0,1), (1,1)
-                epoxy::DrawArrays(epoxy::TRIANGLE_STRIP, 0, 4);
-
-                epoxy::Flush();
+                Self::apply_uniforms(&compute.program, frame);
+                epoxy::DispatchCompute(x, y, z);
+                // Later passes and shaders may read anything it wrote
+                epoxy::MemoryBarrier(epoxy::ALL_BARRIER_BITS);
             }
         }
-
-        Propagation::Stop
     }
-}
 
-impl ShaderArea {
-    /// Stores initialization data for later use when the widget is realized.
+    /// Binds the latest output of every pass to its texture unit.
     ///
-    /// # Arguments
+    /// # Safety
     ///
-    /// * `shader` - GLSL fragment shader source code
-    /// * `textures` - Paths to image files to load as textures
-    /// * `uniforms` - Initial uniform values
-    pub fn initialize(
-        &self,
-        shader: String,
-        textures: Vec<PathBuf>,
-        uniforms: HashMap<String, Uniform>,
-    ) {
-        *self.shader_source.borrow_mut() = Some(shader);
-        *self.texture_paths.borrow_mut() = Some(textures);
-        *self.initial_uniforms.borrow_mut() = Some(uniforms);
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    unsafe fn bind_pass_outputs(state: &GLState) {
+        for (i, pass) in state.passes.iter().enumerate() {
+            unsafe {
+                epoxy::ActiveTexture(epoxy::TEXTURE0 + state.pass_unit(i));
+                epoxy::BindTexture(epoxy::TEXTURE_2D, pass.target.output());
+            }
+        }
     }
 
-    /// Sets a uniform value on the shader program.
+    /// Computes the values of the builtin uniforms for a new frame.
     ///
-    /// # Arguments
+    /// # Safety
     ///
-    /// * `name` - The name of the uniform variable
-    /// * `value` - The new value to set
-    pub fn set_uniform(&self, name: String, value: Uniform) {
-        let mut state = self.gl_state.borrow_mut();
-        let Some(state) = state.as_mut() else {
-            warn!("Couldn't set uniform because the widget isn't being rendered");
-            return;
-        };
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    unsafe fn frame_info(&self, state: &mut GLState) -> FrameInfo {
+        let obj = self.obj();
 
-        self.obj().make_current();
-        if let Some(e) = self.obj().error() {
-            error!("Failed to switch OpenGL context: {e}");
-            return;
-        }
+        let now = obj
+            .frame_clock()
+            .map_or_else(glib::monotonic_time, |clock| clock.frame_time());
+        let (time, time_delta, frame) = state.timer.tick(now);
 
-        // Get or find the uniform location
-        let location = if let Some((location, _)) = state.uniforms.get(&name) {
-            *location
+        // GLArea sets the viewport to the size of its framebuffer
+        let mut viewport = [0i32; 4];
+        unsafe { epoxy::GetIntegerv(epoxy::VIEWPORT, viewport.as_mut_ptr()) };
+        let resolution = [viewport[2] as f32, viewport[3] as f32];
+        let camera = self.camera.get();
+        let aspect = f64::from(resolution[0].max(1.0)) / f64::from(resolution[1].max(1.0));
+        let scale_factor = if obj.width() > 0 {
+            resolution[0] / obj.width() as f32
         } else {
-            let name_c = format!("{name}\0");
-            unsafe { epoxy::GetUniformLocation(state.program, name_c.as_ptr().cast::<i8>()) }
+            obj.scale_factor() as f32
         };
 
-        if location < 0 {
-            warn!("Uniform not used in shader: {name}");
-            return;
+        FrameInfo {
+            time,
+            time_delta,
+            frame,
+            resolution,
+            scale_factor,
+            date: FrameInfo::local_date(),
+            texture_sizes: state
+                .textures
+                .iter()
+                .map(|t| [t.size[0] as f32, t.size[1] as f32])
+                .collect(),
+            pointer: self
+                .pointer
+                .borrow_mut()
+                .frame([obj.width() as f32, obj.height() as f32], scale_factor),
+            view: self.view.get().params(),
+            view_matrix: self.view.get().matrix(self.aspect()),
+            vertex_count: self.vertex_count.get() as f32,
+            particle_count: self.particle_count.get() as i32,
+            model_matrix: self
+                .mesh
+                .borrow()
+                .as_ref()
+                .map_or(camera::IDENTITY, |mesh| mesh.model_matrix),
+            camera_matrix: camera.view_matrix(),
+            projection_matrix: OrbitCamera::projection_matrix(aspect),
+            camera_position: camera.position().map(|v| v as f32),
         }
-
-        // Update the uniform value
-        state.uniforms.insert(name, (location, value));
-
-        // Queue a redraw to apply the new uniform
-        self.obj().queue_render();
     }
 
     /// Builds the vertex shader for a fullscreen quad.
@@ -268,6 +3197,107 @@ void main() {{
         )
     }
 
+    /// Returns whether the widget's context uses OpenGL ES.
+    fn uses_es(&self) -> bool {
+        self.obj().api() == gdk::GLAPI::GLES
+    }
+
+    /// Returns the GLSL version header for the current OpenGL API.
+    ///
+    /// GTK can use either OpenGL or OpenGL ES depending on the platform.
+    /// The GLSL version header differs between the two.
+    fn glsl_version(uses_es: bool) -> &'static str {
+        if uses_es {
+            "#version 300 es\nprecision highp float;\n"
+        } else {
+            "#version 330 core\n"
+        }
+    }
+
+    /// Compiles and links the user's fragment shader with a custom vertex
+    /// shader, or the fullscreen quad vertex shader.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    unsafe fn build_program(
+        vertex: Option<&str>,
+        shader: &str,
+        uses_es: bool,
+    ) -> Result<u32, CompileError> {
+        let glsl_version = Self::glsl_version(uses_es);
+        let vertex_shader = match vertex {
+            Some(vertex) => format!("{glsl_version}{}", vertex::wrap(vertex)),
+            None => Self::build_vertex_shader(glsl_version),
+        };
+        let mut fragment_shader = glsl_version.to_owned();
+        append_user_source(&mut fragment_shader, shader);
+
+        unsafe { Self::link_program(&vertex_shader, &fragment_shader) }
+    }
+
+    /// Checks that a fragment shader compiles and links, using a temporary
+    /// OpenGL context from the default display.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError`] if no context can be created or if the
+    /// shader fails to compile or link.
+    pub fn check_shader(shader: &str) -> Result<(), glib::Error> {
+        let Some(display) = gdk::Display::default() else {
+            return Err(glib::Error::new(
+                ShaderError::Context,
+                "No default display to create an OpenGL context",
+            ));
+        };
+        let context = display
+            .create_gl_context()
+            .and_then(|context| context.realize().map(|()| context))
+            .map_err(|e| {
+                glib::Error::new(
+                    ShaderError::Context,
+                    &format!("Failed to create an OpenGL context: {e}"),
+                )
+            })?;
+
+        // Like the widget's default dialect
+        let shader = if legacy::is_legacy(shader) {
+            legacy::wrap(shader)
+        } else {
+            shader.to_owned()
+        };
+
+        // Restored after, as the caller may be in the middle of rendering
+        let previous = gdk::GLContext::current();
+        context.make_current();
+        let result =
+            unsafe { Self::build_program(None, &shader, context.api() == gdk::GLAPI::GLES) };
+        if let Ok(program) = result {
+            unsafe { epoxy::DeleteProgram(program) };
+        }
+        match previous {
+            Some(previous) => previous.make_current(),
+            None => gdk::GLContext::clear_current(),
+        }
+
+        result.map(|_| ()).map_err(Into::into)
+    }
+
+    /// Logs a compile error and emits the `compile-error` signal.
+    fn report_compile_error(&self, e: &CompileError) {
+        error!("{e}");
+        self.obj().emit_by_name::<()>(
+            "compile-error",
+            &[
+                &e.stage,
+                &e.log,
+                &e.messages_variant(),
+                &e.pass.clone().unwrap_or_default(),
+            ],
+        );
+    }
+
     /// Compiles a shader from source.
     ///
     /// # Safety
@@ -275,10 +3305,21 @@ void main() {{
     /// This function calls unsafe OpenGL functions. An active OpenGL context
     /// must be bound before calling.
     ///
+    /// # Arguments
+    ///
+    /// * `src` - The shader source
+    /// * `stage` - The stage to compile the shader for
+    ///
     /// # Returns
     ///
-    /// The shader ID, or 0 if compilation failed (shader is not deleted on failure)
-    unsafe fn compile_shader(src: &str, kind: u32) -> u32 {
+    /// The shader ID. On failure the shader is deleted.
+    unsafe fn compile_shader(src: &str, stage: ShaderStage) -> Result<u32, CompileError> {
+        let kind = match stage {
+            ShaderStage::Vertex => epoxy::VERTEX_SHADER,
+            ShaderStage::Compute => epoxy::COMPUTE_SHADER,
+            _ => epoxy::FRAGMENT_SHADER,
+        };
+
         unsafe {
             let shader = epoxy::CreateShader(kind);
             let ptr = src.as_ptr().cast::<i8>();
@@ -289,9 +3330,11 @@ void main() {{
             let mut ok = 0i32;
             epoxy::GetShaderiv(shader, epoxy::COMPILE_STATUS, &raw mut ok);
             if ok == 0 {
-                Self::log_shader_error(shader, "Shader");
+                let log = Self::info_log(shader, stage);
+                epoxy::DeleteShader(shader);
+                return Err(CompileError::from_log(stage, log));
             }
-            shader
+            Ok(shader)
         }
     }
 
@@ -301,50 +3344,95 @@ void main() {{
     ///
     /// This function calls unsafe OpenGL functions. An active OpenGL context
     /// must be bound before calling.
-    unsafe fn link_program(vertex: &str, fragment: &str) -> u32 {
+    ///
+    /// # Arguments
+    ///
+    /// * `vertex` - The vertex shader source
+    /// * `fragment` - The fragment shader source
+    unsafe fn link_program(vertex: &str, fragment: &str) -> Result<u32, CompileError> {
+        unsafe {
+            let vert = Self::compile_shader(vertex, ShaderStage::Vertex)?;
+            let frag = match Self::compile_shader(fragment, ShaderStage::Fragment) {
+                Ok(frag) => frag,
+                Err(e) => {
+                    epoxy::DeleteShader(vert);
+                    return Err(e);
+                }
+            };
+
+            Self::link_shaders(&[vert, frag])
+        }
+    }
+
+    /// Compiles a compute shader into a program.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// supporting compute shaders must be bound before calling.
+    unsafe fn build_compute_program(shader: &str) -> Result<u32, CompileError> {
+        // The line numbers restart in the code wrapped by `ComputePass::wrap`
+        let source = format!("{}{shader}", compute::GLSL_VERSION);
         unsafe {
-            let vert = Self::compile_shader(vertex, epoxy::VERTEX_SHADER);
-            let frag = Self::compile_shader(fragment, epoxy::FRAGMENT_SHADER);
+            let shader = Self::compile_shader(&source, ShaderStage::Compute)?;
+            Self::link_shaders(&[shader])
+        }
+    }
 
+    /// Links compiled shaders into a program, deleting the shaders.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    unsafe fn link_shaders(shaders: &[u32]) -> Result<u32, CompileError> {
+        unsafe {
             let program = epoxy::CreateProgram();
-            epoxy::AttachShader(program, vert);
-            epoxy::AttachShader(program, frag);
+            for &shader in shaders {
+                epoxy::AttachShader(program, shader);
+            }
             epoxy::LinkProgram(program);
 
+            for &shader in shaders {
+                epoxy::DeleteShader(shader);
+            }
+
             let mut ok = 0i32;
             epoxy::GetProgramiv(program, epoxy::LINK_STATUS, &raw mut ok);
             if ok == 0 {
-                Self::log_shader_error(program, "Program");
+                let log = Self::info_log(program, ShaderStage::Link);
+                epoxy::DeleteProgram(program);
+                return Err(CompileError::from_log(ShaderStage::Link, log));
             }
 
-            epoxy::DeleteShader(vert);
-            epoxy::DeleteShader(frag);
-            program
+            Ok(program)
         }
     }
 
-    /// Logs shader or program compilation/linking errors.
-    unsafe fn log_shader_error(id: u32, kind: &str) {
+    /// Reads the info log of a shader, or of a program for the link stage.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    unsafe fn info_log(id: u32, stage: ShaderStage) -> String {
         unsafe {
-            let mut log_len = 0i32;
-            let info_log_fn = match kind {
-                "Program" => epoxy::GetProgramInfoLog,
-                _ => epoxy::GetShaderInfoLog,
+            type GetIv = unsafe fn(u32, u32, *mut i32);
+            type GetInfoLog = unsafe fn(u32, i32, *mut i32, *mut i8);
+            let (get_iv, info_log_fn): (GetIv, GetInfoLog) = match stage {
+                ShaderStage::Link => (epoxy::GetProgramiv, epoxy::GetProgramInfoLog),
+                _ => (epoxy::GetShaderiv, epoxy::GetShaderInfoLog),
             };
-            info_log_fn(id, log_len, std::ptr::null_mut(), std::ptr::null_mut());
-            epoxy::GetProgramiv(id, epoxy::INFO_LOG_LENGTH, &raw mut log_len);
 
-            let mut buf = vec![0u8; log_len as usize];
-            info_log_fn(
-                id,
-                log_len,
-                std::ptr::null_mut(),
-                buf.as_mut_ptr().cast::<i8>(),
-            );
-            error!(
-                "{kind} compile/link error: {}",
-                String::from_utf8_lossy(&buf)
-            );
+            let mut log_len = 0i32;
+            get_iv(id, epoxy::INFO_LOG_LENGTH, &raw mut log_len);
+
+            let mut buf = vec![0u8; log_len.max(0) as usize];
+            let mut written = 0i32;
+            info_log_fn(id, log_len, &raw mut written, buf.as_mut_ptr().cast::<i8>());
+            buf.truncate(written.max(0) as usize);
+
+            String::from_utf8_lossy(&buf).into_owned()
         }
     }
 
@@ -362,8 +3450,8 @@ void main() {{
     ///
     /// # Returns
     ///
-    /// The texture ID, or `None` if loading failed
-    unsafe fn load_texture(index: u32, path: &Path) -> Option<u32> {
+    /// The loaded texture, or `None` if loading failed
+    unsafe fn load_texture(index: u32, path: &Path) -> Option<Texture> {
         let image = match image::open(path) {
             Ok(x) => x,
             Err(e) => {
@@ -380,27 +3468,7 @@ void main() {{
             epoxy::ActiveTexture(epoxy::TEXTURE0 + index);
             epoxy::BindTexture(epoxy::TEXTURE_2D, id);
 
-            // Set texture parameters
-            epoxy::TexParameteri(
-                epoxy::TEXTURE_2D,
-                epoxy::TEXTURE_MIN_FILTER,
-                epoxy::LINEAR as i32,
-            );
-            epoxy::TexParameteri(
-                epoxy::TEXTURE_2D,
-                epoxy::TEXTURE_MAG_FILTER,
-                epoxy::LINEAR as i32,
-            );
-            epoxy::TexParameteri(
-                epoxy::TEXTURE_2D,
-                epoxy::TEXTURE_WRAP_S,
-                epoxy::CLAMP_TO_EDGE as i32,
-            );
-            epoxy::TexParameteri(
-                epoxy::TEXTURE_2D,
-                epoxy::TEXTURE_WRAP_T,
-                epoxy::CLAMP_TO_EDGE as i32,
-            );
+            Self::set_texture_parameters(epoxy::LINEAR);
 
             // Upload texture data
             epoxy::TexImage2D(
@@ -415,30 +3483,137 @@ void main() {{
                 data.as_ptr().cast::<c_void>(),
             );
 
-            Some(id)
+            Some(Texture {
+                id,
+                size: [width, height],
+            })
+        }
+    }
+
+    /// Sets the filtering of the bound texture, clamping it to its edges.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    pub(super) unsafe fn set_texture_parameters(filter: u32) {
+        unsafe {
+            epoxy::TexParameteri(epoxy::TEXTURE_2D, epoxy::TEXTURE_MIN_FILTER, filter as i32);
+            epoxy::TexParameteri(epoxy::TEXTURE_2D, epoxy::TEXTURE_MAG_FILTER, filter as i32);
+            epoxy::TexParameteri(
+                epoxy::TEXTURE_2D,
+                epoxy::TEXTURE_WRAP_S,
+                epoxy::CLAMP_TO_EDGE as i32,
+            );
+            epoxy::TexParameteri(
+                epoxy::TEXTURE_2D,
+                epoxy::TEXTURE_WRAP_T,
+                epoxy::CLAMP_TO_EDGE as i32,
+            );
+        }
+    }
+
+    /// Applies all uniform values to a shader program.
+    ///
+    /// Builtins are applied first, so that values set by the user with the
+    /// same name take precedence.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    unsafe fn apply_uniforms(program: &Program, frame: &FrameInfo) {
+        unsafe {
+            epoxy::UseProgram(program.id);
+
+            for (builtin, location, active) in &program.builtins {
+                let value = builtin.value(frame);
+                match active {
+                    // The type was checked when collecting the locations, but
+                    // some values may still not fit, like a fractional time
+                    // declared as an int
+                    Some(active) => {
+                        if let Ok(value) = active.coerce("", &value) {
+                            Self::apply_uniform(*location, &value);
+                        }
+                    }
+                    None => Self::apply_uniform(*location, &value),
+                }
+            }
+            for (location, value) in program.uniforms.values() {
+                Self::apply_uniform(*location, value);
+            }
         }
     }
 
-    /// Applies all uniform values to the shader program.
+    /// Applies a single uniform value to the current program.
     ///
     /// # Safety
     ///
     /// This function calls unsafe OpenGL functions. An active OpenGL context
     /// must be bound before calling.
-    unsafe fn apply_uniforms(state: &GLState) {
+    unsafe fn apply_uniform(location: i32, value: &Uniform) {
         unsafe {
-            epoxy::UseProgram(state.program);
-
-            for (location, value) in state.uniforms.values() {
-                match &value {
-                    Uniform::Float(v) => epoxy::Uniform1f(*location, *v),
-                    Uniform::Vec2(v) => epoxy::Uniform2f(*location, v[0], v[1]),
-                    Uniform::Vec3(v) => epoxy::Uniform3f(*location, v[0], v[1], v[2]),
-                    Uniform::Vec4(v) => epoxy::Uniform4f(*location, v[0], v[1], v[2], v[3]),
-                    Uniform::Int(v) => epoxy::Uniform1i(*location, *v),
-                    Uniform::IVec2(v) => epoxy::Uniform2i(*location, v[0], v[1]),
-                    Uniform::IVec3(v) => epoxy::Uniform3i(*location, v[0], v[1], v[2]),
-                    Uniform::IVec4(v) => epoxy::Uniform4i(*location, v[0], v[1], v[2], v[3]),
+            match value {
+                Uniform::Float(v) => epoxy::Uniform1f(location, *v),
+                Uniform::Vec2(v) => epoxy::Uniform2f(location, v[0], v[1]),
+                Uniform::Vec3(v) => epoxy::Uniform3f(location, v[0], v[1], v[2]),
+                Uniform::Vec4(v) => epoxy::Uniform4f(location, v[0], v[1], v[2], v[3]),
+                Uniform::Int(v) => epoxy::Uniform1i(location, *v),
+                Uniform::IVec2(v) => epoxy::Uniform2i(location, v[0], v[1]),
+                Uniform::IVec3(v) => epoxy::Uniform3i(location, v[0], v[1], v[2]),
+                Uniform::IVec4(v) => epoxy::Uniform4i(location, v[0], v[1], v[2], v[3]),
+                Uniform::Bool(v) => epoxy::Uniform1i(location, i32::from(*v)),
+                Uniform::UInt(v) => epoxy::Uniform1ui(location, *v),
+                Uniform::UVec2(v) => epoxy::Uniform2ui(location, v[0], v[1]),
+                Uniform::UVec3(v) => epoxy::Uniform3ui(location, v[0], v[1], v[2]),
+                Uniform::UVec4(v) => epoxy::Uniform4ui(location, v[0], v[1], v[2], v[3]),
+                Uniform::Double(v) => epoxy::Uniform1d(location, *v),
+                Uniform::DVec2(v) => epoxy::Uniform2d(location, v[0], v[1]),
+                Uniform::DVec3(v) => epoxy::Uniform3d(location, v[0], v[1], v[2]),
+                Uniform::DVec4(v) => epoxy::Uniform4d(location, v[0], v[1], v[2], v[3]),
+                Uniform::Mat2(v) => epoxy::UniformMatrix2fv(location, 1, epoxy::FALSE, v.as_ptr()),
+                Uniform::Mat3(v) => epoxy::UniformMatrix3fv(location, 1, epoxy::FALSE, v.as_ptr()),
+                Uniform::Mat4(v) => epoxy::UniformMatrix4fv(location, 1, epoxy::FALSE, v.as_ptr()),
+                Uniform::FloatArray(v) => epoxy::Uniform1fv(location, v.len() as i32, v.as_ptr()),
+                Uniform::Vec2Array(v) => {
+                    epoxy::Uniform2fv(location, v.len() as i32, v.as_ptr().cast());
+                }
+                Uniform::Vec3Array(v) => {
+                    epoxy::Uniform3fv(location, v.len() as i32, v.as_ptr().cast());
+                }
+                Uniform::Vec4Array(v) => {
+                    epoxy::Uniform4fv(location, v.len() as i32, v.as_ptr().cast());
+                }
+                Uniform::IntArray(v) => epoxy::Uniform1iv(location, v.len() as i32, v.as_ptr()),
+                Uniform::IVec2Array(v) => {
+                    epoxy::Uniform2iv(location, v.len() as i32, v.as_ptr().cast());
+                }
+                Uniform::IVec3Array(v) => {
+                    epoxy::Uniform3iv(location, v.len() as i32, v.as_ptr().cast());
+                }
+                Uniform::IVec4Array(v) => {
+                    epoxy::Uniform4iv(location, v.len() as i32, v.as_ptr().cast());
+                }
+                Uniform::UIntArray(v) => epoxy::Uniform1uiv(location, v.len() as i32, v.as_ptr()),
+                Uniform::UVec2Array(v) => {
+                    epoxy::Uniform2uiv(location, v.len() as i32, v.as_ptr().cast());
+                }
+                Uniform::UVec3Array(v) => {
+                    epoxy::Uniform3uiv(location, v.len() as i32, v.as_ptr().cast());
+                }
+                Uniform::UVec4Array(v) => {
+                    epoxy::Uniform4uiv(location, v.len() as i32, v.as_ptr().cast());
+                }
+                Uniform::DoubleArray(v) => epoxy::Uniform1dv(location, v.len() as i32, v.as_ptr()),
+                Uniform::DVec2Array(v) => {
+                    epoxy::Uniform2dv(location, v.len() as i32, v.as_ptr().cast());
+                }
+                Uniform::DVec3Array(v) => {
+                    epoxy::Uniform3dv(location, v.len() as i32, v.as_ptr().cast());
+                }
+                Uniform::DVec4Array(v) => {
+                    epoxy::Uniform4dv(location, v.len() as i32, v.as_ptr().cast());
                 }
             }
         }
@@ -0,0 +1,285 @@
+//! Pointer and touch state for the input builtin uniforms.
+//!
+//! Event handlers record positions in logical widget coordinates (origin at
+//! the top-left). They are converted once per frame to device pixels with the
+//! origin at the bottom-left, matching `gl_FragCoord` and `resolution`.
+
+use gtk::{gdk, glib::translate::IntoGlib};
+
+/// Maximum number of touch points exposed through `touches`.
+pub const MAX_TOUCHES: usize = 10;
+
+/// Pointer and touch state recorded by the event controllers.
+#[derive(Debug, Default)]
+pub struct PointerState {
+    /// Last pointer position
+    position: [f32; 2],
+    /// Currently pressed buttons, as a bitmask (1: primary, 2: middle, 4: secondary)
+    buttons: i32,
+    /// Position of the last press
+    click: [f32; 2],
+    /// Position of the pointer the last time a button was down
+    down_position: [f32; 2],
+    /// Whether a press happened since the last frame
+    clicked: bool,
+    /// Offset of the current (or last) drag from its start point
+    drag_delta: [f32; 2],
+    /// Active touch points, in the order they started
+    touches: Vec<(gdk::EventSequence, [f32; 2])>,
+}
+
+impl PointerState {
+    /// Records a pointer motion.
+    pub fn motion(&mut self, x: f64, y: f64) {
+        self.position = [x as f32, y as f32];
+        if self.buttons != 0 {
+            self.down_position = self.position;
+        }
+    }
+
+    /// Records a button press.
+    ///
+    /// # Arguments
+    ///
+    /// * `button` - The GDK button number (1: primary, 2: middle, 3: secondary)
+    /// * `x`, `y` - The position of the press
+    pub fn press(&mut self, button: u32, x: f64, y: f64) {
+        self.buttons |= Self::button_mask(button);
+        self.position = [x as f32, y as f32];
+        self.click = self.position;
+        self.down_position = self.position;
+        self.clicked = true;
+    }
+
+    /// Records a button release.
+    pub fn release(&mut self, button: u32) {
+        self.buttons &= !Self::button_mask(button);
+    }
+
+    /// Records the offset of a drag from its start point.
+    pub fn drag(&mut self, dx: f64, dy: f64) {
+        self.drag_delta = [dx as f32, dy as f32];
+    }
+
+    /// Records the start or move of a touch point.
+    pub fn touch_update(&mut self, sequence: gdk::EventSequence, position: [f32; 2]) {
+        if let Some((_, p)) = self.touches.iter_mut().find(|(s, _)| *s == sequence) {
+            *p = position;
+        } else {
+            self.touches.push((sequence, position));
+        }
+    }
+
+    /// Records the end of a touch point.
+    pub fn touch_end(&mut self, sequence: &gdk::EventSequence) {
+        self.touches.retain(|(s, _)| s != sequence);
+    }
+
+    /// Converts the state to device pixels for a new frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - Size of the widget in logical pixels
+    /// * `scale` - Ratio between device pixels and logical pixels
+    pub fn frame(&mut self, size: [f32; 2], scale: f32) -> PointerInfo {
+        let to_device = |[x, y]: [f32; 2]| [x * scale, (size[1] - y) * scale];
+        let to_uv = |[x, y]: [f32; 2]| {
+            if size[0] > 0.0 && size[1] > 0.0 {
+                [x / size[0], 1.0 - y / size[1]]
+            } else {
+                [0.0; 2]
+            }
+        };
+
+        let down = to_device(self.down_position);
+        let click = to_device(self.click);
+        // Shadertoy's iMouse: zw is negated when the button is up, and w is
+        // only positive on the frame of the click
+        let mouse = [
+            down[0],
+            down[1],
+            if self.buttons & 1 != 0 {
+                click[0]
+            } else {
+                -click[0]
+            },
+            if self.clicked { click[1] } else { -click[1] },
+        ];
+        self.clicked = false;
+
+        PointerInfo {
+            position: to_device(self.position),
+            position_uv: to_uv(self.position),
+            buttons: self.buttons,
+            click,
+            drag_delta: [self.drag_delta[0] * scale, -self.drag_delta[1] * scale],
+            mouse,
+            touches: self
+                .touches
+                .iter()
+                .take(MAX_TOUCHES)
+                .map(|(_, p)| {
+                    let [x, y] = to_device(*p);
+                    [x, y, 1.0]
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the bit of a GDK button number in the `buttons` bitmask.
+    fn button_mask(button: u32) -> i32 {
+        match button {
+            gdk::BUTTON_PRIMARY => 1,
+            gdk::BUTTON_MIDDLE => 2,
+            gdk::BUTTON_SECONDARY => 4,
+            _ => 0,
+        }
+    }
+}
+
+/// Pointer values for one frame, in device pixels with the origin at the
+/// bottom-left.
+#[derive(Debug, Default)]
+pub struct PointerInfo {
+    /// Pointer position
+    pub position: [f32; 2],
+    /// Pointer position in uv space
+    pub position_uv: [f32; 2],
+    /// Pressed buttons bitmask
+    pub buttons: i32,
+    /// Position of the last press
+    pub click: [f32; 2],
+    /// Offset of the current (or last) drag
+    pub drag_delta: [f32; 2],
+    /// Shadertoy-style `iMouse` value
+    pub mouse: [f32; 4],
+    /// Active touch points as `(x, y, 1.0)`
+    pub touches: Vec<[f32; 3]>,
+}
+
+/// Width of the keyboard texture, one texel per key code.
+pub const KEYBOARD_WIDTH: usize = 256;
+
+/// Rows of the keyboard texture: down, pressed this frame, toggled.
+pub const KEYBOARD_ROWS: usize = 3;
+
+/// Keyboard state, laid out like Shadertoy's keyboard texture.
+///
+/// Keys are indexed by their JavaScript key code. Row 0 holds keys that are
+/// down, row 1 keys pressed since the last frame, and row 2 keys toggled by
+/// every press.
+#[derive(Debug)]
+pub struct KeyboardState {
+    /// Texel values, row by row
+    data: [u8; KEYBOARD_WIDTH * KEYBOARD_ROWS],
+    /// Whether the data changed since the last upload
+    dirty: bool,
+}
+
+impl Default for KeyboardState {
+    fn default() -> Self {
+        Self {
+            data: [0; KEYBOARD_WIDTH * KEYBOARD_ROWS],
+            dirty: true,
+        }
+    }
+}
+
+impl KeyboardState {
+    /// Records a key press. Auto-repeated presses are ignored.
+    pub fn press(&mut self, key: gdk::Key) {
+        let Some(code) = key_code(key) else {
+            return;
+        };
+        if self.data[code] != 0 {
+            return;
+        }
+
+        self.data[code] = 255;
+        self.data[KEYBOARD_WIDTH + code] = 255;
+        self.data[2 * KEYBOARD_WIDTH + code] ^= 255;
+        self.dirty = true;
+    }
+
+    /// Records a key release.
+    pub fn release(&mut self, key: gdk::Key) {
+        if let Some(code) = key_code(key) {
+            self.data[code] = 0;
+            self.dirty = true;
+        }
+    }
+
+    /// Releases every key, e.g. when the widget loses focus.
+    pub fn release_all(&mut self) {
+        self.data[..KEYBOARD_WIDTH].fill(0);
+        self.dirty = true;
+    }
+
+    /// Returns the texture data if it changed since the last call.
+    pub fn take_changes(&mut self) -> Option<&[u8]> {
+        std::mem::take(&mut self.dirty).then_some(&self.data[..])
+    }
+
+    /// Clears the "pressed this frame" row once a frame has been rendered.
+    pub fn end_frame(&mut self) {
+        let pressed = &mut self.data[KEYBOARD_WIDTH..2 * KEYBOARD_WIDTH];
+        if pressed.iter().any(|&v| v != 0) {
+            pressed.fill(0);
+            self.dirty = true;
+        }
+    }
+}
+
+/// Maps a GDK key to its JavaScript key code, as used by Shadertoy.
+fn key_code(key: gdk::Key) -> Option<usize> {
+    use gdk::Key;
+
+    let key = key.to_lower();
+    let code = match key {
+        Key::BackSpace => 8,
+        Key::Tab | Key::ISO_Left_Tab => 9,
+        Key::Return | Key::KP_Enter => 13,
+        Key::Shift_L | Key::Shift_R => 16,
+        Key::Control_L | Key::Control_R => 17,
+        Key::Alt_L | Key::Alt_R => 18,
+        Key::Pause => 19,
+        Key::Caps_Lock => 20,
+        Key::Escape => 27,
+        Key::space => 32,
+        Key::Page_Up => 33,
+        Key::Page_Down => 34,
+        Key::End => 35,
+        Key::Home => 36,
+        Key::Left => 37,
+        Key::Up => 38,
+        Key::Right => 39,
+        Key::Down => 40,
+        Key::Insert => 45,
+        Key::Delete => 46,
+        Key::semicolon => 186,
+        Key::equal => 187,
+        Key::comma => 188,
+        Key::minus => 189,
+        Key::period => 190,
+        Key::slash => 191,
+        Key::grave => 192,
+        Key::bracketleft => 219,
+        Key::backslash => 220,
+        Key::bracketright => 221,
+        Key::apostrophe => 222,
+        _ => {
+            let raw = key.into_glib();
+            return if (Key::F1.into_glib()..=Key::F12.into_glib()).contains(&raw) {
+                Some(112 + (raw - Key::F1.into_glib()) as usize)
+            } else if (Key::KP_0.into_glib()..=Key::KP_9.into_glib()).contains(&raw) {
+                Some(96 + (raw - Key::KP_0.into_glib()) as usize)
+            } else {
+                // Letters and digits use their uppercase ASCII codes
+                key.to_unicode()
+                    .filter(char::is_ascii_alphanumeric)
+                    .map(|c| c.to_ascii_uppercase() as usize)
+            };
+        }
+    };
+    Some(code)
+}
@@ -0,0 +1,780 @@
+//! Interactive Shader Format (ISF) compatibility.
+//!
+//! ISF shaders start with a `/*{ … }*/` JSON comment declaring their inputs
+//! and passes, and use `gl_FragColor`, `isf_FragNormCoord`, `IMG_PIXEL`… in
+//! the GLSL that follows. [`wrap`] declares all of these before the code,
+//! while `TIME`, `RENDERSIZE`… are filled as builtins. Every pass runs the
+//! same code, telling passes apart with `PASSINDEX`.
+
+use std::fmt::Write;
+
+use gtk::glib::{self, Variant, VariantDict, prelude::*};
+use log::warn;
+
+use super::{
+    Uniform,
+    json::Json,
+    pass::{Pass, PassFormat, PassSize},
+};
+
+/// The type of an ISF input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputType {
+    /// `float`
+    Float,
+    /// `long`, a choice among integer `VALUES`
+    Long,
+    /// `bool`
+    Bool,
+    /// `event`, a momentary `bool`
+    Event,
+    /// `color`, an RGBA `vec4`
+    Color,
+    /// `point2D`, a `vec2` in pixels
+    Point2D,
+    /// `image`, a `sampler2D`
+    Image,
+}
+
+impl InputType {
+    /// Parses the `TYPE` of an input.
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "float" => Self::Float,
+            "long" => Self::Long,
+            "bool" => Self::Bool,
+            "event" => Self::Event,
+            "color" => Self::Color,
+            "point2D" => Self::Point2D,
+            "image" => Self::Image,
+            _ => return None,
+        })
+    }
+
+    /// Returns the GLSL type of the uniform declared for the input.
+    fn glsl(self) -> &'static str {
+        match self {
+            Self::Float => "float",
+            Self::Long => "int",
+            Self::Bool | Self::Event => "bool",
+            Self::Color => "vec4",
+            Self::Point2D => "vec2",
+            Self::Image => "sampler2D",
+        }
+    }
+}
+
+/// Attributes of an input exposed as metadata, lowercased.
+const METADATA: [&str; 9] = [
+    "name", "type", "label", "default", "min", "max", "identity", "values", "labels",
+];
+
+/// An input declared in the `INPUTS` of an ISF header.
+#[derive(Debug, Clone)]
+pub struct Input {
+    /// Name of the uniform
+    pub name: String,
+    /// Type of the input
+    pub kind: InputType,
+    /// The input's JSON object, for the metadata (`LABEL`, `MIN`, `MAX`…)
+    json: Json,
+}
+
+impl Input {
+    /// Returns the value of the uniform from the input's `DEFAULT`, if it
+    /// has a valid one.
+    pub fn default_value(&self) -> Option<Uniform> {
+        let default = self.json.get("DEFAULT")?;
+        let numbers: Vec<f32> = default
+            .elements()
+            .iter()
+            .filter_map(Json::as_f64)
+            .map(|n| n as f32)
+            .collect();
+        match self.kind {
+            InputType::Float => default.as_f64().map(|n| Uniform::Float(n as f32)),
+            InputType::Long => default.as_f64().map(|n| Uniform::Int(n as i32)),
+            // `bool` uniforms are set like `int` ones
+            InputType::Bool | InputType::Event => match default {
+                Json::Bool(b) => Some(Uniform::Int(i32::from(*b))),
+                Json::Number(n) => Some(Uniform::Int(i32::from(*n != 0.0))),
+                _ => None,
+            },
+            InputType::Color => match numbers[..] {
+                [r, g, b, a] => Some(Uniform::Vec4([r, g, b, a])),
+                [r, g, b] => Some(Uniform::Vec4([r, g, b, 1.0])),
+                _ => None,
+            },
+            InputType::Point2D => match numbers[..] {
+                [x, y] => Some(Uniform::Vec2([x, y])),
+                _ => None,
+            },
+            InputType::Image => None,
+        }
+    }
+
+    /// Returns the metadata of the input as a `GVariant` of type `a{sv}`.
+    ///
+    /// The keys are the lowercase ISF attributes present on the input:
+    /// `name`, `type`, `label`, `default`, `min`, `max`, `identity`,
+    /// `values` and `labels`.
+    pub fn to_variant(&self) -> Variant {
+        let dict = VariantDict::new(None);
+        for (key, value) in match &self.json {
+            Json::Object(members) => members.as_slice(),
+            _ => &[],
+        } {
+            let key = key.to_lowercase();
+            if METADATA.contains(&key.as_str())
+                && let Some(value) = json_to_variant(value)
+            {
+                dict.insert_value(&key, &value);
+            }
+        }
+        dict.end()
+    }
+}
+
+/// A pass declared in the `PASSES` of an ISF header.
+#[derive(Debug, Clone)]
+pub struct IsfPass {
+    /// Name of the texture the pass renders into, `None` for the screen
+    pub target: Option<String>,
+    /// Whether the texture holds floating point values
+    pub float: bool,
+    /// Size of the texture
+    pub size: PassSize,
+}
+
+/// A parsed ISF header.
+#[derive(Debug, Clone, Default)]
+pub struct Header {
+    /// Declared inputs, in order
+    pub inputs: Vec<Input>,
+    /// Names of the images of `IMPORTED`, in order
+    pub imported: Vec<String>,
+    /// Declared passes, in order
+    pub passes: Vec<IsfPass>,
+}
+
+impl Header {
+    /// Parses the header of an ISF shader.
+    ///
+    /// Returns the header and the byte offset of the GLSL code after it.
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the problem if the shader doesn't start with
+    /// a valid JSON comment.
+    pub fn parse(source: &str) -> Result<(Self, usize), String> {
+        let start = source.len() - source.trim_start().len();
+        if !source[start..].starts_with("/*") {
+            return Err("missing JSON header comment".to_owned());
+        }
+        let json_start = start + 2;
+        let json_end = comment_end(&source[json_start..])
+            .map(|i| json_start + i)
+            .ok_or("unterminated header comment")?;
+        let json = Json::parse(&source[json_start..json_end]).map_err(|e| e.to_string())?;
+
+        let mut header = Self::default();
+        for input in json.get("INPUTS").map(Json::elements).unwrap_or_default() {
+            let Some(name) = input.get("NAME").and_then(Json::as_str) else {
+                return Err("input without a NAME".to_owned());
+            };
+            let kind = input.get("TYPE").and_then(Json::as_str).unwrap_or_default();
+            let Some(kind) = InputType::parse(kind) else {
+                warn!("Unsupported ISF input type for `{name}`: {kind}");
+                continue;
+            };
+            header.inputs.push(Input {
+                name: name.to_owned(),
+                kind,
+                json: input.clone(),
+            });
+        }
+
+        // Either an object keyed by name, or an array of objects with a NAME
+        header.imported = match json.get("IMPORTED") {
+            Some(Json::Object(members)) => members.iter().map(|(name, _)| name.clone()).collect(),
+            Some(imported) => imported
+                .elements()
+                .iter()
+                .filter_map(|image| image.get("NAME").and_then(Json::as_str))
+                .map(str::to_owned)
+                .collect(),
+            None => Vec::new(),
+        };
+
+        for pass in json.get("PASSES").map(Json::elements).unwrap_or_default() {
+            let target = pass.get("TARGET").and_then(Json::as_str).map(str::to_owned);
+            let float = matches!(pass.get("FLOAT"), Some(Json::Bool(true)))
+                || pass
+                    .get("FLOAT")
+                    .and_then(Json::as_f64)
+                    .is_some_and(|f| f != 0.0);
+            let size = pass_size(pass.get("WIDTH"), pass.get("HEIGHT")).unwrap_or_else(|| {
+                warn!(
+                    "Unsupported size for ISF pass `{}`, using the widget's size",
+                    target.as_deref().unwrap_or_default()
+                );
+                PassSize::Scale(1.0)
+            });
+            header.passes.push(IsfPass {
+                target,
+                float,
+                size,
+            });
+        }
+
+        Ok((header, json_end + 2))
+    }
+
+    /// Returns the `PASSINDEX` of the pass called `pass`, or of the last
+    /// pass, rendered to the screen, for `None`.
+    fn pass_index(&self, pass: Option<&str>) -> usize {
+        match pass {
+            Some(name) => self
+                .passes
+                .iter()
+                .position(|p| p.target.as_deref() == Some(name))
+                .unwrap_or_default(),
+            None => self.passes.len().saturating_sub(1),
+        }
+    }
+
+    /// Returns the render passes running every ISF pass but the last, which
+    /// is rendered by the widget's shader.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The whole ISF shader, run by every pass
+    pub fn render_passes(&self, source: &str) -> Vec<Pass> {
+        let Some((_, offscreen)) = self.passes.split_last() else {
+            return Vec::new();
+        };
+        offscreen
+            .iter()
+            .enumerate()
+            .filter_map(|(i, pass)| {
+                let Some(target) = &pass.target else {
+                    warn!("ISF pass {i} has no TARGET and is skipped");
+                    return None;
+                };
+                let mut render_pass = Pass::new(target.clone(), source);
+                render_pass.size = pass.size;
+                if pass.float {
+                    render_pass.format = PassFormat::Rgba32F;
+                }
+                Some(render_pass)
+            })
+            .collect()
+    }
+}
+
+/// Returns the offset of the `*/` closing the header comment, skipping those
+/// inside JSON strings, such as a `DESCRIPTION` mentioning comments.
+fn comment_end(text: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut in_string = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if in_string => i += 1,
+            b'"' => in_string = !in_string,
+            b'*' if !in_string && bytes.get(i + 1) == Some(&b'/') => return Some(i),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Wraps an ISF shader into a complete fragment shader.
+///
+/// Image inputs, then imported images, are bound to the textures `tex0`,
+/// `tex1`… in order, and left unbound past the last texture.
+///
+/// # Arguments
+///
+/// * `source` - The whole ISF shader, header included
+/// * `pass` - The name of the pass being compiled, or `None` for the
+///   widget's shader, which renders the last ISF pass
+/// * `texture_count` - The number of textures loaded by the widget
+///
+/// # Errors
+///
+/// Returns a description of the problem if the header is invalid.
+pub fn wrap(source: &str, pass: Option<&str>, texture_count: usize) -> Result<String, String> {
+    let (header, code_start) = Header::parse(source)?;
+
+    let mut shader = String::from(
+        "out vec4 isf_FragColor;\n\
+         #define gl_FragColor isf_FragColor\n\
+         #define texture2D texture\n\
+         in vec2 uv;\n\
+         vec2 isf_FragNormCoord;\n\
+         #define vv_FragNormCoord isf_FragNormCoord\n\
+         uniform float TIME;\n\
+         uniform float TIMEDELTA;\n\
+         uniform int FRAMEINDEX;\n\
+         uniform vec2 RENDERSIZE;\n\
+         uniform vec4 DATE;\n",
+    );
+    let _ = writeln!(shader, "const int PASSINDEX = {};", header.pass_index(pass));
+
+    let images = header
+        .inputs
+        .iter()
+        .filter(|input| input.kind == InputType::Image)
+        .map(|input| &input.name)
+        .chain(&header.imported);
+    for (i, name) in images.enumerate() {
+        if i < texture_count {
+            let _ = writeln!(shader, "uniform sampler2D tex{i};\n#define {name} tex{i}");
+        } else {
+            let _ = writeln!(shader, "uniform sampler2D {name};");
+        }
+    }
+    for input in header
+        .inputs
+        .iter()
+        .filter(|input| input.kind != InputType::Image)
+    {
+        let _ = writeln!(shader, "uniform {} {};", input.kind.glsl(), input.name);
+    }
+    for target in header.passes.iter().filter_map(|p| p.target.as_ref()) {
+        let _ = writeln!(shader, "uniform sampler2D {target};");
+    }
+
+    shader.push_str(
+        "#define IMG_SIZE(image) vec2(textureSize(image, 0))\n\
+         #define IMG_NORM_PIXEL(image, coord) texture(image, coord)\n\
+         #define IMG_PIXEL(image, coord) texture(image, (coord) / IMG_SIZE(image))\n\
+         #define IMG_THIS_NORM_PIXEL(image) texture(image, isf_FragNormCoord)\n\
+         #define IMG_THIS_PIXEL(image) texture(image, isf_FragNormCoord)\n\
+         #define main isf_main\n",
+    );
+
+    // Keep the line numbers of the ISF file, header included
+    let line = source[..code_start].matches('\n').count() + 1;
+    let _ = write!(
+        shader,
+        "#line {line}\n{}\n\n#undef main\n\
+         void main() {{\n\
+         \x20   isf_FragNormCoord = uv;\n\
+         \x20   isf_main();\n\
+         }}\n",
+        &source[code_start..]
+    );
+    Ok(shader)
+}
+
+/// Converts a `WIDTH`/`HEIGHT` pair of a pass into a size.
+///
+/// Both are numbers or expressions such as `"floor($WIDTH / 2.0)"`. Sizes
+/// relative to the render size become a [`PassSize::Scale`], taken from the
+/// width if the two scales differ.
+fn pass_size(width: Option<&Json>, height: Option<&Json>) -> Option<PassSize> {
+    let evaluate = |value: Option<&Json>, variable: &str| {
+        match value {
+            None => Some(Size::Relative(1.0)),
+            Some(Json::Number(n)) => Some(Size::Fixed(*n)),
+            Some(Json::String(expression)) => Size::evaluate(expression),
+            Some(_) => None,
+        }
+        .or_else(|| {
+            warn!("Invalid ISF pass {variable}: {value:?}");
+            None
+        })
+    };
+
+    match (evaluate(width, "WIDTH")?, evaluate(height, "HEIGHT")?) {
+        (Size::Fixed(w), Size::Fixed(h)) => {
+            Some(PassSize::Fixed(w.max(1.0) as u32, h.max(1.0) as u32))
+        }
+        (Size::Relative(scale), Size::Relative(other)) => {
+            if (scale - other).abs() > 1e-3 {
+                warn!("ISF passes with different width and height scales aren't supported");
+            }
+            Some(PassSize::Scale(scale as f32))
+        }
+        (Size::Relative(scale), Size::Fixed(_)) | (Size::Fixed(_), Size::Relative(scale)) => {
+            warn!("ISF passes with a fixed width or height aren't supported");
+            Some(PassSize::Scale(scale as f32))
+        }
+    }
+}
+
+/// A pass dimension, fixed or relative to the render size.
+#[derive(Debug, Clone, Copy)]
+enum Size {
+    Fixed(f64),
+    Relative(f64),
+}
+
+impl Size {
+    /// Render size at which relative expressions are evaluated, large enough
+    /// for `floor()` and friends not to skew the scale.
+    const PROBE: f64 = 4096.0;
+
+    /// Evaluates a size expression using `$WIDTH` and `$HEIGHT`.
+    fn evaluate(expression: &str) -> Option<Self> {
+        let mut parser = Expression {
+            input: expression.as_bytes(),
+            position: 0,
+            relative: false,
+        };
+        let value = parser.sum()?;
+        parser.skip_whitespace();
+        if parser.position != parser.input.len() {
+            return None;
+        }
+        Some(if parser.relative {
+            Self::Relative(value / Self::PROBE)
+        } else {
+            Self::Fixed(value)
+        })
+    }
+}
+
+/// Recursive descent evaluator for size expressions.
+struct Expression<'a> {
+    input: &'a [u8],
+    position: usize,
+    /// Whether `$WIDTH` or `$HEIGHT` was used
+    relative: bool,
+}
+
+impl Expression<'_> {
+    fn skip_whitespace(&mut self) {
+        while self
+            .input
+            .get(self.position)
+            .is_some_and(u8::is_ascii_whitespace)
+        {
+            self.position += 1;
+        }
+    }
+
+    /// Consumes `expected` after optional whitespace.
+    fn eat(&mut self, expected: u8) -> bool {
+        self.skip_whitespace();
+        let found = self.input.get(self.position) == Some(&expected);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn sum(&mut self) -> Option<f64> {
+        let mut value = self.product()?;
+        loop {
+            if self.eat(b'+') {
+                value += self.product()?;
+            } else if self.eat(b'-') {
+                value -= self.product()?;
+            } else {
+                return Some(value);
+            }
+        }
+    }
+
+    fn product(&mut self) -> Option<f64> {
+        let mut value = self.factor()?;
+        loop {
+            if self.eat(b'*') {
+                value *= self.factor()?;
+            } else if self.eat(b'/') {
+                value /= self.factor()?;
+            } else {
+                return Some(value);
+            }
+        }
+    }
+
+    fn factor(&mut self) -> Option<f64> {
+        if self.eat(b'-') {
+            return self.factor().map(|v| -v);
+        }
+        if self.eat(b'(') {
+            let value = self.sum()?;
+            return self.eat(b')').then_some(value);
+        }
+
+        let variable = self.eat(b'$');
+        let start = self.position;
+        while self
+            .input
+            .get(self.position)
+            .is_some_and(|c| c.is_ascii_alphanumeric() || matches!(c, b'_' | b'.'))
+        {
+            self.position += 1;
+        }
+        let word = std::str::from_utf8(&self.input[start..self.position]).ok()?;
+
+        if variable {
+            return match word {
+                "WIDTH" | "HEIGHT" => {
+                    self.relative = true;
+                    Some(Size::PROBE)
+                }
+                _ => None,
+            };
+        }
+        if let Ok(number) = word.parse() {
+            return Some(number);
+        }
+
+        if !self.eat(b'(') {
+            return None;
+        }
+        let mut arguments = vec![self.sum()?];
+        while self.eat(b',') {
+            arguments.push(self.sum()?);
+        }
+        if !self.eat(b')') {
+            return None;
+        }
+        match (word, arguments.as_slice()) {
+            ("floor", &[x]) => Some(x.floor()),
+            ("ceil", &[x]) => Some(x.ceil()),
+            ("round", &[x]) => Some(x.round()),
+            ("abs", &[x]) => Some(x.abs()),
+            ("sqrt", &[x]) => Some(x.sqrt()),
+            ("pow", &[x, y]) => Some(x.powf(y)),
+            ("min", &[x, y]) => Some(x.min(y)),
+            ("max", &[x, y]) => Some(x.max(y)),
+            _ => None,
+        }
+    }
+}
+
+/// Converts a JSON value into a `GVariant`: numbers become doubles, arrays
+/// of numbers or strings become `ad` or `as`, other arrays `av`, and objects
+/// `a{sv}`. `null` has no equivalent.
+fn json_to_variant(json: &Json) -> Option<Variant> {
+    Some(match json {
+        Json::Null => return None,
+        Json::Bool(b) => b.to_variant(),
+        Json::Number(n) => n.to_variant(),
+        Json::String(s) => s.to_variant(),
+        Json::Array(elements) => {
+            if let Some(numbers) = elements
+                .iter()
+                .map(Json::as_f64)
+                .collect::<Option<Vec<_>>>()
+            {
+                numbers.to_variant()
+            } else if let Some(strings) = elements
+                .iter()
+                .map(Json::as_str)
+                .collect::<Option<Vec<_>>>()
+            {
+                strings.to_variant()
+            } else {
+                Variant::array_from_iter::<Variant>(
+                    elements
+                        .iter()
+                        .filter_map(json_to_variant)
+                        .map(|v| Variant::from_variant(&v)),
+                )
+            }
+        }
+        Json::Object(members) => {
+            let dict = VariantDict::new(None);
+            for (key, value) in members {
+                if let Some(value) = json_to_variant(value) {
+                    dict.insert_value(key, &value);
+                }
+            }
+            dict.end()
+        }
+    })
+}
+
+/// Returns the metadata of every input of an ISF shader as a `GVariant` of
+/// type `aa{sv}`, empty if the header is invalid.
+pub fn inputs_variant(source: &str) -> Variant {
+    let inputs = Header::parse(source)
+        .map(|(header, _)| header.inputs)
+        .unwrap_or_default();
+    Variant::array_from_iter_with_type(
+        glib::VariantTy::VARDICT,
+        inputs.iter().map(Input::to_variant),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHADER: &str = r#"
+/*{
+    "DESCRIPTION": "Blurs in two passes */ with a /* comment",
+    "INPUTS": [
+        {"NAME": "amount", "TYPE": "float", "DEFAULT": 0.5, "MIN": 0, "MAX": 1},
+        {"NAME": "tint", "TYPE": "color", "DEFAULT": [1, 0.5, 0]},
+        {"NAME": "flash", "TYPE": "event"},
+        {"NAME": "input_image", "TYPE": "image"},
+        {"NAME": "level", "TYPE": "audioFFT"}
+    ],
+    "IMPORTED": {"noise": {"PATH": "noise.png"}},
+    "PASSES": [
+        {"TARGET": "blurred", "WIDTH": "floor($WIDTH / 2.0)", "HEIGHT": "$HEIGHT / 2", "FLOAT": true},
+        {"TARGET": "small", "WIDTH": 64, "HEIGHT": 32},
+        {}
+    ]
+}*/
+void main() {}
+"#;
+
+    #[test]
+    fn parses_header() {
+        let (header, code_start) = Header::parse(SHADER).unwrap();
+        assert_eq!(&SHADER[code_start..], "\nvoid main() {}\n");
+
+        // Unsupported input types are skipped
+        let inputs: Vec<(&str, InputType)> = header
+            .inputs
+            .iter()
+            .map(|input| (input.name.as_str(), input.kind))
+            .collect();
+        assert_eq!(
+            inputs,
+            [
+                ("amount", InputType::Float),
+                ("tint", InputType::Color),
+                ("flash", InputType::Event),
+                ("input_image", InputType::Image),
+            ]
+        );
+        assert_eq!(header.inputs[0].default_value(), Some(Uniform::Float(0.5)));
+        assert_eq!(
+            header.inputs[1].default_value(),
+            Some(Uniform::Vec4([1.0, 0.5, 0.0, 1.0]))
+        );
+        assert_eq!(header.inputs[2].default_value(), None);
+        assert_eq!(header.imported, ["noise"]);
+
+        let passes: Vec<(Option<&str>, bool, PassSize)> = header
+            .passes
+            .iter()
+            .map(|pass| (pass.target.as_deref(), pass.float, pass.size))
+            .collect();
+        assert_eq!(
+            passes,
+            [
+                (Some("blurred"), true, PassSize::Scale(0.5)),
+                (Some("small"), false, PassSize::Fixed(64, 32)),
+                (None, false, PassSize::Scale(1.0)),
+            ]
+        );
+        assert_eq!(header.pass_index(Some("small")), 1);
+        assert_eq!(header.pass_index(None), 2);
+    }
+
+    #[test]
+    fn builds_render_passes() {
+        let (header, _) = Header::parse(SHADER).unwrap();
+        let passes = header.render_passes(SHADER);
+        let names: Vec<&str> = passes.iter().map(|pass| pass.name.as_str()).collect();
+        assert_eq!(names, ["blurred", "small"]);
+        assert_eq!(passes[0].format, PassFormat::Rgba32F);
+        assert_eq!(passes[1].format, PassFormat::Rgba8);
+        assert!(passes.iter().all(|pass| pass.shader == SHADER));
+
+        let (header, _) = Header::parse("/*{}*/").unwrap();
+        assert!(header.render_passes("").is_empty());
+        assert_eq!(header.pass_index(None), 0);
+    }
+
+    #[test]
+    fn rejects_invalid_headers() {
+        let error = |source| Header::parse(source).unwrap_err();
+        assert_eq!(error("void main() {}"), "missing JSON header comment");
+        assert_eq!(error("// {}\n"), "missing JSON header comment");
+        assert_eq!(error("/*{\"INPUTS\": []}"), "unterminated header comment");
+        assert_eq!(error("/*{\"A\": \"*/\"}"), "unterminated header comment");
+        assert_eq!(
+            error(r#"/*{"INPUTS": [{"TYPE": "float"}]}*/"#),
+            "input without a NAME"
+        );
+        assert!(error("/*{\"INPUTS\": [}*/").starts_with("Unexpected character"));
+    }
+
+    #[test]
+    fn finds_comment_end_outside_strings() {
+        assert_eq!(comment_end("{}*/"), Some(2));
+        assert_eq!(comment_end(r#"{"a": "*/"}*/"#), Some(11));
+        assert_eq!(comment_end(r#"{"a": "\"*/"}*/"#), Some(13));
+        assert_eq!(comment_end(r#"{"a": "\\"}*/"#), Some(11));
+        assert_eq!(comment_end("{}*"), None);
+    }
+
+    #[test]
+    fn evaluates_size_expressions() {
+        let relative = |expression| match Size::evaluate(expression) {
+            Some(Size::Relative(scale)) => scale,
+            size => panic!("{expression}: {size:?}"),
+        };
+        let fixed = |expression| match Size::evaluate(expression) {
+            Some(Size::Fixed(size)) => size,
+            size => panic!("{expression}: {size:?}"),
+        };
+
+        assert_eq!(relative("$WIDTH"), 1.0);
+        assert_eq!(relative("floor($WIDTH / 4.0)"), 0.25);
+        assert_eq!(relative(" ( $HEIGHT + $HEIGHT ) * 0.5 "), 1.0);
+        assert_eq!(relative("max($WIDTH, 0) / -2"), -0.5);
+        assert_eq!(fixed("256"), 256.0);
+        assert_eq!(fixed("pow(2, 8) - 1"), 255.0);
+        assert_eq!(fixed("2 * 3 + 4"), 10.0);
+        assert_eq!(fixed("2 * (3 + 4)"), 14.0);
+        assert_eq!(fixed("--8"), 8.0);
+
+        for expression in [
+            "",
+            "$DEPTH",
+            "WIDTH",
+            "1 +",
+            "(1",
+            "1)",
+            "floor(1, 2)",
+            "cos(0)",
+            "2 3",
+            "$",
+        ] {
+            assert!(Size::evaluate(expression).is_none(), "{expression}");
+        }
+    }
+
+    #[test]
+    fn converts_pass_sizes() {
+        let string = |s: &str| Json::String(s.to_owned());
+        assert_eq!(pass_size(None, None), Some(PassSize::Scale(1.0)));
+        assert_eq!(
+            pass_size(Some(&Json::Number(0.0)), Some(&Json::Number(16.0))),
+            Some(PassSize::Fixed(1, 16))
+        );
+        assert_eq!(
+            pass_size(Some(&string("$WIDTH / 2")), Some(&Json::Number(16.0))),
+            Some(PassSize::Scale(0.5))
+        );
+        assert_eq!(pass_size(Some(&string("$WIDTH +")), None), None);
+        assert_eq!(pass_size(None, Some(&Json::Bool(true))), None);
+    }
+
+    #[test]
+    fn wraps_shader() {
+        let shader = wrap(SHADER, Some("small"), 1).unwrap();
+        assert!(shader.contains("const int PASSINDEX = 1;\n"));
+        assert!(shader.contains("uniform sampler2D tex0;\n#define input_image tex0\n"));
+        assert!(shader.contains("uniform sampler2D noise;\n"));
+        assert!(shader.contains("uniform bool flash;\n"));
+        assert!(shader.contains("uniform sampler2D blurred;\n"));
+        // The code keeps its line numbers
+        assert!(shader.contains("#line 17\n\nvoid main() {}\n"));
+        assert!(wrap("void main() {}", None, 0).is_err());
+    }
+}
@@ -0,0 +1,408 @@
+//! Minimal JSON parser for the shader description formats.
+//!
+//! Shadertoy exports, ISF headers and glTF files are small and only read
+//! once, so a tree of [`Json`] values is all that is needed.
+
+use std::{fmt, iter::Peekable, str::CharIndices};
+
+/// Deepest nesting of arrays and objects accepted, so that the recursion
+/// can't overflow the stack.
+const MAX_DEPTH: usize = 128;
+
+/// A parsed JSON value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    /// `null`
+    Null,
+    /// `true` or `false`
+    Bool(bool),
+    /// Any number
+    Number(f64),
+    /// A string
+    String(String),
+    /// An array
+    Array(Vec<Json>),
+    /// An object, with its members in document order
+    Object(Vec<(String, Json)>),
+}
+
+/// An error encountered while parsing JSON.
+#[derive(Debug, Clone)]
+pub struct JsonError {
+    /// Byte offset of the error in the input
+    pub offset: usize,
+    /// What went wrong
+    pub message: &'static str,
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.message, self.offset)
+    }
+}
+
+impl Json {
+    /// Parses a JSON document.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`JsonError`] if the input isn't valid JSON.
+    pub fn parse(input: &str) -> Result<Self, JsonError> {
+        let mut parser = Parser {
+            input,
+            chars: input.char_indices().peekable(),
+            depth: 0,
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        match parser.chars.peek() {
+            None => Ok(value),
+            Some(&(offset, _)) => Err(JsonError {
+                offset,
+                message: "Trailing characters",
+            }),
+        }
+    }
+
+    /// Returns the member `key` of an object.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Self::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a number.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements of an array, or an empty slice for any other
+    /// value.
+    pub fn elements(&self) -> &[Json] {
+        match self {
+            Self::Array(elements) => elements,
+            _ => &[],
+        }
+    }
+}
+
+/// Recursive descent parser state.
+struct Parser<'a> {
+    /// The whole input, for slicing numbers
+    input: &'a str,
+    /// Remaining characters with their byte offsets
+    chars: Peekable<CharIndices<'a>>,
+    /// Number of arrays and objects being parsed
+    depth: usize,
+}
+
+impl Parser<'_> {
+    /// Returns an error at the current position.
+    fn error<T>(&mut self, message: &'static str) -> Result<T, JsonError> {
+        let offset = self.chars.peek().map_or(self.input.len(), |&(i, _)| i);
+        Err(JsonError { offset, message })
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .chars
+            .next_if(|(_, c)| matches!(c, ' ' | '\t' | '\n' | '\r'))
+            .is_some()
+        {}
+    }
+
+    /// Consumes `expected` after optional whitespace.
+    fn expect(&mut self, expected: char) -> Result<(), JsonError> {
+        self.skip_whitespace();
+        if self.chars.next_if(|&(_, c)| c == expected).is_some() {
+            Ok(())
+        } else {
+            self.error("Unexpected character")
+        }
+    }
+
+    /// Consumes a keyword such as `true`.
+    fn keyword(&mut self, keyword: &str, value: Json) -> Result<Json, JsonError> {
+        for expected in keyword.chars() {
+            if self.chars.next_if(|&(_, c)| c == expected).is_none() {
+                return self.error("Invalid literal");
+            }
+        }
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Json, JsonError> {
+        self.skip_whitespace();
+        match self.chars.peek().map(|&(_, c)| c) {
+            Some('{' | '[') if self.depth >= MAX_DEPTH => self.error("Nesting too deep"),
+            Some('{') => self.nested(Self::object),
+            Some('[') => self.nested(Self::array),
+            Some('"') => self.string().map(Json::String),
+            Some('t') => self.keyword("true", Json::Bool(true)),
+            Some('f') => self.keyword("false", Json::Bool(false)),
+            Some('n') => self.keyword("null", Json::Null),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some(_) => self.error("Unexpected character"),
+            None => self.error("Unexpected end of input"),
+        }
+    }
+
+    /// Parses an array or object one level deeper.
+    fn nested(
+        &mut self,
+        parse: fn(&mut Self) -> Result<Json, JsonError>,
+    ) -> Result<Json, JsonError> {
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn object(&mut self) -> Result<Json, JsonError> {
+        self.expect('{')?;
+        let mut members = Vec::new();
+        self.skip_whitespace();
+        if self.chars.next_if(|&(_, c)| c == '}').is_some() {
+            return Ok(Json::Object(members));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.expect(':')?;
+            members.push((key, self.value()?));
+
+            self.skip_whitespace();
+            match self.chars.next_if(|&(_, c)| matches!(c, ',' | '}')) {
+                Some((_, ',')) => {}
+                Some(_) => return Ok(Json::Object(members)),
+                None => return self.error("Expected `,` or `}`"),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, JsonError> {
+        self.expect('[')?;
+        let mut elements = Vec::new();
+        self.skip_whitespace();
+        if self.chars.next_if(|&(_, c)| c == ']').is_some() {
+            return Ok(Json::Array(elements));
+        }
+
+        loop {
+            elements.push(self.value()?);
+
+            self.skip_whitespace();
+            match self.chars.next_if(|&(_, c)| matches!(c, ',' | ']')) {
+                Some((_, ',')) => {}
+                Some(_) => return Ok(Json::Array(elements)),
+                None => return self.error("Expected `,` or `]`"),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        if self.chars.next_if(|&(_, c)| c == '"').is_none() {
+            return self.error("Expected a string");
+        }
+
+        let mut s = String::new();
+        loop {
+            match self.chars.next() {
+                Some((_, '"')) => return Ok(s),
+                Some((_, '\\')) => {
+                    let c = match self.chars.next() {
+                        Some((_, '"')) => '"',
+                        Some((_, '\\')) => '\\',
+                        Some((_, '/')) => '/',
+                        Some((_, 'b')) => '\u{8}',
+                        Some((_, 'f')) => '\u{c}',
+                        Some((_, 'n')) => '\n',
+                        Some((_, 'r')) => '\r',
+                        Some((_, 't')) => '\t',
+                        Some((_, 'u')) => self.unicode_escape()?,
+                        _ => return self.error("Invalid escape sequence"),
+                    };
+                    s.push(c);
+                }
+                Some((offset, c)) if c < ' ' => {
+                    return Err(JsonError {
+                        offset,
+                        message: "Control character in string",
+                    });
+                }
+                Some((_, c)) => s.push(c),
+                None => return self.error("Unterminated string"),
+            }
+        }
+    }
+
+    /// Parses the digits of a `\u` escape, combining surrogate pairs.
+    fn unicode_escape(&mut self) -> Result<char, JsonError> {
+        let high = self.hex4()?;
+        let code = if (0xd800..0xdc00).contains(&high) {
+            if self.chars.next_if(|&(_, c)| c == '\\').is_none()
+                || self.chars.next_if(|&(_, c)| c == 'u').is_none()
+            {
+                return self.error("Unpaired surrogate");
+            }
+            let low = self.hex4()?;
+            if !(0xdc00..0xe000).contains(&low) {
+                return self.error("Unpaired surrogate");
+            }
+            0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+        } else {
+            high
+        };
+        char::from_u32(code).map_or_else(|| self.error("Invalid code point"), Ok)
+    }
+
+    fn hex4(&mut self) -> Result<u32, JsonError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let Some(digit) = self.chars.next().and_then(|(_, c)| c.to_digit(16)) else {
+                return self.error("Invalid unicode escape");
+            };
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+
+    /// Parses a number, following the JSON grammar: no leading zeros,
+    /// plus sign, or missing digits around the decimal point.
+    fn number(&mut self) -> Result<Json, JsonError> {
+        let start = self.chars.peek().map_or(self.input.len(), |&(i, _)| i);
+        let invalid = Err(JsonError {
+            offset: start,
+            message: "Invalid number",
+        });
+
+        self.chars.next_if(|&(_, c)| c == '-');
+        if self.chars.next_if(|&(_, c)| c == '0').is_some() {
+            if self.chars.next_if(|(_, c)| c.is_ascii_digit()).is_some() {
+                return invalid;
+            }
+        } else if self.digits() == 0 {
+            return invalid;
+        }
+        if self.chars.next_if(|&(_, c)| c == '.').is_some() && self.digits() == 0 {
+            return invalid;
+        }
+        if self
+            .chars
+            .next_if(|&(_, c)| matches!(c, 'e' | 'E'))
+            .is_some()
+        {
+            self.chars.next_if(|&(_, c)| matches!(c, '-' | '+'));
+            if self.digits() == 0 {
+                return invalid;
+            }
+        }
+        let end = self.chars.peek().map_or(self.input.len(), |&(i, _)| i);
+
+        match self.input[start..end].parse() {
+            Ok(n) => Ok(Json::Number(n)),
+            Err(_) => invalid,
+        }
+    }
+
+    /// Consumes decimal digits, returning how many there were.
+    fn digits(&mut self) -> usize {
+        let mut count = 0;
+        while self.chars.next_if(|(_, c)| c.is_ascii_digit()).is_some() {
+            count += 1;
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(input: &str) -> &'static str {
+        Json::parse(input).unwrap_err().message
+    }
+
+    #[test]
+    fn parses_values() {
+        let json = Json::parse(r#" {"a": [1, -2.5e3, true, null], "b": {"c": "d"}} "#).unwrap();
+        assert_eq!(
+            json.get("a").unwrap().elements(),
+            [
+                Json::Number(1.0),
+                Json::Number(-2500.0),
+                Json::Bool(true),
+                Json::Null
+            ]
+        );
+        assert_eq!(json.get("b").and_then(|b| b.get("c")?.as_str()), Some("d"));
+        assert_eq!(json.get("e"), None);
+        assert_eq!(Json::parse("[]").unwrap(), Json::Array(Vec::new()));
+        assert_eq!(Json::parse("{}").unwrap(), Json::Object(Vec::new()));
+    }
+
+    #[test]
+    fn keeps_member_order() {
+        let json = Json::parse(r#"{"z": 1, "a": 2}"#).unwrap();
+        let Json::Object(members) = json else {
+            panic!("not an object");
+        };
+        let keys: Vec<&str> = members.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, ["z", "a"]);
+    }
+
+    #[test]
+    fn parses_escapes() {
+        let json = Json::parse(r#""\"\\\/\b\f\n\r\té😀""#).unwrap();
+        assert_eq!(json.as_str(), Some("\"\\/\u{8}\u{c}\n\r\té😀"));
+        assert_eq!(error(r#""\ud83d""#), "Unpaired surrogate");
+        assert_eq!(error(r#""\ud83dA""#), "Unpaired surrogate");
+        assert_eq!(error(r#""\u12""#), "Invalid unicode escape");
+        assert_eq!(error(r#""\x""#), "Invalid escape sequence");
+        assert_eq!(error("\"a\nb\""), "Control character in string");
+        assert_eq!(error("\"abc"), "Unterminated string");
+    }
+
+    #[test]
+    fn follows_number_grammar() {
+        for (input, value) in [("0", 0.0), ("-0.5", -0.5), ("1E+2", 100.0), ("10e-1", 1.0)] {
+            assert_eq!(Json::parse(input).unwrap(), Json::Number(value), "{input}");
+        }
+        for input in ["01", "-", "1.", ".5", "+1", "1e", "-01", "0x10"] {
+            assert!(Json::parse(input).is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_documents() {
+        assert_eq!(error(""), "Unexpected end of input");
+        assert_eq!(error("[1 2]"), "Expected `,` or `]`");
+        assert_eq!(error(r#"{"a" 1}"#), "Unexpected character");
+        assert_eq!(error(r#"{"a": 1,}"#), "Expected a string");
+        assert_eq!(error("[1,]"), "Unexpected character");
+        assert_eq!(error("tru"), "Invalid literal");
+        assert_eq!(error("1 2"), "Trailing characters");
+        assert_eq!(Json::parse("[1 2]").unwrap_err().offset, 3);
+    }
+
+    #[test]
+    fn limits_nesting() {
+        let nested = |depth| "[".repeat(depth) + &"]".repeat(depth);
+        assert!(Json::parse(&nested(MAX_DEPTH)).is_ok());
+        assert_eq!(error(&nested(MAX_DEPTH + 1)), "Nesting too deep");
+        assert_eq!(error(&"{\"a\":".repeat(100_000)), "Nesting too deep");
+    }
+}
@@ -0,0 +1,138 @@
+//! Legacy GLSL (ES 1.00, GLSL 1.10/1.20) compatibility.
+//!
+//! Snippets from The Book of Shaders and glslCanvas write `gl_FragColor`,
+//! read `varying` inputs and call `texture2D`, none of which exist in the
+//! GLSL 3.30 / ES 3.00 the widget compiles. [`wrap`] maps them onto their
+//! modern equivalents with macros, so the code compiles unchanged, while
+//! glslCanvas' `u_time`, `u_resolution`, `u_mouse`… are filled as builtins.
+
+use std::{collections::BTreeSet, fmt::Write};
+
+use super::imp::append_user_source;
+
+/// Returns whether a fragment shader is written in legacy GLSL: it has a
+/// `#version` below 130 (or `100` for ES), or uses `gl_FragColor`,
+/// `gl_FragData`, `varying` or `texture2D`.
+pub fn is_legacy(source: &str) -> bool {
+    let version = source
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("#version"))
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|version| version.parse::<u32>().ok());
+    if let Some(version) = version {
+        return version < 130;
+    }
+
+    identifiers(source).any(|word| {
+        matches!(
+            word,
+            "gl_FragColor" | "gl_FragData" | "varying" | "texture2D"
+        )
+    })
+}
+
+/// Wraps a legacy fragment shader into one that compiles as modern GLSL.
+///
+/// The `#version` directive of the source, if any, is blanked out, as the
+/// widget prepends its own.
+pub fn wrap(source: &str) -> String {
+    let words: BTreeSet<&str> = identifiers(source).collect();
+
+    // A single output, which is `gl_FragData[0]` for sources using it
+    let mut shader = String::from(if words.contains("gl_FragData") {
+        "out vec4 legacy_FragData[1];\n#define gl_FragData legacy_FragData\n"
+    } else {
+        "out vec4 legacy_FragColor;\n#define gl_FragColor legacy_FragColor\n"
+    });
+    // Legacy sources may name a sampler `texture`, which hides the modern
+    // function: call it from wrappers declared before, and rename the sampler
+    let declares_texture = identifiers(source)
+        .zip(identifiers(source).skip(1))
+        .any(|pair| matches!(pair, ("sampler2D" | "samplerCube", "texture")));
+    if declares_texture {
+        shader.push_str(
+            "vec4 legacy_texture2D(sampler2D s, vec2 p) { return texture(s, p); }\n\
+             vec4 legacy_texture2D(sampler2D s, vec2 p, float bias) { return texture(s, p, bias); }\n\
+             vec4 legacy_textureCube(samplerCube s, vec3 p) { return texture(s, p); }\n\
+             vec4 legacy_textureCube(samplerCube s, vec3 p, float bias) { return texture(s, p, bias); }\n\
+             #define texture2D legacy_texture2D\n\
+             #define textureCube legacy_textureCube\n\
+             #define texture legacy_texture\n",
+        );
+    } else {
+        shader.push_str("#define texture2D texture\n#define textureCube texture\n");
+    }
+    shader.push_str(
+        "#define varying in\n\
+         #define texture2DProj textureProj\n\
+         #define texture2DLod textureLod\n\
+         #define texture2DProjLod textureProjLod\n\
+         #define textureCubeLod textureLod\n\
+         #define v_texcoord uv\n",
+    );
+
+    // glslCanvas textures: `u_tex0` and `u_tex0Resolution`
+    let is_index = |index: &str| !index.is_empty() && index.bytes().all(|c| c.is_ascii_digit());
+    for word in &words {
+        let Some(index) = word.strip_prefix("u_tex") else {
+            continue;
+        };
+        if is_index(index) {
+            let _ = writeln!(shader, "#define {word} tex{index}");
+        } else if let Some(index) = index.strip_suffix("Resolution")
+            && is_index(index)
+        {
+            let _ = writeln!(shader, "#define {word} tex{index}_size");
+        }
+    }
+
+    // Blank out the `#version` directive, keeping the line count so that
+    // errors point into the user's code
+    let source: Vec<&str> = source
+        .lines()
+        .map(|line| {
+            if line.trim_start().starts_with("#version") {
+                ""
+            } else {
+                line
+            }
+        })
+        .collect();
+    append_user_source(&mut shader, &source.join("\n"));
+    shader.push('\n');
+    shader
+}
+
+/// Iterates over the identifiers and keywords of a GLSL source, skipping
+/// comments.
+fn identifiers(source: &str) -> impl Iterator<Item = &str> {
+    let bytes = source.as_bytes();
+    let mut i = 0;
+    std::iter::from_fn(move || {
+        while i < bytes.len() {
+            let rest = &bytes[i..];
+            if rest.starts_with(b"//") {
+                i += rest.iter().position(|&c| c == b'\n').unwrap_or(rest.len());
+            } else if rest.starts_with(b"/*") {
+                i += rest
+                    .windows(2)
+                    .position(|w| w == b"*/")
+                    .map_or(rest.len(), |end| end + 2);
+            } else if rest[0].is_ascii_alphabetic() || rest[0] == b'_' {
+                let start = i;
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                    i += 1;
+                }
+                return Some(&source[start..i]);
+            } else if rest[0].is_ascii_digit() {
+                // Skip numbers such as `1e5` as a whole
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'.') {
+                    i += 1;
+                }
+            } else {
+                i += 1;
+            }
+        }
+        None
+    })
+}
@@ -0,0 +1,1041 @@
+//! Triangle meshes loaded from OBJ and glTF files.
+//!
+//! A mesh replaces the fullscreen quad: the widget's shader is drawn over
+//! its triangles with depth testing, using the depth buffer of the GLArea.
+//! Vertices have three attributes, read by [`VERTEX_SHADER`] unless a custom
+//! vertex shader is set:
+//!
+//! | Location | GLSL Type | Value                      |
+//! | -------- | --------- | -------------------------- |
+//! | 0        | `vec3`    | `position`, in model space |
+//! | 1        | `vec3`    | `normal`, in model space   |
+//! | 2        | `vec2`    | `texcoord`                 |
+//!
+//! Meshes are centered at the origin and scaled to fit the cube from -1 to 1
+//! by `model_matrix`. The camera builtins (`camera_matrix`,
+//! `projection_matrix` and `camera_position`) look at them from an
+//! [`OrbitCamera`](super::camera::OrbitCamera).
+//!
+//! OBJ files are read for their positions, texture coordinates, normals and
+//! polygonal faces. glTF files, either `.gltf` with embedded or external
+//! buffers or binary `.glb`, are read for the triangles of every mesh in the
+//! default scene, with node transforms applied. Materials are ignored, and
+//! missing normals are computed from the faces.
+
+use std::{collections::HashMap, path::Path};
+
+use gtk::{gio, gio::prelude::*, glib};
+
+use super::{ShaderError, json::Json};
+
+/// Vertex shader used with a mesh when no custom vertex shader is set.
+///
+/// The fragment shader can read `uv` (the texture coordinates),
+/// `world_position` and `world_normal`.
+pub const VERTEX_SHADER: &str = "\
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec3 normal;
+layout(location = 2) in vec2 texcoord;
+uniform mat4 model_matrix;
+uniform mat4 camera_matrix;
+uniform mat4 projection_matrix;
+out vec2 uv;
+out vec3 world_position;
+out vec3 world_normal;
+void main() {
+    vec4 world = model_matrix * vec4(position, 1.0);
+    world_position = world.xyz;
+    world_normal = transpose(inverse(mat3(model_matrix))) * normal;
+    uv = texcoord;
+    gl_Position = projection_matrix * camera_matrix * world;
+}
+";
+
+/// Floats per vertex: position, normal and texture coordinates.
+const VERTEX_SIZE: usize = 8;
+
+/// Nodes deeper than this are rejected, guarding the stack against long
+/// chains of children.
+const MAX_NODE_DEPTH: usize = 64;
+
+/// Most elements of an accessor without a buffer view, which is zeroed
+/// rather than read.
+const MAX_ZEROED_ELEMENTS: usize = 1 << 20;
+
+/// A column-major 4×4 matrix.
+type Matrix = [f64; 16];
+
+const IDENTITY: Matrix = [
+    1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0,
+];
+
+/// An indexed triangle mesh.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mesh {
+    /// Interleaved positions, normals and texture coordinates
+    pub vertices: Vec<[f32; VERTEX_SIZE]>,
+    /// Vertex indices, three per triangle
+    pub indices: Vec<u32>,
+    /// Column-major matrix centering the mesh and scaling it to fit the cube
+    /// from -1 to 1
+    pub model_matrix: [f32; 16],
+}
+
+impl Mesh {
+    /// Loads a mesh from an OBJ, glTF or GLB file, picked by extension.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GIOError` if the file (or an external glTF buffer) can't
+    /// be read, or a [`ShaderError::Parse`] if it is malformed or has no
+    /// triangles.
+    pub fn load(path: &Path) -> Result<Self, glib::Error> {
+        let (contents, _) = gio::File::for_path(path).load_contents(gio::Cancellable::NONE)?;
+        let directory = path.parent().unwrap_or(Path::new("."));
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let text = || {
+            std::str::from_utf8(&contents).map_err(|e| {
+                glib::Error::new(
+                    gio::IOErrorEnum::InvalidData,
+                    &format!("{} is not valid UTF-8: {e}", path.display()),
+                )
+            })
+        };
+        let mesh = match extension.as_str() {
+            "obj" => parse_obj(text()?),
+            "gltf" => parse_gltf(text()?, None, directory)?,
+            "glb" => parse_glb(&contents, directory)?,
+            _ => Err(format!(
+                "Unsupported mesh format `{extension}`, expected obj, gltf or glb"
+            )),
+        };
+        mesh.map_err(|e| glib::Error::new(ShaderError::Parse, &format!("{}: {e}", path.display())))
+    }
+}
+
+/// Collects vertices and triangles, then finishes them into a [`Mesh`].
+#[derive(Default)]
+struct Builder {
+    vertices: Vec<[f32; VERTEX_SIZE]>,
+    indices: Vec<u32>,
+    /// Whether each vertex lacks a normal, to compute it from the faces
+    missing_normals: Vec<bool>,
+}
+
+impl Builder {
+    fn push_vertex(&mut self, position: [f32; 3], normal: Option<[f32; 3]>, uv: [f32; 2]) -> u32 {
+        let [nx, ny, nz] = normal.unwrap_or_default();
+        self.vertices.push([
+            position[0],
+            position[1],
+            position[2],
+            nx,
+            ny,
+            nz,
+            uv[0],
+            uv[1],
+        ]);
+        self.missing_normals.push(normal.is_none());
+        (self.vertices.len() - 1) as u32
+    }
+
+    fn finish(mut self) -> Result<Mesh, String> {
+        if self.indices.is_empty() {
+            return Err("No triangles".to_owned());
+        }
+
+        // Smooth normals, weighted by the area of the faces
+        if self.missing_normals.contains(&true) {
+            for triangle in self.indices.chunks_exact(3) {
+                let [a, b, c] = [0, 1, 2].map(|i| {
+                    let v = self.vertices[triangle[i] as usize];
+                    [v[0], v[1], v[2]]
+                });
+                let normal = cross(sub(b, a), sub(c, a));
+                for &index in triangle {
+                    if self.missing_normals[index as usize] {
+                        let vertex = &mut self.vertices[index as usize];
+                        for axis in 0..3 {
+                            vertex[3 + axis] += normal[axis];
+                        }
+                    }
+                }
+            }
+            for (vertex, _) in self
+                .vertices
+                .iter_mut()
+                .zip(&self.missing_normals)
+                .filter(|(_, missing)| **missing)
+            {
+                let [x, y, z] = [vertex[3], vertex[4], vertex[5]];
+                let length = (x * x + y * y + z * z).sqrt();
+                if length > 0.0 {
+                    vertex[3] /= length;
+                    vertex[4] /= length;
+                    vertex[5] /= length;
+                }
+            }
+        }
+
+        let mut min = [f32::INFINITY; 3];
+        let mut max = [f32::NEG_INFINITY; 3];
+        for vertex in &self.vertices {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(vertex[axis]);
+                max[axis] = max[axis].max(vertex[axis]);
+            }
+        }
+        let extent = (0..3).map(|axis| max[axis] - min[axis]).fold(0.0, f32::max);
+        let scale = if extent > 0.0 { 2.0 / extent } else { 1.0 };
+        let center = [0, 1, 2].map(|axis| (min[axis] + max[axis]) / 2.0);
+        #[rustfmt::skip]
+        let model_matrix = [
+            scale, 0.0, 0.0, 0.0,
+            0.0, scale, 0.0, 0.0,
+            0.0, 0.0, scale, 0.0,
+            -center[0] * scale, -center[1] * scale, -center[2] * scale, 1.0,
+        ];
+
+        Ok(Mesh {
+            vertices: self.vertices,
+            indices: self.indices,
+            model_matrix,
+        })
+    }
+}
+
+/// Parses the source of an OBJ file.
+fn parse_obj(source: &str) -> Result<Mesh, String> {
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut texcoords: Vec<[f32; 2]> = Vec::new();
+    let mut normals: Vec<[f32; 3]> = Vec::new();
+    let mut builder = Builder::default();
+    // Vertices already pushed, by their position, texcoord and normal indices
+    let mut corners: HashMap<(usize, Option<usize>, Option<usize>), u32> = HashMap::new();
+
+    for (number, line) in source.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut words = line.split_whitespace();
+        let error = |what: &str| format!("{what} on line {}", number + 1);
+        match words.next() {
+            Some("v") => {
+                positions.push(parse_floats(words).ok_or_else(|| error("Invalid vertex"))?)
+            }
+            Some("vt") => {
+                // The second coordinate is optional
+                let values: Vec<f32> = words
+                    .map(str::parse)
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| error("Invalid texture coordinate"))?;
+                let Some(&u) = values.first() else {
+                    return Err(error("Invalid texture coordinate"));
+                };
+                texcoords.push([u, values.get(1).copied().unwrap_or_default()]);
+            }
+            Some("vn") => normals.push(parse_floats(words).ok_or_else(|| error("Invalid normal"))?),
+            Some("f") => {
+                let mut face = Vec::new();
+                for word in words {
+                    let mut refs = word.split('/');
+                    let mut index = |len: usize| -> Result<Option<usize>, String> {
+                        match refs.next() {
+                            None | Some("") => Ok(None),
+                            Some(r) => r
+                                .parse::<i64>()
+                                .ok()
+                                .and_then(|i| resolve_index(i, len))
+                                .map(Some)
+                                .ok_or_else(|| error("Invalid face index")),
+                        }
+                    };
+                    let key = (
+                        index(positions.len())?.ok_or_else(|| error("Face without position"))?,
+                        index(texcoords.len())?,
+                        index(normals.len())?,
+                    );
+                    let vertex = match corners.get(&key) {
+                        Some(&vertex) => vertex,
+                        None => {
+                            let vertex = builder.push_vertex(
+                                positions[key.0],
+                                key.2.map(|n| normals[n]),
+                                key.1.map(|t| texcoords[t]).unwrap_or_default(),
+                            );
+                            corners.insert(key, vertex);
+                            vertex
+                        }
+                    };
+                    face.push(vertex);
+                }
+                if face.len() < 3 {
+                    return Err(error("Face with fewer than 3 vertices"));
+                }
+                // Polygons are assumed convex and split into a fan
+                for i in 1..face.len() - 1 {
+                    builder.indices.extend([face[0], face[i], face[i + 1]]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    builder.finish()
+}
+
+/// Resolves a 1-based OBJ index, negative indices counting from the end.
+fn resolve_index(index: i64, len: usize) -> Option<usize> {
+    let resolved = match index {
+        0 => return None,
+        i if i > 0 => i - 1,
+        i => len as i64 + i,
+    };
+    usize::try_from(resolved).ok().filter(|&i| i < len)
+}
+
+/// Parses the first `N` numbers of an OBJ statement.
+fn parse_floats<'a, const N: usize>(mut words: impl Iterator<Item = &'a str>) -> Option<[f32; N]> {
+    let mut values = [0.0; N];
+    for value in &mut values {
+        *value = words.next()?.parse().ok()?;
+    }
+    Some(values)
+}
+
+/// Splits a binary glTF file into its JSON and binary chunks, and parses it.
+fn parse_glb(contents: &[u8], directory: &Path) -> Result<Result<Mesh, String>, glib::Error> {
+    let read_u32 = |at: usize| {
+        contents
+            .get(at..at + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+    };
+    if contents.get(..4) != Some(b"glTF") || read_u32(4) != Some(2) {
+        return Ok(Err("Not a glTF 2.0 binary file".to_owned()));
+    }
+
+    let mut json = None;
+    let mut binary = None;
+    let mut offset = 12;
+    while let (Some(length), Some(kind)) = (read_u32(offset), read_u32(offset + 4)) {
+        let Some(chunk) = contents.get(offset + 8..offset + 8 + length) else {
+            return Ok(Err("Truncated chunk".to_owned()));
+        };
+        match kind {
+            // "JSON" and "BIN\0" in little-endian
+            0x4E4F_534A => json = Some(chunk),
+            0x004E_4942 => binary = Some(chunk),
+            _ => {}
+        }
+        offset += 8 + length;
+    }
+
+    let Some(json) = json.and_then(|json| std::str::from_utf8(json).ok()) else {
+        return Ok(Err("Missing JSON chunk".to_owned()));
+    };
+    parse_gltf(json, binary, directory)
+}
+
+/// Parses a glTF document, with the binary chunk of a GLB file if any.
+///
+/// Errors reading external buffers are returned as is, parse errors in the
+/// inner result.
+fn parse_gltf(
+    json: &str,
+    binary: Option<&[u8]>,
+    directory: &Path,
+) -> Result<Result<Mesh, String>, glib::Error> {
+    let root = match Json::parse(json) {
+        Ok(root) => root,
+        Err(e) => return Ok(Err(format!("Invalid glTF JSON: {e}"))),
+    };
+
+    let mut buffers = Vec::new();
+    for (i, buffer) in array(&root, "buffers").iter().enumerate() {
+        let data = match buffer.get("uri").and_then(Json::as_str) {
+            None if i == 0 && binary.is_some() => binary.unwrap_or_default().to_vec(),
+            None => return Ok(Err(format!("Buffer {i} has no data"))),
+            Some(uri) if uri.starts_with("data:") => match uri.split_once(";base64,") {
+                Some((_, data)) => glib::base64_decode(data),
+                None => return Ok(Err(format!("Buffer {i} isn't base64-encoded"))),
+            },
+            Some(uri) => {
+                let path = directory.join(uri);
+                let (contents, _) =
+                    gio::File::for_path(path).load_contents(gio::Cancellable::NONE)?;
+                contents.to_vec()
+            }
+        };
+        buffers.push(data);
+    }
+
+    let gltf = Gltf {
+        root: &root,
+        buffers,
+    };
+    let mut builder = Builder::default();
+    let mut visited = vec![false; array(&root, "nodes").len()];
+    let scene = index(&root, "scene").unwrap_or(0);
+    let result = match array(&root, "scenes").get(scene) {
+        Some(scene) => array(scene, "nodes").iter().try_for_each(|node| {
+            let node = node.as_f64().ok_or("Invalid scene node")? as usize;
+            gltf.add_node(node, IDENTITY, 0, &mut visited, &mut builder)
+        }),
+        // Without scenes, meshes are taken as they are
+        None => (0..array(&root, "meshes").len())
+            .try_for_each(|mesh| gltf.add_mesh(mesh, &IDENTITY, &mut builder)),
+    };
+    Ok(result
+        .map_err(str::to_owned)
+        .and_then(|()| builder.finish()))
+}
+
+/// A parsed glTF document with its buffers loaded.
+struct Gltf<'a> {
+    root: &'a Json,
+    buffers: Vec<Vec<u8>>,
+}
+
+impl Gltf<'_> {
+    /// Adds the meshes of a node and its children.
+    ///
+    /// glTF hierarchies are trees, so a node reached twice, through a cycle or
+    /// a shared child, is an error rather than walked again.
+    fn add_node(
+        &self,
+        index: usize,
+        parent: Matrix,
+        depth: usize,
+        visited: &mut [bool],
+        builder: &mut Builder,
+    ) -> Result<(), &'static str> {
+        if depth > MAX_NODE_DEPTH {
+            return Err("Node hierarchy too deep");
+        }
+        let node = array(self.root, "nodes").get(index).ok_or("Missing node")?;
+        if std::mem::replace(&mut visited[index], true) {
+            return Err("Node used more than once");
+        }
+        let transform = multiply(&parent, &node_matrix(node));
+        if let Some(mesh) = self::index(node, "mesh") {
+            self.add_mesh(mesh, &transform, builder)?;
+        }
+        for child in array(node, "children") {
+            let child = child.as_f64().ok_or("Invalid child node")? as usize;
+            self.add_node(child, transform, depth + 1, visited, builder)?;
+        }
+        Ok(())
+    }
+
+    /// Adds the triangles of a mesh, transformed by `transform`.
+    fn add_mesh(
+        &self,
+        index: usize,
+        transform: &Matrix,
+        builder: &mut Builder,
+    ) -> Result<(), &'static str> {
+        let mesh = array(self.root, "meshes")
+            .get(index)
+            .ok_or("Missing mesh")?;
+        let normal_transform = normal_matrix(transform);
+        for primitive in array(mesh, "primitives") {
+            // Points and lines have no surface to shade
+            if self::index(primitive, "mode").unwrap_or(4) != 4 {
+                continue;
+            }
+            let attributes = primitive.get("attributes");
+            let attribute = |name| attributes.and_then(|a| self::index(a, name));
+
+            let positions = self.read(attribute("POSITION").ok_or("Missing positions")?, 3)?;
+            let normals = attribute("NORMAL").map(|a| self.read(a, 3)).transpose()?;
+            let texcoords = attribute("TEXCOORD_0")
+                .map(|a| self.read(a, 2))
+                .transpose()?;
+            let count = positions.len() / 3;
+
+            let base = builder.vertices.len() as u32;
+            let first_index = builder.indices.len();
+            for i in 0..count {
+                let position = transform_point(transform, &positions[i * 3..i * 3 + 3], 1.0);
+                let normal = normals.as_ref().and_then(|normals| {
+                    let normal =
+                        transform_point(&normal_transform, normals.get(i * 3..i * 3 + 3)?, 0.0);
+                    let length = normal.iter().map(|c| c * c).sum::<f64>().sqrt();
+                    Some(normal.map(|c| (c / length.max(f64::EPSILON)) as f32))
+                });
+                // glTF puts the origin of textures at the top-left
+                let uv = texcoords
+                    .as_ref()
+                    .and_then(|t| t.get(i * 2..i * 2 + 2))
+                    .map_or([0.0, 0.0], |t| [t[0] as f32, 1.0 - t[1] as f32]);
+                builder.push_vertex(position.map(|c| c as f32), normal, uv);
+            }
+
+            match self::index(primitive, "indices") {
+                Some(accessor) => {
+                    for index in self.read(accessor, 1)? {
+                        if index as usize >= count {
+                            return Err("Index out of range");
+                        }
+                        builder.indices.push(base + index as u32);
+                    }
+                }
+                None => builder.indices.extend(base..base + count as u32),
+            }
+            // Drop a trailing incomplete triangle
+            let added = builder.indices.len() - first_index;
+            builder.indices.truncate(first_index + added - added % 3);
+        }
+        Ok(())
+    }
+
+    /// Reads the values of an accessor with `components` components per
+    /// element, converting them to floats.
+    fn read(&self, index: usize, components: usize) -> Result<Vec<f64>, &'static str> {
+        let accessor = array(self.root, "accessors")
+            .get(index)
+            .ok_or("Missing accessor")?;
+        if accessor.get("sparse").is_some() {
+            return Err("Sparse accessors are not supported");
+        }
+        let expected = match accessor.get("type").and_then(Json::as_str) {
+            Some("SCALAR") => 1,
+            Some("VEC2") => 2,
+            Some("VEC3") => 3,
+            Some("VEC4") => 4,
+            _ => 0,
+        };
+        if expected != components {
+            return Err("Unexpected accessor type");
+        }
+        let count = self::index(accessor, "count").ok_or("Missing accessor count")?;
+        let normalized = matches!(accessor.get("normalized"), Some(Json::Bool(true)));
+        let (size, max): (usize, f64) = match self::index(accessor, "componentType") {
+            Some(5120) => (1, 127.0),
+            Some(5121) => (1, 255.0),
+            Some(5122) => (2, 32767.0),
+            Some(5123) => (2, 65535.0),
+            Some(5125) => (4, 1.0),
+            Some(5126) => (4, 1.0),
+            _ => return Err("Unsupported accessor component type"),
+        };
+        let component_type = self::index(accessor, "componentType").unwrap_or_default();
+
+        // Accessors without a buffer view are zeroed
+        let Some(view) = self::index(accessor, "bufferView") else {
+            if count > MAX_ZEROED_ELEMENTS {
+                return Err("Accessor too large");
+            }
+            return Ok(vec![0.0; count * components]);
+        };
+        let view = array(self.root, "bufferViews")
+            .get(view)
+            .ok_or("Missing buffer view")?;
+        let buffer = self::index(view, "buffer")
+            .and_then(|b| self.buffers.get(b))
+            .ok_or("Missing buffer")?;
+        let offset = self::index(view, "byteOffset")
+            .unwrap_or(0)
+            .checked_add(self::index(accessor, "byteOffset").unwrap_or(0))
+            .ok_or("Accessor out of bounds")?;
+        let stride = self::index(view, "byteStride").unwrap_or(size * components);
+        if stride < size * components {
+            return Err("Invalid buffer view stride");
+        }
+
+        // Check that the last element is in the buffer before allocating
+        // anything, as the count comes from the file
+        let end = count
+            .checked_sub(1)
+            .map_or(Some(offset), |last| {
+                last.checked_mul(stride)?
+                    .checked_add(offset)?
+                    .checked_add(size * components)
+            })
+            .ok_or("Accessor out of bounds")?;
+        if end > buffer.len() {
+            return Err("Accessor out of bounds");
+        }
+
+        let mut values = Vec::with_capacity(count * components);
+        for element in 0..count {
+            for component in 0..components {
+                let at = offset + element * stride + component * size;
+                let b = buffer.get(at..at + size).ok_or("Accessor out of bounds")?;
+                let value = match component_type {
+                    5120 => f64::from(b[0] as i8),
+                    5121 => f64::from(b[0]),
+                    5122 => f64::from(i16::from_le_bytes([b[0], b[1]])),
+                    5123 => f64::from(u16::from_le_bytes([b[0], b[1]])),
+                    5125 => f64::from(u32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+                    _ => f64::from(f32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+                };
+                values.push(if normalized {
+                    (value / max).max(-1.0)
+                } else {
+                    value
+                });
+            }
+        }
+        Ok(values)
+    }
+}
+
+/// Returns the elements of the array member `key`.
+fn array<'a>(json: &'a Json, key: &str) -> &'a [Json] {
+    json.get(key).map(Json::elements).unwrap_or_default()
+}
+
+/// Returns the member `key` as an index or count.
+fn index(json: &Json, key: &str) -> Option<usize> {
+    json.get(key)
+        .and_then(Json::as_f64)
+        .filter(|n| *n >= 0.0)
+        .map(|n| n as usize)
+}
+
+/// Returns the local transform of a glTF node, from its matrix or its
+/// translation, rotation and scale.
+fn node_matrix(node: &Json) -> Matrix {
+    let numbers = |key, default: &[f64]| -> Vec<f64> {
+        let values: Vec<f64> = array(node, key).iter().filter_map(Json::as_f64).collect();
+        if values.len() == default.len() {
+            values
+        } else {
+            default.to_vec()
+        }
+    };
+    let matrix = numbers("matrix", &IDENTITY);
+    if matrix != IDENTITY {
+        return matrix.try_into().unwrap_or(IDENTITY);
+    }
+
+    let t = numbers("translation", &[0.0; 3]);
+    let [x, y, z, w] = numbers("rotation", &[0.0, 0.0, 0.0, 1.0])[..] else {
+        return IDENTITY;
+    };
+    let s = numbers("scale", &[1.0; 3]);
+    #[rustfmt::skip]
+    let matrix = [
+        (1.0 - 2.0 * (y * y + z * z)) * s[0], 2.0 * (x * y + w * z) * s[0], 2.0 * (x * z - w * y) * s[0], 0.0,
+        2.0 * (x * y - w * z) * s[1], (1.0 - 2.0 * (x * x + z * z)) * s[1], 2.0 * (y * z + w * x) * s[1], 0.0,
+        2.0 * (x * z + w * y) * s[2], 2.0 * (y * z - w * x) * s[2], (1.0 - 2.0 * (x * x + y * y)) * s[2], 0.0,
+        t[0], t[1], t[2], 1.0,
+    ];
+    matrix
+}
+
+fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut product = [0.0; 16];
+    for column in 0..4 {
+        for row in 0..4 {
+            product[column * 4 + row] = (0..4).map(|k| a[k * 4 + row] * b[column * 4 + k]).sum();
+        }
+    }
+    product
+}
+
+/// Transforms a point (`w` = 1) or a direction (`w` = 0).
+fn transform_point(matrix: &Matrix, v: &[f64], w: f64) -> [f64; 3] {
+    [0, 1, 2].map(|row| {
+        matrix[row] * v[0] + matrix[4 + row] * v[1] + matrix[8 + row] * v[2] + matrix[12 + row] * w
+    })
+}
+
+/// Returns the inverse transpose of the upper 3×3 of a transform, which keeps
+/// normals perpendicular to their faces under non-uniform scaling.
+///
+/// Normals are normalized after, so the cofactors are only divided by the
+/// sign of the determinant.
+fn normal_matrix(matrix: &Matrix) -> Matrix {
+    let [a, b, c] = [0, 4, 8].map(|i| [matrix[i], matrix[i + 1], matrix[i + 2]]);
+    let cross = |u: [f64; 3], v: [f64; 3]| {
+        [
+            u[1] * v[2] - u[2] * v[1],
+            u[2] * v[0] - u[0] * v[2],
+            u[0] * v[1] - u[1] * v[0],
+        ]
+    };
+    let cofactors = [cross(b, c), cross(c, a), cross(a, b)];
+    let determinant: f64 = (0..3).map(|i| a[i] * cofactors[0][i]).sum();
+
+    let mut normal = IDENTITY;
+    for (column, cofactors) in cofactors.iter().enumerate() {
+        for (row, cofactor) in cofactors.iter().enumerate() {
+            normal[column * 4 + row] = cofactor * determinant.signum();
+        }
+    }
+    normal
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+/// The vertex array and buffers of a mesh uploaded to the GPU.
+#[derive(Debug)]
+pub struct MeshBuffers {
+    vao: u32,
+    /// Vertex and index buffers
+    buffers: [u32; 2],
+    index_count: i32,
+}
+
+impl MeshBuffers {
+    /// Uploads a mesh and sets up its vertex attributes.
+    ///
+    /// The caller is responsible for binding its own vertex array again.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    pub unsafe fn new(mesh: &Mesh) -> Self {
+        let mut this = Self {
+            vao: 0,
+            buffers: [0; 2],
+            index_count: mesh.indices.len() as i32,
+        };
+        let stride = (VERTEX_SIZE * size_of::<f32>()) as i32;
+        unsafe {
+            epoxy::GenVertexArrays(1, &raw mut this.vao);
+            epoxy::BindVertexArray(this.vao);
+            epoxy::GenBuffers(2, this.buffers.as_mut_ptr());
+
+            epoxy::BindBuffer(epoxy::ARRAY_BUFFER, this.buffers[0]);
+            epoxy::BufferData(
+                epoxy::ARRAY_BUFFER,
+                size_of_val(mesh.vertices.as_slice()) as isize,
+                mesh.vertices.as_ptr().cast(),
+                epoxy::STATIC_DRAW,
+            );
+            epoxy::BindBuffer(epoxy::ELEMENT_ARRAY_BUFFER, this.buffers[1]);
+            epoxy::BufferData(
+                epoxy::ELEMENT_ARRAY_BUFFER,
+                size_of_val(mesh.indices.as_slice()) as isize,
+                mesh.indices.as_ptr().cast(),
+                epoxy::STATIC_DRAW,
+            );
+
+            // Position, normal and texture coordinates
+            for (location, (size, offset)) in [(3, 0), (3, 3), (2, 6)].into_iter().enumerate() {
+                epoxy::EnableVertexAttribArray(location as u32);
+                epoxy::VertexAttribPointer(
+                    location as u32,
+                    size,
+                    epoxy::FLOAT,
+                    epoxy::FALSE,
+                    stride,
+                    (offset * size_of::<f32>()) as *const _,
+                );
+            }
+        }
+        this
+    }
+
+    /// Draws the triangles of the mesh with depth testing.
+    ///
+    /// The caller is responsible for binding its own vertex array again.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    pub unsafe fn draw(&self) {
+        unsafe {
+            epoxy::Enable(epoxy::DEPTH_TEST);
+            epoxy::BindVertexArray(self.vao);
+            epoxy::DrawElements(
+                epoxy::TRIANGLES,
+                self.index_count,
+                epoxy::UNSIGNED_INT,
+                std::ptr::null(),
+            );
+            epoxy::Disable(epoxy::DEPTH_TEST);
+        }
+    }
+
+    /// Deletes the vertex array and buffers.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    pub unsafe fn delete(&self) {
+        unsafe {
+            epoxy::DeleteVertexArrays(1, &raw const self.vao);
+            epoxy::DeleteBuffers(2, self.buffers.as_ptr());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: &[f32], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len(), "{actual:?} != {expected:?}");
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-5, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn parses_obj() {
+        let mesh = parse_obj(
+            "# a square and a triangle\n\
+             v 0 0 0\n\
+             v 2 0 0\n\
+             v 2 2 0\n\
+             v 0 2 0\n\
+             vt 0 0\n\
+             vt 1\n\
+             vn 0 0 1\n\
+             f 1/1/1 2/2/1 3/1/1 4//1 # fan\n\
+             f -4 -3 -2\n\
+             o ignored\n",
+        )
+        .unwrap();
+
+        assert_eq!(mesh.indices, [0, 1, 2, 0, 2, 3, 4, 5, 6]);
+        assert_eq!(mesh.vertices.len(), 7);
+        assert_close(&mesh.vertices[1], &[2.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0]);
+        assert_close(&mesh.vertices[3], &[0.0, 2.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+        // Normals computed from the face
+        assert_close(&mesh.vertices[5], &[2.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+        // Centered and fitted from -1 to 1
+        assert_close(
+            &mesh.model_matrix,
+            &[
+                1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, -1.0, -1.0, 0.0, 1.0,
+            ],
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_obj() {
+        let error = |source| parse_obj(source).unwrap_err();
+        assert_eq!(error(""), "No triangles");
+        assert_eq!(error("v 0 0 0\nvn 0 0 1\n"), "No triangles");
+        assert_eq!(error("v 1 2\n"), "Invalid vertex on line 1");
+        assert_eq!(error("vt\n"), "Invalid texture coordinate on line 1");
+        assert_eq!(error("vt 0 x\n"), "Invalid texture coordinate on line 1");
+        assert_eq!(error("vn 0 0 z\n"), "Invalid normal on line 1");
+        assert_eq!(error("v 0 0 0\nf 1 2 3\n"), "Invalid face index on line 2");
+        assert_eq!(error("v 0 0 0\nf 0 1 1\n"), "Invalid face index on line 2");
+        assert_eq!(
+            error("v 0 0 0\nf 1/2 1 1\n"),
+            "Invalid face index on line 2"
+        );
+        assert_eq!(
+            error("v 0 0 0\nf /1 1 1\n"),
+            "Face without position on line 2"
+        );
+        assert_eq!(
+            error("v 0 0 0\nf 1 1\n"),
+            "Face with fewer than 3 vertices on line 2"
+        );
+    }
+
+    #[test]
+    fn resolves_obj_indices() {
+        assert_eq!(resolve_index(1, 3), Some(0));
+        assert_eq!(resolve_index(3, 3), Some(2));
+        assert_eq!(resolve_index(-1, 3), Some(2));
+        assert_eq!(resolve_index(-3, 3), Some(0));
+        assert_eq!(resolve_index(0, 3), None);
+        assert_eq!(resolve_index(4, 3), None);
+        assert_eq!(resolve_index(-4, 3), None);
+        assert_eq!(resolve_index(i64::MIN, 3), None);
+    }
+
+    /// A triangle with slanted normals, scaled along x by its parent node.
+    const GLTF: &str = r#"{
+        "scene": 0,
+        "scenes": [{"nodes": [0]}],
+        "nodes": [
+            {"children": [1], "scale": [2, 1, 1]},
+            {"mesh": 0, "translation": [0, 0, 1]}
+        ],
+        "meshes": [{"primitives": [{"attributes": {"POSITION": 0, "NORMAL": 1}, "indices": 2}]}],
+        "buffers": [{"byteLength": 78}],
+        "bufferViews": [
+            {"buffer": 0, "byteLength": 72},
+            {"buffer": 0, "byteOffset": 72, "byteLength": 6}
+        ],
+        "accessors": [
+            {"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3"},
+            {"bufferView": 0, "byteOffset": 36, "componentType": 5126, "count": 3, "type": "VEC3"},
+            {"bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR"}
+        ]
+    }"#;
+
+    fn binary() -> Vec<u8> {
+        let floats: [f32; 18] = [
+            0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, // positions
+            1.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0, 1.0, 0.0, // normals
+        ];
+        let mut binary: Vec<u8> = floats.iter().flat_map(|f| f.to_le_bytes()).collect();
+        binary.extend([0u16, 1, 2].iter().flat_map(|i| i.to_le_bytes()));
+        binary
+    }
+
+    fn chunk(kind: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut chunk = (data.len() as u32).to_le_bytes().to_vec();
+        chunk.extend(kind);
+        chunk.extend(data);
+        chunk
+    }
+
+    fn glb(chunks: &[Vec<u8>]) -> Vec<u8> {
+        let body = chunks.concat();
+        let mut file = b"glTF".to_vec();
+        file.extend(2u32.to_le_bytes());
+        file.extend(((12 + body.len()) as u32).to_le_bytes());
+        file.extend(body);
+        file
+    }
+
+    fn parse(json: &str) -> Result<Mesh, String> {
+        let file = glb(&[chunk(b"JSON", json.as_bytes()), chunk(b"BIN\0", &binary())]);
+        parse_glb(&file, Path::new(".")).unwrap()
+    }
+
+    #[test]
+    fn parses_glb() {
+        let mesh = parse(GLTF).unwrap();
+        assert_eq!(mesh.indices, [0, 1, 2]);
+        // Normals follow the inverse transpose of the node transforms
+        let [nx, ny] = [1.0 / 5f32.sqrt(), 2.0 / 5f32.sqrt()];
+        assert_close(&mesh.vertices[0], &[0.0, 0.0, 1.0, nx, ny, 0.0, 0.0, 0.0]);
+        assert_close(&mesh.vertices[1], &[2.0, 0.0, 1.0, nx, ny, 0.0, 0.0, 0.0]);
+        assert_close(&mesh.vertices[2], &[0.0, 1.0, 1.0, nx, ny, 0.0, 0.0, 0.0]);
+
+        // Without scenes, meshes are taken untransformed
+        let mesh = parse(&GLTF.replace(r#""scenes""#, r#""unused""#)).unwrap();
+        assert_close(&mesh.vertices[1][..3], &[1.0, 0.0, 0.0]);
+
+        // Points are skipped
+        assert_eq!(
+            parse(&GLTF.replace(r#""indices": 2"#, r#""indices": 2, "mode": 0"#)).unwrap_err(),
+            "No triangles"
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_glb() {
+        let error = |file: &[u8]| parse_glb(file, Path::new(".")).unwrap().unwrap_err();
+        let mut file = glb(&[chunk(b"JSON", GLTF.as_bytes())]);
+        file[4] = 1;
+        assert_eq!(error(&file), "Not a glTF 2.0 binary file");
+        assert_eq!(error(b"glTF"), "Not a glTF 2.0 binary file");
+        assert_eq!(error(b"PK\x03\x04\x02\0\0\0"), "Not a glTF 2.0 binary file");
+
+        let file = glb(&[chunk(b"JSON", GLTF.as_bytes())]);
+        assert_eq!(error(&file[..file.len() - 1]), "Truncated chunk");
+        assert_eq!(
+            error(&glb(&[chunk(b"BIN\0", &binary())])),
+            "Missing JSON chunk"
+        );
+        assert_eq!(
+            error(&glb(&[chunk(b"JSON", b"\xff")])),
+            "Missing JSON chunk"
+        );
+        assert!(error(&glb(&[chunk(b"JSON", b"{")])).starts_with("Invalid glTF JSON"));
+    }
+
+    #[test]
+    fn rejects_invalid_gltf() {
+        let error = |from: &str, to: &str| {
+            let json = GLTF.replace(from, to);
+            assert_ne!(json, GLTF, "{from}");
+            parse(&json).unwrap_err()
+        };
+
+        // Accessors reading past the buffer, or too large to allocate
+        let indices = r#""count": 3, "type": "SCALAR""#;
+        assert_eq!(
+            error(indices, r#""count": 4, "type": "SCALAR""#),
+            "Accessor out of bounds"
+        );
+        assert_eq!(
+            error(indices, r#""count": 1e19, "type": "SCALAR""#),
+            "Accessor out of bounds"
+        );
+        assert_eq!(
+            error(r#"{"bufferView": 1, "#, r#"{"count": 1e12, "#),
+            "Accessor too large"
+        );
+        assert_eq!(
+            error(
+                r#""byteLength": 72}"#,
+                r#""byteLength": 72, "byteStride": 4}"#
+            ),
+            "Invalid buffer view stride"
+        );
+        assert_eq!(
+            error(r#""type": "SCALAR""#, r#""type": "VEC2""#),
+            "Unexpected accessor type"
+        );
+        assert_eq!(error("5123", "5130"), "Unsupported accessor component type");
+        assert_eq!(
+            error(r#""indices": 2"#, r#""indices": 3"#),
+            "Missing accessor"
+        );
+        assert_eq!(error(r#""mesh": 0"#, r#""mesh": 1"#), "Missing mesh");
+
+        // Node hierarchies must be trees
+        assert_eq!(
+            error(r#""children": [1]"#, r#""children": [0]"#),
+            "Node used more than once"
+        );
+        assert_eq!(
+            error(r#""children": [1]"#, r#""children": [1, 1]"#),
+            "Node used more than once"
+        );
+        assert_eq!(
+            error(r#""nodes": [0]"#, r#""nodes": [0, 1]"#),
+            "Node used more than once"
+        );
+        assert_eq!(
+            error(r#""children": [1]"#, r#""children": [2]"#),
+            "Missing node"
+        );
+    }
+
+    #[test]
+    fn transforms_normals() {
+        let normal = |matrix: &Matrix, v: [f64; 3]| {
+            let n = transform_point(&normal_matrix(matrix), &v, 0.0);
+            let length = n.iter().map(|c| c * c).sum::<f64>().sqrt();
+            n.map(|c| (c / length) as f32)
+        };
+        let mut scale = IDENTITY;
+        scale[0] = 2.0;
+        // A translation doesn't move directions
+        scale[12] = 5.0;
+        assert_close(
+            &normal(&scale, [1.0, 1.0, 0.0]),
+            &[0.447_213_6, 0.894_427_2, 0.0],
+        );
+
+        // Mirroring flips normals along with the faces
+        let mut mirror = IDENTITY;
+        mirror[0] = -1.0;
+        assert_close(&normal(&mirror, [1.0, 0.0, 0.0]), &[-1.0, 0.0, 0.0]);
+
+        let rotation =
+            node_matrix(&Json::parse(r#"{"rotation": [0, 0, 0.7071068, 0.7071068]}"#).unwrap());
+        assert_close(&normal(&rotation, [1.0, 0.0, 0.0]), &[0.0, 1.0, 0.0]);
+    }
+}
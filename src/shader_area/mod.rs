@@ -4,8 +4,17 @@
 //! shaders on a fullscreen quad. It supports:
 //!
 //! - Custom fragment shaders in GLSL
+//! - Optional custom vertex shaders drawing procedural geometry
+//! - Instanced GPU particles with their state in float textures
+//! - Compute shaders with storage buffers and images, on OpenGL 4.3+
+//! - OBJ and glTF meshes with depth testing and an orbit camera
 //! - Multiple texture inputs (accessible as `tex0`, `tex1`, etc.)
-//! - Uniform variables (float, vec2/3/4, int, ivec2/3/4)
+//! - Uniform variables (float, vec2/3/4, int, ivec2/3/4, bool, uint, uvec2/3/4,
+//!   mat2/3/4, and double and dvec2/3/4 with fp64 support), also from graphene
+//!   and `GdkRGBA` values, arrays of them (see [`UniformType`]) and uniform
+//!   blocks (see [`UniformBlock`])
+//! - Setting several uniforms in the same frame (see [`ShaderArea::set_uniforms`])
+//! - Listing the uniforms the shaders use (see [`ShaderArea::list_uniforms`])
 //! - Automatic resource cleanup when the widget is destroyed
 //!
 //! # Example (from GJS)
@@ -33,22 +42,120 @@
 //! - `uv` - A `vec2` interpolant from `(0, 0)` at bottom-left to `(1, 1)` at top-right
 //! - `tex0`, `tex1`, ... - Sampler uniforms for each loaded texture
 //! - Custom uniforms - Can be set via the `uniforms` parameter or setter methods
+//! - Builtin uniforms - `time`, `time_delta`, `frame`, `resolution`, `scale_factor`,
+//!   `date` and `texN_size`, filled automatically when declared (see [`builtins`])
+//! - Input builtins - pointer, click, drag and touch uniforms, filled when the
+//!   `pointer-input` property is enabled
+//! - `keyboard` - A Shadertoy-style 256×3 keyboard texture, bound when the
+//!   `keyboard-input` property is enabled
+//! - `view`, `view_matrix` - A pan/zoom/rotate transform driven by scroll, drag
+//!   and touch gestures when the `view-controls` property is enabled
+//! - `model_matrix`, `camera_matrix`, `projection_matrix` - The transforms of
+//!   a mesh, driven by pointer drags when the `orbit-camera` property is
+//!   enabled (see [`Mesh`])
+//! - Render passes - The output of each [`Pass`], as a sampler named after it
+//!
+//! With the `dialect` property set to [`ShaderDialect::Shadertoy`], shaders
+//! define `mainImage` instead, and get Shadertoy's `iTime`, `iResolution`,
+//! `iMouse`, `iChannel0`… inputs (see [`ShaderArea::new_shadertoy`]).
+//! [`ShaderDialect::Isf`] accepts Interactive Shader Format files, whose
+//! JSON header declares their inputs (see [`ShaderArea::new_isf`]), and
+//! [`ShaderDialect::Transition`] transitions from the gl-transitions
+//! collection (see [`ShaderArea::new_transition`]). Legacy GLSL from The Book
+//! of Shaders or glslCanvas, writing `gl_FragColor` and reading `u_time` or
+//! `u_resolution`, is detected and runs as is.
+//!
+//! # Rendering
+//!
+//! By default the widget renders whenever GTK redraws it or a uniform is set.
+//! The `animate` property renders on every frame of the frame clock instead
+//! (optionally capped by `max-fps`), pausing while the widget is unmapped,
+//! insensitive, or in a backdrop or suspended window. The `on-demand` property
+//! only renders when a uniform actually changes.
+//!
+//! A custom vertex shader set with [`ShaderArea::set_vertex_shader`] replaces
+//! the fullscreen quad: the widget draws `vertex-count` vertices as the
+//! `primitive` property's [`Primitive`], and the shader positions them from
+//! `vertexId` and `vertexCount`.
+//!
+//! Offscreen passes added with [`ShaderArea::add_pass`] are rendered in order
+//! before the widget's shader, which draws to the screen. A pass can sample
+//! the passes before it, and its own output or the later passes' from the
+//! previous frame, for feedback effects like reaction-diffusion.
+//!
+//! A particle system set with [`ShaderArea::set_particles`] keeps the state
+//! of `particle-count` particles in float textures, advanced by an update
+//! shader before the passes, and draws them as instanced sprites over the
+//! widget's shader, blended according to `particle-blend` (see
+//! [`Particles`]).
+//!
+//! On OpenGL 4.3 and later (see the `compute-supported` property), compute
+//! passes added with [`ShaderArea::add_compute`] are dispatched before
+//! anything else, reading and writing [`StorageBuffer`]s and
+//! [`StorageImage`]s that can be read back.
+//!
+//! A mesh loaded with [`ShaderArea::load_mesh`] also replaces the fullscreen
+//! quad: the widget's shader is drawn over its triangles with depth testing,
+//! by a vertex shader applying the model, camera and projection matrices.
+//!
+//! # Errors
+//!
+//! Compile and link failures are reported through the `compile-error` signal,
+//! with line numbers relative to the source passed by the user.
+//! [`ShaderArea::new_checked`] additionally checks the shader up front and
+//! returns a [`ShaderError`].
 
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 use glib::{Object, subclass::types::ObjectSubclassIsExt};
-use gtk::glib;
+use gtk::{gdk, glib, graphene};
 
 use crate::init;
 
+mod active;
+mod block;
+mod builtins;
+mod camera;
+mod compute;
+mod dialect;
+mod error;
 mod ffi;
 mod imp;
+mod input;
+mod isf;
+mod json;
+mod legacy;
+mod mesh;
+mod particles;
+mod pass;
+mod shadertoy;
+mod transition;
+mod uniform;
+mod vertex;
+mod view;
+
+pub use active::{UniformInfo, UniformSource};
+pub use block::{BlockContents, UniformBlock};
+pub use compute::{ComputePass, ComputeSchedule, StorageBuffer, StorageImage};
+pub use dialect::ShaderDialect;
+pub use error::{ShaderError, ShaderStage};
+pub use mesh::Mesh;
+pub use particles::{BlendMode, Particles};
+pub use pass::{Pass, PassFilter, PassFormat, PassSize};
+pub use transition::Easing;
+pub use uniform::UniformType;
+pub use vertex::Primitive;
 
 /// A uniform value that can be passed to shaders.
 ///
 /// These types correspond to GLSL uniform types and can be set from GJS
-/// using the appropriate setter methods on [`ShaderArea`].
-#[derive(Debug, Clone)]
+/// using the appropriate setter methods on [`ShaderArea`]. Graphene vectors,
+/// points, rectangles and matrices, as well as `gdk::RGBA`, convert into
+/// the matching vector or matrix.
+#[derive(Debug, Clone, PartialEq)]
 pub enum Uniform {
     /// A single float value (`float` in GLSL)
     Float(f32),
@@ -66,6 +173,110 @@ pub enum Uniform {
     IVec3([i32; 3]),
     /// A 4-component integer vector (`ivec4` in GLSL)
     IVec4([i32; 4]),
+    /// A boolean (`bool` in GLSL)
+    Bool(bool),
+    /// A single unsigned integer value (`uint` in GLSL)
+    UInt(u32),
+    /// A 2-component unsigned integer vector (`uvec2` in GLSL)
+    UVec2([u32; 2]),
+    /// A 3-component unsigned integer vector (`uvec3` in GLSL)
+    UVec3([u32; 3]),
+    /// A 4-component unsigned integer vector (`uvec4` in GLSL)
+    UVec4([u32; 4]),
+    /// A double-precision value (`double` in GLSL, with
+    /// `GL_ARB_gpu_shader_fp64`)
+    Double(f64),
+    /// A 2-component double-precision vector (`dvec2` in GLSL)
+    DVec2([f64; 2]),
+    /// A 3-component double-precision vector (`dvec3` in GLSL)
+    DVec3([f64; 3]),
+    /// A 4-component double-precision vector (`dvec4` in GLSL)
+    DVec4([f64; 4]),
+    /// A 2×2 matrix in column-major order (`mat2` in GLSL)
+    Mat2([f32; 4]),
+    /// A 3×3 matrix in column-major order (`mat3` in GLSL)
+    Mat3([f32; 9]),
+    /// A 4×4 matrix in column-major order (`mat4` in GLSL)
+    Mat4([f32; 16]),
+    /// An array of floats (`float[N]` in GLSL)
+    FloatArray(Vec<f32>),
+    /// An array of 2-component vectors (`vec2[N]` in GLSL)
+    Vec2Array(Vec<[f32; 2]>),
+    /// An array of 3-component vectors (`vec3[N]` in GLSL)
+    Vec3Array(Vec<[f32; 3]>),
+    /// An array of 4-component vectors (`vec4[N]` in GLSL)
+    Vec4Array(Vec<[f32; 4]>),
+    /// An array of integers (`int[N]` in GLSL)
+    IntArray(Vec<i32>),
+    /// An array of 2-component integer vectors (`ivec2[N]` in GLSL)
+    IVec2Array(Vec<[i32; 2]>),
+    /// An array of 3-component integer vectors (`ivec3[N]` in GLSL)
+    IVec3Array(Vec<[i32; 3]>),
+    /// An array of 4-component integer vectors (`ivec4[N]` in GLSL)
+    IVec4Array(Vec<[i32; 4]>),
+    /// An array of unsigned integers (`uint[N]` in GLSL)
+    UIntArray(Vec<u32>),
+    /// An array of 2-component unsigned integer vectors (`uvec2[N]` in GLSL)
+    UVec2Array(Vec<[u32; 2]>),
+    /// An array of 3-component unsigned integer vectors (`uvec3[N]` in GLSL)
+    UVec3Array(Vec<[u32; 3]>),
+    /// An array of 4-component unsigned integer vectors (`uvec4[N]` in GLSL)
+    UVec4Array(Vec<[u32; 4]>),
+    /// An array of doubles (`double[N]` in GLSL)
+    DoubleArray(Vec<f64>),
+    /// An array of 2-component double-precision vectors (`dvec2[N]` in GLSL)
+    DVec2Array(Vec<[f64; 2]>),
+    /// An array of 3-component double-precision vectors (`dvec3[N]` in GLSL)
+    DVec3Array(Vec<[f64; 3]>),
+    /// An array of 4-component double-precision vectors (`dvec4[N]` in GLSL)
+    DVec4Array(Vec<[f64; 4]>),
+}
+
+impl From<graphene::Vec2> for Uniform {
+    fn from(v: graphene::Vec2) -> Self {
+        Self::Vec2(v.to_float())
+    }
+}
+
+impl From<graphene::Vec3> for Uniform {
+    fn from(v: graphene::Vec3) -> Self {
+        Self::Vec3(v.to_float())
+    }
+}
+
+impl From<graphene::Vec4> for Uniform {
+    fn from(v: graphene::Vec4) -> Self {
+        Self::Vec4(v.to_float())
+    }
+}
+
+impl From<graphene::Point> for Uniform {
+    fn from(point: graphene::Point) -> Self {
+        Self::Vec2([point.x(), point.y()])
+    }
+}
+
+/// A rectangle becomes a `vec4` of its origin and size, normalized to a
+/// positive size.
+impl From<graphene::Rect> for Uniform {
+    fn from(rect: graphene::Rect) -> Self {
+        let rect = rect.normalize_r();
+        Self::Vec4([rect.x(), rect.y(), rect.width(), rect.height()])
+    }
+}
+
+/// Graphene multiplies row vectors by matrices, so its row-major floats are
+/// the column-major floats of the same transform in GLSL.
+impl From<graphene::Matrix> for Uniform {
+    fn from(matrix: graphene::Matrix) -> Self {
+        Self::Mat4(matrix.to_float())
+    }
+}
+
+impl From<gdk::RGBA> for Uniform {
+    fn from(color: gdk::RGBA) -> Self {
+        Self::Vec4([color.red(), color.green(), color.blue(), color.alpha()])
+    }
 }
 
 glib::wrapper! {
@@ -107,6 +318,226 @@ impl ShaderArea {
         this
     }
 
+    /// Creates a new `ShaderArea` widget, checking that the shader compiles.
+    ///
+    /// The shader is compiled and linked on a temporary OpenGL context from
+    /// the default display, so errors are reported before the widget is
+    /// realized. The OpenGL context current before is made current again.
+    ///
+    /// # Arguments
+    ///
+    /// * `shader` - GLSL fragment shader source code
+    /// * `textures` - Paths to image files to load as textures (accessible as `tex0`, `tex1`, etc.)
+    /// * `uniforms` - Initial uniform values to pass to the shader
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError`] if the shader fails to compile or link, or if
+    /// no OpenGL context is available to check it.
+    pub fn new_checked(
+        shader: String,
+        textures: Vec<PathBuf>,
+        uniforms: HashMap<String, Uniform>,
+    ) -> Result<Self, glib::Error> {
+        init();
+
+        imp::ShaderArea::check_shader(&shader)?;
+        Ok(Self::new(shader, textures, uniforms))
+    }
+
+    /// Creates a new `ShaderArea` widget from a shader file, reloading the
+    /// shader and textures whenever their files change on disk.
+    ///
+    /// # Arguments
+    ///
+    /// * `shader_path` - Path of the GLSL fragment shader file
+    /// * `textures` - Paths to image files to load as textures (accessible as `tex0`, `tex1`, etc.)
+    /// * `uniforms` - Initial uniform values to pass to the shader
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GIOError` if the shader file can't be read.
+    ///
+    /// # Note
+    ///
+    /// Compile failures after a reload are reported through the
+    /// `compile-error` signal, and the previous shader keeps running.
+    pub fn new_from_files(
+        shader_path: PathBuf,
+        textures: Vec<PathBuf>,
+        uniforms: HashMap<String, Uniform>,
+    ) -> Result<Self, glib::Error> {
+        let shader = imp::ShaderArea::read_shader(&shader_path)?;
+        let this = Self::new(shader, textures, uniforms);
+        this.imp().watch_files(&shader_path);
+        Ok(this)
+    }
+
+    /// Creates a new `ShaderArea` widget running a Shadertoy shader.
+    ///
+    /// The `dialect` is set to [`ShaderDialect::Shadertoy`], and animation
+    /// and pointer input are enabled for `iTime` and `iMouse`.
+    ///
+    /// # Arguments
+    ///
+    /// * `shader` - Shadertoy code defining `mainImage`
+    /// * `textures` - Paths to image files to load as textures, bound to
+    ///   `iChannel0` to `iChannel3`
+    #[must_use]
+    pub fn new_shadertoy(shader: String, textures: Vec<PathBuf>) -> Self {
+        let this = Self::new(shader, textures, HashMap::new());
+        this.set_dialect(ShaderDialect::Shadertoy);
+        this.set_animate(true);
+        this.set_pointer_input(true);
+        this
+    }
+
+    /// Creates a new `ShaderArea` widget from a Shadertoy JSON export.
+    ///
+    /// Buffers A to D become render passes named `buffer_a` to `buffer_d`,
+    /// and the channels of every pass are bound like on Shadertoy.
+    ///
+    /// # Arguments
+    ///
+    /// * `json` - The JSON export
+    /// * `media_dir` - Directory where the textures of the shader were
+    ///   downloaded, looked up by file name
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError::Parse`] if the export is malformed.
+    pub fn new_from_shadertoy_json(
+        json: &str,
+        media_dir: Option<&Path>,
+    ) -> Result<Self, glib::Error> {
+        let project = shadertoy::parse_export(json, media_dir)?;
+
+        let this = Self::new_shadertoy(project.image, project.textures);
+        for (i, sampler) in project.image_channels.iter().enumerate() {
+            this.set_channel(None, i as u32, sampler.as_deref());
+        }
+        for (pass, channels) in project.buffers {
+            for (i, sampler) in channels.iter().enumerate() {
+                this.set_channel(Some(&pass.name), i as u32, sampler.as_deref());
+            }
+            this.add_pass(pass)?;
+        }
+        this.set_keyboard_input(project.keyboard);
+        Ok(this)
+    }
+
+    /// Creates a new `ShaderArea` widget running an Interactive Shader
+    /// Format (ISF) shader.
+    ///
+    /// The `dialect` is set to [`ShaderDialect::Isf`]. The inputs of the
+    /// JSON header are declared as uniforms, initialized to their `DEFAULT`
+    /// unless given in `uniforms`, and every pass with a `TARGET` but the
+    /// last becomes a render pass named after its target. `event` inputs
+    /// are reset to false after the frame that sees them set.
+    ///
+    /// # Arguments
+    ///
+    /// * `shader` - The ISF shader, JSON header included
+    /// * `textures` - Paths to image files bound to the `image` inputs, then
+    ///   to the `IMPORTED` images, in order
+    /// * `uniforms` - Initial uniform values, overriding the defaults
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError::Parse`] if the header is malformed, or a
+    /// [`ShaderError::Pass`] if a pass target isn't a valid pass name.
+    pub fn new_isf(
+        shader: String,
+        textures: Vec<PathBuf>,
+        mut uniforms: HashMap<String, Uniform>,
+    ) -> Result<Self, glib::Error> {
+        let (header, _) = isf::Header::parse(&shader).map_err(|e| {
+            glib::Error::new(ShaderError::Parse, &format!("Invalid ISF header: {e}"))
+        })?;
+        for input in &header.inputs {
+            if let Some(value) = input.default_value() {
+                uniforms.entry(input.name.clone()).or_insert(value);
+            }
+        }
+
+        let this = Self::new(shader.clone(), textures, uniforms);
+        this.set_dialect(ShaderDialect::Isf);
+        for pass in header.render_passes(&shader) {
+            this.add_pass(pass)?;
+        }
+        Ok(this)
+    }
+
+    /// Returns the inputs declared by the widget's ISF shader, for building
+    /// controls.
+    ///
+    /// The result is a `GVariant` of type `aa{sv}`, with one dictionary per
+    /// input holding the ISF attributes it sets, lowercased: `name`, `type`,
+    /// `label`, `default`, `min`, `max`, `identity`, `values` and `labels`.
+    /// Numbers are doubles. It is empty if the shader has no valid ISF
+    /// header.
+    #[must_use]
+    pub fn isf_inputs(&self) -> glib::Variant {
+        self.imp().isf_inputs()
+    }
+
+    /// Creates a new `ShaderArea` widget playing a gl-transitions
+    /// transition between two images.
+    ///
+    /// The `dialect` is set to [`ShaderDialect::Transition`], and the
+    /// parameters of the transition are initialized from their
+    /// `// = value` comments. The first image is shown until
+    /// [`Self::start_transition`] or [`Self::queue_image`] is called.
+    ///
+    /// # Arguments
+    ///
+    /// * `transition` - Code defining `vec4 transition(vec2 uv)`
+    /// * `from` - Path of the image to transition from, bound to `tex0`
+    /// * `to` - Path of the image to transition to, bound to `tex1`
+    #[must_use]
+    pub fn new_transition(transition: String, from: PathBuf, to: PathBuf) -> Self {
+        let mut uniforms: HashMap<String, Uniform> = transition::parse_defaults(&transition)
+            .into_iter()
+            .collect();
+        uniforms.insert("progress".to_owned(), Uniform::Float(0.0));
+
+        let this = Self::new(transition, vec![from, to], uniforms);
+        this.set_dialect(ShaderDialect::Transition);
+        this
+    }
+
+    /// Plays the transition from the first image to the second one over
+    /// `transition-duration` seconds, restarting it if it is running.
+    ///
+    /// The `finished` signal is emitted when it ends.
+    pub fn start_transition(&self) {
+        self.imp().start_transition();
+    }
+
+    /// Queues an image to transition to after the current transition.
+    ///
+    /// Once a transition finishes, the image it transitioned to becomes the
+    /// one to transition from, and the transition to the next queued image
+    /// starts. If no transition is running, it starts right away.
+    pub fn queue_image(&self, path: PathBuf) {
+        self.imp().queue_image(path);
+    }
+
+    /// Binds a Shadertoy `iChannelN` to a sampler.
+    ///
+    /// By default, `iChannelN` is bound to the texture `texN`.
+    ///
+    /// # Arguments
+    ///
+    /// * `pass` - The name of the render pass, or `None` for the widget's
+    ///   shader
+    /// * `channel` - The channel index, from 0 to 3
+    /// * `sampler` - The name of the sampler to bind (`tex0`, `keyboard`, the
+    ///   name of a pass…), or `None` to leave the channel unbound
+    pub fn set_channel(&self, pass: Option<&str>, channel: u32, sampler: Option<&str>) {
+        self.imp().set_channel(pass, channel as usize, sampler);
+    }
+
     /// Sets a uniform value on the shader.
     ///
     /// # Arguments
@@ -118,7 +549,269 @@ impl ShaderArea {
     ///
     /// If the widget is not yet realized, the uniform will be set once it is.
     /// If the uniform name doesn't exist in the shader, a warning is logged.
+    /// If the value can't be converted to the type declared by the shader,
+    /// an error is logged and the value is not set.
     pub fn set_uniform(&self, name: String, value: Uniform) {
         self.imp().set_uniform(name, value);
     }
+
+    /// Sets several uniform values at once, so that they're applied to the
+    /// same frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError::Uniform`] if a value can't be converted to
+    /// the type declared by the shaders, in which case none are set.
+    ///
+    /// # Note
+    ///
+    /// Inside a transaction (see [`Self::begin_uniforms`]), the values are
+    /// only checked and applied when it's committed.
+    pub fn set_uniforms(&self, values: HashMap<String, Uniform>) -> Result<(), glib::Error> {
+        self.imp().set_uniforms(values)
+    }
+
+    /// Starts a uniform transaction: values given to [`Self::set_uniform`]
+    /// and [`Self::set_uniforms`] are kept until [`Self::commit_uniforms`],
+    /// which applies them all to the same frame, or [`Self::abort_uniforms`],
+    /// which drops them.
+    ///
+    /// Transactions can be nested; the values are applied when the
+    /// outermost one is committed. A transaction left open for many frames
+    /// is logged as a warning.
+    pub fn begin_uniforms(&self) {
+        self.imp().begin_uniforms();
+    }
+
+    /// Commits a uniform transaction started by [`Self::begin_uniforms`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError::Uniform`] if a value can't be converted to
+    /// the type declared by the shaders, in which case none are set.
+    pub fn commit_uniforms(&self) -> Result<(), glib::Error> {
+        self.imp().commit_uniforms()
+    }
+
+    /// Aborts a uniform transaction started by [`Self::begin_uniforms`],
+    /// along with any outer ones, dropping the values set since.
+    pub fn abort_uniforms(&self) {
+        self.imp().abort_uniforms();
+    }
+
+    /// Sets the contents of a uniform block, replacing those of the block
+    /// with the same name.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError::Uniform`] if the block name is invalid or
+    /// its contents are empty.
+    ///
+    /// # Note
+    ///
+    /// If the widget is not yet realized, the block is uploaded once it is.
+    /// Unknown members, and bytes past the size of the block in the shader,
+    /// are dropped with a warning.
+    pub fn set_uniform_block(&self, block: UniformBlock) -> Result<(), glib::Error> {
+        self.imp().set_uniform_block(block)
+    }
+
+    /// Removes the uniform block called `name`, returning whether it existed.
+    pub fn remove_uniform_block(&self, name: &str) -> bool {
+        self.imp().remove_uniform_block(name)
+    }
+
+    /// Lists the uniforms the shaders use, sorted by name, except samplers
+    /// and images.
+    ///
+    /// # Note
+    ///
+    /// The list is empty until the widget is realized, and changes when the
+    /// programs are linked again, as notified by `uniforms-changed`.
+    pub fn list_uniforms(&self) -> Vec<UniformInfo> {
+        self.imp().list_uniforms()
+    }
+
+    /// Lists the samplers and images the shaders use, sorted by name, with
+    /// the texture unit the widget binds them to as their value.
+    pub fn list_samplers(&self) -> Vec<UniformInfo> {
+        self.imp().list_samplers()
+    }
+
+    /// Replaces the fragment shader at runtime.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The new GLSL fragment shader source code
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError`] if the widget is realized and the shader fails
+    /// to compile or link. The previous shader keeps running in that case.
+    ///
+    /// # Note
+    ///
+    /// Uniform locations and texture samplers are resolved again, and current
+    /// uniform values are kept. If the widget is not yet realized, the shader
+    /// is compiled once it is. With the ISF dialect, the render passes
+    /// declared by the previous header are replaced by those of the new one.
+    pub fn set_shader(&self, source: String) -> Result<(), glib::Error> {
+        self.imp().set_shader(source)
+    }
+
+    /// Replaces the vertex shader drawing the widget's shader, or restores
+    /// the fullscreen quad for `None`.
+    ///
+    /// A custom vertex shader gets `vertexId` (`gl_VertexID` as a `float`)
+    /// and the `vertexCount` uniform, and usually declares its own outputs
+    /// for the fragment shader. Set the `primitive` and `vertex-count`
+    /// properties to choose what is drawn. Render passes keep drawing the
+    /// fullscreen quad.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError`] if the widget is realized and the shaders
+    /// fail to compile or link. The previous vertex shader keeps running in
+    /// that case.
+    pub fn set_vertex_shader(&self, source: Option<String>) -> Result<(), glib::Error> {
+        self.imp().set_vertex_shader(source)
+    }
+
+    /// Sets the particle system drawn over the widget's shader, or removes
+    /// it for `None`.
+    ///
+    /// Every frame, the update shader computes the next state of each
+    /// particle, then each particle is drawn as an instanced quad by the
+    /// sprite shaders. Set the `particle-count` and `particle-blend`
+    /// properties to choose how many particles are drawn and how, and pass
+    /// emitter parameters as regular uniforms.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError`] if the widget is realized and the shaders
+    /// fail to compile or link. The previous particle system keeps running
+    /// in that case. If the widget isn't realized yet, errors are reported
+    /// through `compile-error` once it is, and the widget renders without
+    /// particles.
+    pub fn set_particles(&self, particles: Option<Particles>) -> Result<(), glib::Error> {
+        self.imp().set_particles(particles)
+    }
+
+    /// Loads an OBJ, glTF or GLB file and draws the widget's shader over its
+    /// triangles instead of the fullscreen quad.
+    ///
+    /// See [`ShaderArea::set_mesh`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GIOError` if the file can't be read, or a
+    /// [`ShaderError::Parse`] if it isn't a valid mesh. The previous mesh is
+    /// kept in that case.
+    pub fn load_mesh(&self, path: &Path) -> Result<(), glib::Error> {
+        self.imp().set_mesh(Some(Mesh::load(path)?));
+        Ok(())
+    }
+
+    /// Sets the mesh the widget's shader is drawn over, or restores the
+    /// fullscreen quad for `None`.
+    ///
+    /// Unless a custom vertex shader is set, the mesh is placed by the
+    /// `model_matrix`, `camera_matrix` and `projection_matrix` builtins, and
+    /// the fragment shader gets `uv`, `world_position` and `world_normal`.
+    /// Setting a mesh enables the `has-depth-buffer` property. Compile
+    /// errors are reported through `compile-error`.
+    pub fn set_mesh(&self, mesh: Option<Mesh>) {
+        self.imp().set_mesh(mesh);
+    }
+
+    /// Adds an offscreen render pass, rendered after the existing ones and
+    /// before the widget's own shader.
+    ///
+    /// The output of the pass is available to every shader as a `sampler2D`
+    /// uniform named after the pass.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError`] if the name is invalid or already used, or
+    /// if the widget is realized and the shader fails to compile or link.
+    pub fn add_pass(&self, pass: Pass) -> Result<(), glib::Error> {
+        self.imp().add_pass(pass)
+    }
+
+    /// Removes the render pass called `name`, returning whether it existed.
+    ///
+    /// If the shaders failed to compile, they are compiled again without it.
+    pub fn remove_pass(&self, name: &str) -> bool {
+        self.imp().remove_pass(name)
+    }
+
+    /// Adds a compute pass, dispatched after the existing ones and before
+    /// everything else is rendered.
+    ///
+    /// Compute shaders need OpenGL 4.3, which is checked when the widget is
+    /// realized: passes added before that are reported through
+    /// `compile-error` if the context turns out not to support them.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError`] if the name is invalid or already used, or
+    /// if the widget is realized and either doesn't support compute shaders
+    /// ([`ShaderError::Unsupported`]) or the shader fails to compile or link.
+    pub fn add_compute(&self, pass: ComputePass) -> Result<(), glib::Error> {
+        self.imp().add_compute(pass)
+    }
+
+    /// Removes the compute pass called `name`, returning whether it existed.
+    pub fn remove_compute(&self, name: &str) -> bool {
+        self.imp().remove_compute(name)
+    }
+
+    /// Dispatches a [`ComputeSchedule::OnDemand`] compute pass on the next
+    /// frame, returning whether the widget is realized with that pass.
+    pub fn run_compute(&self, name: &str) -> bool {
+        self.imp().run_compute(name)
+    }
+
+    /// Creates a shader storage buffer, or replaces the contents of the
+    /// buffer with the same name.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError`] if the buffer is invalid, or if the widget
+    /// is realized and doesn't support storage buffers.
+    pub fn set_storage_buffer(&self, buffer: StorageBuffer) -> Result<(), glib::Error> {
+        self.imp().set_storage_buffer(buffer)
+    }
+
+    /// Creates a storage image, or replaces the image with the same name.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError`] if the image is invalid, or if the widget
+    /// is realized and doesn't support storage images.
+    pub fn set_storage_image(&self, image: StorageImage) -> Result<(), glib::Error> {
+        self.imp().set_storage_image(image)
+    }
+
+    /// Reads back the contents of a storage buffer as of the last frame, or
+    /// `None` if the widget isn't realized with that buffer.
+    pub fn read_storage_buffer(&self, name: &str) -> Option<glib::Bytes> {
+        self.imp().read_storage_buffer(name)
+    }
+
+    /// Reads back the pixels of a storage image as of the last frame, or
+    /// `None` if the widget isn't realized with that image.
+    pub fn read_storage_image(&self, name: &str) -> Option<glib::Bytes> {
+        self.imp().read_storage_image(name)
+    }
+
+    /// Resets the orbit camera driven by the `orbit-camera` property.
+    pub fn reset_camera(&self) {
+        self.imp().reset_camera();
+    }
+
+    /// Resets the view transform driven by the `view-controls` property.
+    pub fn reset_view(&self) {
+        self.imp().reset_view();
+    }
 }
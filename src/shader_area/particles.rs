@@ -0,0 +1,319 @@
+//! Instanced GPU particles.
+//!
+//! The state of each particle (position, velocity, age…) lives in float
+//! textures, one texel per particle, laid out on a square grid. Every frame:
+//!
+//! 1. The update shader, a fragment shader run over the whole grid, reads
+//!    the current state through `particle_state0`… and writes the next one to
+//!    `particle_out0`…. Like passes, the state textures are ping-ponged.
+//! 2. After the widget's shader, every particle is drawn as an instanced
+//!    quad by the sprite vertex and fragment shaders, blended with the chosen
+//!    [`BlendMode`]. The vertex shader reads the state of its particle and
+//!    places the quad's corner.
+//!
+//! The state starts out zeroed, so spawning and respawning particles is up
+//! to the update shader, typically driven by emitter uniforms set by the
+//! application. The update shader and the sprite vertex shader both get:
+//!
+//! | Name               | GLSL Type   | Value                                       |
+//! | ------------------ | ----------- | ------------------------------------------- |
+//! | `particle_stateN`  | `sampler2D` | Current state, for each state buffer        |
+//! | `particle_count`   | `int`       | Number of particles                         |
+//! | `particle_index`   | `int`       | Index of the particle                       |
+//! | `particle_texel`   | `ivec2`     | Texel of the particle in the state textures |
+//!
+//! The sprite vertex shader also gets `particle_corner`, the corner of the
+//! quad from `vec2(0.0)` to `vec2(1.0)`. Other builtins and uniforms work as
+//! in the widget's shader, except that `resolution` is the size of the state
+//! grid in the update shader.
+
+use std::fmt::Write;
+
+use gtk::glib;
+
+use super::imp::append_user_source;
+
+/// Maximum number of state buffers, each an RGBA texture.
+pub const MAX_BUFFERS: u32 = 4;
+
+/// Name the particle shaders are reported under in compile errors, in place
+/// of a pass name.
+pub const NAME: &str = "particles";
+
+/// Shaders of a particle system.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Particles {
+    /// Fragment shader computing the next state of each particle, writing
+    /// to `particle_outN`
+    pub update: String,
+    /// Vertex shader placing the sprite of each particle
+    pub vertex: String,
+    /// Fragment shader shading the sprites
+    pub fragment: String,
+    /// Number of RGBA32F state buffers, from 1 to [`MAX_BUFFERS`]
+    pub buffers: u32,
+}
+
+impl Particles {
+    /// Creates a particle system with a single state buffer.
+    pub fn new(
+        update: impl Into<String>,
+        vertex: impl Into<String>,
+        fragment: impl Into<String>,
+    ) -> Self {
+        Self {
+            update: update.into(),
+            vertex: vertex.into(),
+            fragment: fragment.into(),
+            buffers: 1,
+        }
+    }
+
+    /// Returns the number of state buffers, clamped to the supported range.
+    pub fn buffer_count(&self) -> u32 {
+        self.buffers.clamp(1, MAX_BUFFERS)
+    }
+
+    /// Wraps the update shader, declaring the state samplers and outputs.
+    pub fn wrap_update(&self) -> String {
+        let mut shader = self.declarations();
+        for i in 0..self.buffer_count() {
+            let _ = writeln!(shader, "layout(location = {i}) out vec4 particle_out{i};");
+        }
+        shader.push_str(
+            "#define particle_texel ivec2(gl_FragCoord.xy)\n\
+             #define particle_index (particle_texel.y * textureSize(particle_state0, 0).x + particle_texel.x)\n",
+        );
+        append_user_source(&mut shader, &self.update);
+        shader
+    }
+
+    /// Wraps the sprite vertex shader, declaring the state samplers and the
+    /// per-instance inputs.
+    pub fn wrap_vertex(&self) -> String {
+        let mut shader = self.declarations();
+        shader.push_str(
+            "#define particle_index gl_InstanceID\n\
+             #define particle_texel ivec2(gl_InstanceID % textureSize(particle_state0, 0).x, \
+             gl_InstanceID / textureSize(particle_state0, 0).x)\n\
+             #define particle_corner vec2(float(gl_VertexID & 1), float(gl_VertexID >> 1))\n",
+        );
+        append_user_source(&mut shader, &self.vertex);
+        shader
+    }
+
+    /// Returns the declarations shared by the update and vertex shaders.
+    fn declarations(&self) -> String {
+        let mut shader = String::from("uniform int particle_count;\n");
+        for i in 0..self.buffer_count() {
+            let _ = writeln!(shader, "uniform sampler2D particle_state{i};");
+        }
+        shader
+    }
+}
+
+/// How particle sprites are blended over the widget's shader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, glib::Enum)]
+#[enum_type(name = "GtkGlShadersBlendMode")]
+pub enum BlendMode {
+    /// Sprites replace what is below them
+    None,
+    /// Sprites are blended by their alpha
+    #[default]
+    Alpha,
+    /// Sprites have premultiplied alpha
+    Premultiplied,
+    /// Sprites add to what is below them, for glowing effects
+    Additive,
+}
+
+impl BlendMode {
+    /// Enables blending with the source and destination factors of the mode.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    pub unsafe fn enable(self) {
+        let (source, destination) = match self {
+            Self::None => return,
+            Self::Alpha => (epoxy::SRC_ALPHA, epoxy::ONE_MINUS_SRC_ALPHA),
+            Self::Premultiplied => (epoxy::ONE, epoxy::ONE_MINUS_SRC_ALPHA),
+            Self::Additive => (epoxy::SRC_ALPHA, epoxy::ONE),
+        };
+        unsafe {
+            epoxy::Enable(epoxy::BLEND);
+            epoxy::BlendFunc(source, destination);
+        }
+    }
+}
+
+/// Returns the size of the state grid for `count` particles.
+pub fn grid_size(count: u32) -> [i32; 2] {
+    let width = f64::from(count).sqrt().ceil().max(1.0) as u32;
+    let height = count.div_ceil(width).max(1);
+    [width as i32, height as i32]
+}
+
+/// The framebuffer and ping-pong state textures of a particle system.
+#[derive(Debug, Default)]
+pub struct ParticleTarget {
+    /// Framebuffer the update shader renders into
+    framebuffer: u32,
+    /// The two textures of each state buffer, swapped after every update
+    textures: Vec<[u32; 2]>,
+    /// Index of the textures holding the current state
+    current: usize,
+    /// Size of the state grid, `[0, 0]` until allocated
+    size: [i32; 2],
+}
+
+impl ParticleTarget {
+    /// Creates the framebuffer and textures. Storage is allocated by
+    /// [`Self::resize`].
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    pub unsafe fn new(buffers: u32) -> Self {
+        let mut target = Self {
+            textures: vec![[0; 2]; buffers as usize],
+            ..Self::default()
+        };
+        unsafe {
+            epoxy::GenFramebuffers(1, &raw mut target.framebuffer);
+            for textures in &mut target.textures {
+                epoxy::GenTextures(2, textures.as_mut_ptr());
+            }
+        }
+        target
+    }
+
+    /// Returns the textures holding the current state, one per buffer.
+    pub fn outputs(&self) -> impl Iterator<Item = u32> {
+        self.textures.iter().map(|textures| textures[self.current])
+    }
+
+    /// Returns the number of state buffers.
+    pub fn buffers(&self) -> usize {
+        self.textures.len()
+    }
+
+    /// Returns the size of the state grid.
+    pub fn size(&self) -> [i32; 2] {
+        self.size
+    }
+
+    /// Allocates the textures if the size of the grid changed, resetting
+    /// every particle to zero.
+    ///
+    /// The caller is responsible for binding the widget's framebuffer again.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    pub unsafe fn resize(&mut self, size: [i32; 2]) {
+        if self.size == size {
+            return;
+        }
+        self.size = size;
+
+        unsafe {
+            epoxy::BindFramebuffer(epoxy::FRAMEBUFFER, self.framebuffer);
+            // Each texture is checked and cleared on its own through the
+            // first attachment
+            for i in 1..self.textures.len() as u32 {
+                epoxy::FramebufferTexture2D(
+                    epoxy::FRAMEBUFFER,
+                    epoxy::COLOR_ATTACHMENT0 + i,
+                    epoxy::TEXTURE_2D,
+                    0,
+                    0,
+                );
+            }
+            let attachment = epoxy::COLOR_ATTACHMENT0;
+            epoxy::DrawBuffers(1, &raw const attachment);
+            for &texture in self.textures.iter().flatten() {
+                epoxy::BindTexture(epoxy::TEXTURE_2D, texture);
+                // Particles read exact texels
+                super::imp::ShaderArea::set_texture_parameters(epoxy::NEAREST);
+                epoxy::TexImage2D(
+                    epoxy::TEXTURE_2D,
+                    0,
+                    epoxy::RGBA32F as i32,
+                    size[0],
+                    size[1],
+                    0,
+                    epoxy::RGBA,
+                    epoxy::FLOAT,
+                    std::ptr::null(),
+                );
+
+                epoxy::FramebufferTexture2D(
+                    epoxy::FRAMEBUFFER,
+                    epoxy::COLOR_ATTACHMENT0,
+                    epoxy::TEXTURE_2D,
+                    texture,
+                    0,
+                );
+                // Float formats aren't color-renderable on every OpenGL ES
+                // implementation
+                if epoxy::CheckFramebufferStatus(epoxy::FRAMEBUFFER) != epoxy::FRAMEBUFFER_COMPLETE
+                {
+                    log::error!("Particle state can't be stored in RGBA32F textures");
+                }
+                epoxy::ClearColor(0.0, 0.0, 0.0, 0.0);
+                epoxy::Clear(epoxy::COLOR_BUFFER_BIT);
+            }
+        }
+    }
+
+    /// Binds the framebuffer for rendering the next state into every buffer,
+    /// and sets the viewport to the size of the grid.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    pub unsafe fn bind(&self) {
+        let attachments: Vec<u32> = (0..self.textures.len() as u32)
+            .map(|i| epoxy::COLOR_ATTACHMENT0 + i)
+            .collect();
+        unsafe {
+            epoxy::BindFramebuffer(epoxy::FRAMEBUFFER, self.framebuffer);
+            for (attachment, textures) in attachments.iter().zip(&self.textures) {
+                epoxy::FramebufferTexture2D(
+                    epoxy::FRAMEBUFFER,
+                    *attachment,
+                    epoxy::TEXTURE_2D,
+                    textures[1 - self.current],
+                    0,
+                );
+            }
+            epoxy::DrawBuffers(attachments.len() as i32, attachments.as_ptr());
+            epoxy::Viewport(0, 0, self.size[0], self.size[1]);
+        }
+    }
+
+    /// Makes the state rendered by the last [`Self::bind`] the current one.
+    pub fn swap(&mut self) {
+        self.current = 1 - self.current;
+    }
+
+    /// Deletes the framebuffer and textures.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    pub unsafe fn delete(&self) {
+        unsafe {
+            epoxy::DeleteFramebuffers(1, &raw const self.framebuffer);
+            for textures in &self.textures {
+                epoxy::DeleteTextures(2, textures.as_ptr());
+            }
+        }
+    }
+}
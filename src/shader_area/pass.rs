@@ -0,0 +1,301 @@
+//! Offscreen render passes.
+//!
+//! Passes are rendered in order before the widget's own shader, each into its
+//! own texture. Every program can sample the output of a pass through a
+//! `sampler2D` uniform named after it: passes that already ran this frame
+//! give their new output, while the pass itself and the ones after it give
+//! their output from the previous frame. Each pass keeps two textures and
+//! swaps them after rendering, so that it can read its previous frame while
+//! writing the next one.
+
+use gtk::glib;
+
+use super::ShaderError;
+
+/// An offscreen render pass.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pass {
+    /// Name of the pass, used as the name of its sampler uniform
+    pub name: String,
+    /// GLSL fragment shader source code, with the same inputs as the
+    /// widget's shader
+    pub shader: String,
+    /// Size of the output texture
+    pub size: PassSize,
+    /// Pixel format of the output texture
+    pub format: PassFormat,
+    /// Filtering used when sampling the output texture
+    pub filter: PassFilter,
+}
+
+impl Pass {
+    /// Creates a pass the size of the widget, with an RGBA8 output and
+    /// linear filtering.
+    pub fn new(name: impl Into<String>, shader: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            shader: shader.into(),
+            size: PassSize::Scale(1.0),
+            format: PassFormat::Rgba8,
+            filter: PassFilter::Linear,
+        }
+    }
+
+    /// Checks that the name of the pass is a valid GLSL identifier that isn't
+    /// used by another pass.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ShaderError::Pass`] otherwise.
+    pub fn validate<'a>(
+        &self,
+        others: impl IntoIterator<Item = &'a Pass>,
+    ) -> Result<(), glib::Error> {
+        if !is_identifier(&self.name) {
+            return Err(glib::Error::new(
+                ShaderError::Pass,
+                &format!("Invalid pass name `{}`", self.name),
+            ));
+        }
+
+        if others.into_iter().any(|other| other.name == self.name) {
+            return Err(glib::Error::new(
+                ShaderError::Pass,
+                &format!("A pass named `{}` already exists", self.name),
+            ));
+        }
+
+        if let PassSize::Fixed(0, _) | PassSize::Fixed(_, 0) = self.size {
+            return Err(glib::Error::new(
+                ShaderError::Pass,
+                &format!("Pass `{}` has an empty size", self.name),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns whether `name` is a valid GLSL identifier, not reserved by
+/// OpenGL.
+pub fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !name.starts_with("gl_")
+}
+
+/// Size of the output texture of a pass.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PassSize {
+    /// A fraction of the widget's framebuffer size
+    Scale(f32),
+    /// A fixed size in pixels
+    Fixed(u32, u32),
+}
+
+impl PassSize {
+    /// Returns the size in pixels for a widget framebuffer of `viewport`
+    /// pixels.
+    pub fn resolve(self, viewport: [i32; 2]) -> [i32; 2] {
+        match self {
+            Self::Scale(scale) => viewport.map(|v| ((v as f32 * scale).round() as i32).max(1)),
+            Self::Fixed(width, height) => [width as i32, height as i32],
+        }
+    }
+}
+
+/// Pixel format of the output texture of a pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, glib::Enum)]
+#[enum_type(name = "GtkGlShadersPassFormat")]
+pub enum PassFormat {
+    /// 8-bit normalized channels
+    #[default]
+    Rgba8,
+    /// 16-bit floating point channels
+    Rgba16F,
+    /// 32-bit floating point channels
+    Rgba32F,
+}
+
+impl PassFormat {
+    /// Returns the internal format and the pixel type of the format.
+    pub fn gl(self) -> (u32, u32) {
+        match self {
+            Self::Rgba8 => (epoxy::RGBA8, epoxy::UNSIGNED_BYTE),
+            Self::Rgba16F => (epoxy::RGBA16F, epoxy::HALF_FLOAT),
+            Self::Rgba32F => (epoxy::RGBA32F, epoxy::FLOAT),
+        }
+    }
+
+    /// Returns the size of a pixel in bytes.
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            Self::Rgba8 => 4,
+            Self::Rgba16F => 8,
+            Self::Rgba32F => 16,
+        }
+    }
+}
+
+/// Filtering used when sampling the output texture of a pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, glib::Enum)]
+#[enum_type(name = "GtkGlShadersPassFilter")]
+pub enum PassFilter {
+    /// Linear interpolation between texels
+    #[default]
+    Linear,
+    /// Nearest texel, for simulations that read exact cells
+    Nearest,
+}
+
+impl PassFilter {
+    /// Returns the OpenGL filter.
+    fn gl(self) -> u32 {
+        match self {
+            Self::Linear => epoxy::LINEAR,
+            Self::Nearest => epoxy::NEAREST,
+        }
+    }
+}
+
+/// The framebuffer and ping-pong textures of a pass.
+#[derive(Debug, Default)]
+pub struct PassTarget {
+    /// Framebuffer the pass renders into
+    framebuffer: u32,
+    /// The two textures, swapped after every frame
+    textures: [u32; 2],
+    /// Index of the texture holding the latest output
+    current: usize,
+    /// Size of the textures, `[0, 0]` until allocated
+    size: [i32; 2],
+}
+
+impl PassTarget {
+    /// Creates the framebuffer and textures. Storage is allocated by
+    /// [`Self::resize`].
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    pub unsafe fn new() -> Self {
+        let mut target = Self::default();
+        unsafe {
+            epoxy::GenFramebuffers(1, &raw mut target.framebuffer);
+            epoxy::GenTextures(2, target.textures.as_mut_ptr());
+        }
+        target
+    }
+
+    /// Returns the texture holding the latest output.
+    pub fn output(&self) -> u32 {
+        self.textures[self.current]
+    }
+
+    /// Returns the size of the textures.
+    pub fn size(&self) -> [i32; 2] {
+        self.size
+    }
+
+    /// Allocates the textures if their size changed, clearing their
+    /// contents.
+    ///
+    /// The caller is responsible for binding the widget's framebuffer again.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    pub unsafe fn resize(
+        &mut self,
+        name: &str,
+        size: [i32; 2],
+        format: PassFormat,
+        filter: PassFilter,
+    ) {
+        if self.size == size {
+            return;
+        }
+        self.size = size;
+
+        let (internal_format, pixel_type) = format.gl();
+        unsafe {
+            epoxy::BindFramebuffer(epoxy::FRAMEBUFFER, self.framebuffer);
+            for &texture in &self.textures {
+                epoxy::BindTexture(epoxy::TEXTURE_2D, texture);
+                super::imp::ShaderArea::set_texture_parameters(filter.gl());
+                epoxy::TexImage2D(
+                    epoxy::TEXTURE_2D,
+                    0,
+                    internal_format as i32,
+                    size[0],
+                    size[1],
+                    0,
+                    epoxy::RGBA,
+                    pixel_type,
+                    std::ptr::null(),
+                );
+
+                epoxy::FramebufferTexture2D(
+                    epoxy::FRAMEBUFFER,
+                    epoxy::COLOR_ATTACHMENT0,
+                    epoxy::TEXTURE_2D,
+                    texture,
+                    0,
+                );
+                // Float formats aren't color-renderable on every OpenGL ES
+                // implementation
+                if epoxy::CheckFramebufferStatus(epoxy::FRAMEBUFFER) != epoxy::FRAMEBUFFER_COMPLETE
+                {
+                    log::error!("Pass `{name}` can't render to a {format:?} texture");
+                }
+                epoxy::ClearColor(0.0, 0.0, 0.0, 0.0);
+                epoxy::Clear(epoxy::COLOR_BUFFER_BIT);
+            }
+        }
+    }
+
+    /// Binds the framebuffer for rendering into the texture that doesn't
+    /// hold the latest output, and sets the viewport to its size.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    pub unsafe fn bind(&self) {
+        unsafe {
+            epoxy::BindFramebuffer(epoxy::FRAMEBUFFER, self.framebuffer);
+            epoxy::FramebufferTexture2D(
+                epoxy::FRAMEBUFFER,
+                epoxy::COLOR_ATTACHMENT0,
+                epoxy::TEXTURE_2D,
+                self.textures[1 - self.current],
+                0,
+            );
+            epoxy::Viewport(0, 0, self.size[0], self.size[1]);
+        }
+    }
+
+    /// Makes the texture rendered by the last [`Self::bind`] the latest
+    /// output.
+    pub fn swap(&mut self) {
+        self.current = 1 - self.current;
+    }
+
+    /// Deletes the framebuffer and textures.
+    ///
+    /// # Safety
+    ///
+    /// This function calls unsafe OpenGL functions. An active OpenGL context
+    /// must be bound before calling.
+    pub unsafe fn delete(&self) {
+        unsafe {
+            epoxy::DeleteFramebuffers(1, &raw const self.framebuffer);
+            epoxy::DeleteTextures(2, self.textures.as_ptr());
+        }
+    }
+}
@@ -0,0 +1,456 @@
+//! Shadertoy compatibility.
+//!
+//! Shadertoy shaders define `void mainImage(out vec4, in vec2)` and read
+//! `iTime`, `iResolution`, `iChannel0`… instead of declaring their inputs.
+//! [`wrap`] adds the declarations and a `main` calling `mainImage`, while the
+//! `i*` uniforms are filled as builtins. [`parse_export`] turns a Shadertoy
+//! JSON export into the widget's shader, textures and render passes.
+
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+use gtk::glib;
+use log::warn;
+
+use super::{
+    ShaderError,
+    imp::append_user_source,
+    json::Json,
+    pass::{Pass, PassFilter, PassFormat},
+};
+
+/// Number of `iChannelN` inputs.
+pub const CHANNELS: usize = 4;
+
+/// Sampler bound to each `iChannelN`, by uniform name (`tex0`, `keyboard`,
+/// the name of a pass…), or `None` when unbound.
+pub type Channels = [Option<String>; CHANNELS];
+
+/// Wraps a Shadertoy shader into a complete fragment shader.
+///
+/// # Arguments
+///
+/// * `source` - The Shadertoy code, defining `mainImage`
+/// * `channels` - The sampler bound to each `iChannelN`
+/// * `image` - Whether this is the image pass, whose alpha is ignored like on
+///   Shadertoy
+pub fn wrap(source: &str, channels: &Channels, image: bool) -> String {
+    let mut shader = String::from(
+        "out vec4 shadertoy_out_color;\n\
+         uniform vec3 iResolution;\n\
+         uniform float iTime;\n\
+         uniform float iTimeDelta;\n\
+         uniform float iFrameRate;\n\
+         uniform int iFrame;\n\
+         uniform vec4 iMouse;\n\
+         uniform vec4 iDate;\n\
+         uniform float iChannelTime[4];\n\
+         const float iSampleRate = 44100.0;\n",
+    );
+
+    let mut declared: Vec<&str> = Vec::new();
+    for (i, sampler) in channels.iter().enumerate() {
+        match sampler {
+            Some(name) => {
+                if !declared.contains(&name.as_str()) {
+                    let _ = writeln!(shader, "uniform sampler2D {name};");
+                    declared.push(name);
+                }
+                let _ = writeln!(shader, "#define iChannel{i} {name}");
+            }
+            None => {
+                let _ = writeln!(shader, "uniform sampler2D iChannel{i};");
+            }
+        }
+    }
+
+    // Filled by `main`, as the samplers can't be queried from a global
+    // initializer
+    shader.push_str("vec3 iChannelResolution[4];\n");
+
+    append_user_source(&mut shader, source);
+    shader.push_str("\n\nvoid main() {\n");
+    for i in 0..CHANNELS {
+        let _ = writeln!(
+            shader,
+            "    iChannelResolution[{i}] = vec3(vec2(textureSize(iChannel{i}, 0)), 1.0);"
+        );
+    }
+    shader.push_str("    mainImage(shadertoy_out_color, gl_FragCoord.xy);\n");
+    if image {
+        shader.push_str("    shadertoy_out_color.a = 1.0;\n");
+    }
+    shader.push_str("}\n");
+    shader
+}
+
+/// Returns the default channels: `iChannelN` bound to `texN` for each of
+/// the `texture_count` textures.
+pub fn default_channels(texture_count: usize) -> Channels {
+    std::array::from_fn(|i| (i < texture_count).then(|| format!("tex{i}")))
+}
+
+/// A Shadertoy shader loaded from a JSON export.
+#[derive(Debug, Default)]
+pub struct Project {
+    /// Code of the image pass, with the common code prepended (see
+    /// [`with_common`])
+    pub image: String,
+    /// Channels of the image pass
+    pub image_channels: Channels,
+    /// Buffer passes, in rendering order, with their channels
+    pub buffers: Vec<(Pass, Channels)>,
+    /// Texture files used by the channels, as `tex0`, `tex1`, …
+    pub textures: Vec<PathBuf>,
+    /// Whether a channel reads the keyboard
+    pub keyboard: bool,
+}
+
+/// Parses a Shadertoy JSON export.
+///
+/// Both the format of the Shadertoy API (`{"Shader": {…}}`) and the export
+/// of the website (`[{…}]`) are accepted. Buffers A to D become passes named
+/// `buffer_a` to `buffer_d`. Textures are looked up by file name in
+/// `media_dir`; channels whose texture isn't found, and inputs that aren't
+/// textures, buffers or the keyboard (cubemaps, video, sound…), are left
+/// unbound with a warning.
+///
+/// # Errors
+///
+/// Returns a [`ShaderError::Parse`] if the export is malformed or has no
+/// image pass.
+pub fn parse_export(json: &str, media_dir: Option<&Path>) -> Result<Project, glib::Error> {
+    let parse_error = |message: &str| {
+        glib::Error::new(
+            ShaderError::Parse,
+            &format!("Invalid Shadertoy export: {message}"),
+        )
+    };
+
+    let root = Json::parse(json).map_err(|e| parse_error(&e.to_string()))?;
+    let shader = match &root {
+        Json::Array(shaders) => shaders.first().ok_or_else(|| parse_error("no shader"))?,
+        root => root.get("Shader").unwrap_or(root),
+    };
+    let passes = shader
+        .get("renderpass")
+        .map(Json::elements)
+        .unwrap_or_default();
+
+    let kind = |pass: &Json| {
+        pass.get("type")
+            .and_then(Json::as_str)
+            .unwrap_or_default()
+            .to_owned()
+    };
+    let code = |pass: &Json| {
+        pass.get("code")
+            .and_then(Json::as_str)
+            .unwrap_or_default()
+            .to_owned()
+    };
+
+    let common: String = passes
+        .iter()
+        .filter(|pass| kind(pass) == "common")
+        .map(|pass| code(pass) + "\n")
+        .collect();
+
+    // Buffers render in alphabetical order, A to D
+    let mut buffers: Vec<&Json> = passes
+        .iter()
+        .filter(|pass| kind(pass) == "buffer")
+        .collect();
+    let pass_name = |pass: &Json| {
+        pass.get("name")
+            .and_then(Json::as_str)
+            .unwrap_or_default()
+            .to_owned()
+    };
+    buffers.sort_by_key(|pass| pass_name(pass));
+
+    let buffer_names: Vec<String> = buffers
+        .iter()
+        .map(|pass| sampler_name(&pass_name(pass)))
+        .collect();
+    let buffer_ids: Vec<Option<String>> = buffers
+        .iter()
+        .map(|pass| {
+            let output = pass.get("outputs")?.elements().first()?;
+            id_string(output.get("id")?)
+        })
+        .collect();
+
+    let mut project = Project::default();
+    let mut filters = vec![PassFilter::Linear; buffers.len()];
+
+    let mut resolve_channels = |pass: &Json, project: &mut Project| -> Channels {
+        let mut channels = Channels::default();
+        for input in pass.get("inputs").map(Json::elements).unwrap_or_default() {
+            let Some(channel) = input
+                .get("channel")
+                .and_then(Json::as_f64)
+                .map(|c| c as usize)
+                .filter(|&c| c < CHANNELS)
+            else {
+                continue;
+            };
+            let ctype = input
+                .get("type")
+                .or_else(|| input.get("ctype"))
+                .and_then(Json::as_str)
+                .unwrap_or_default();
+            let src = input
+                .get("filepath")
+                .or_else(|| input.get("src"))
+                .and_then(Json::as_str)
+                .unwrap_or_default();
+
+            channels[channel] = match ctype {
+                "buffer" => {
+                    let id = input.get("id").and_then(id_string);
+                    // Older exports refer to buffers by placeholder image
+                    let index = buffer_ids
+                        .iter()
+                        .position(|buffer_id| buffer_id.is_some() && *buffer_id == id)
+                        .or_else(|| {
+                            (0..CHANNELS).find(|i| src.ends_with(&format!("buffer0{i}.png")))
+                        })
+                        .filter(|&i| i < buffers.len());
+                    index.map(|i| {
+                        let filter = input
+                            .get("sampler")
+                            .and_then(|s| s.get("filter"))
+                            .and_then(Json::as_str);
+                        if filter == Some("nearest") {
+                            filters[i] = PassFilter::Nearest;
+                        }
+                        buffer_names[i].clone()
+                    })
+                }
+                "keyboard" => {
+                    project.keyboard = true;
+                    Some("keyboard".to_owned())
+                }
+                "texture" => {
+                    let file = Path::new(src).file_name();
+                    let path = media_dir.zip(file).map(|(dir, file)| dir.join(file));
+                    match path.filter(|path| path.is_file()) {
+                        Some(path) => {
+                            let index = project
+                                .textures
+                                .iter()
+                                .position(|p| *p == path)
+                                .unwrap_or_else(|| {
+                                    project.textures.push(path);
+                                    project.textures.len() - 1
+                                });
+                            Some(format!("tex{index}"))
+                        }
+                        None => {
+                            warn!("Shadertoy texture not found: {src}");
+                            None
+                        }
+                    }
+                }
+                other => {
+                    warn!("Unsupported Shadertoy input on channel {channel}: {other}");
+                    None
+                }
+            };
+        }
+        channels
+    };
+
+    let image = passes
+        .iter()
+        .find(|pass| kind(pass) == "image")
+        .ok_or_else(|| parse_error("no image pass"))?;
+    project.image = with_common(&common, &code(image));
+    project.image_channels = resolve_channels(image, &mut project);
+
+    let buffer_channels: Vec<Channels> = buffers
+        .iter()
+        .map(|pass| resolve_channels(pass, &mut project))
+        .collect();
+    project.buffers = buffers
+        .iter()
+        .zip(buffer_names)
+        .zip(buffer_channels)
+        .zip(filters)
+        .map(|(((pass, name), channels), filter)| {
+            let mut buffer = Pass::new(name, with_common(&common, &code(pass)));
+            // Shadertoy buffers hold floating point values
+            buffer.format = PassFormat::Rgba32F;
+            buffer.filter = filter;
+            (buffer, channels)
+        })
+        .collect();
+
+    Ok(project)
+}
+
+/// Prepends the code of the Common tab to that of a pass.
+///
+/// The line numbers restart at the pass's code, so that compile errors point
+/// into its own tab rather than past the common code.
+fn with_common(common: &str, code: &str) -> String {
+    if common.is_empty() {
+        code.to_owned()
+    } else {
+        let mut source = common.to_owned();
+        append_user_source(&mut source, code);
+        source
+    }
+}
+
+/// Returns the id of a pass output as a string, as exports use both strings
+/// and numbers.
+fn id_string(id: &Json) -> Option<String> {
+    match id {
+        Json::String(s) => Some(s.clone()),
+        Json::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Turns a pass name such as `Buffer A` into a sampler name (`buffer_a`).
+fn sampler_name(name: &str) -> String {
+    let name: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        name
+    } else {
+        format!("buffer_{name}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_error(json: &str) -> String {
+        let e = parse_export(json, None).unwrap_err();
+        assert!(e.matches(ShaderError::Parse));
+        e.message().to_owned()
+    }
+
+    #[test]
+    fn parses_api_export() {
+        let project = parse_export(
+            r#"{"Shader": {"renderpass": [
+                {"type": "image", "code": "image();", "inputs": [
+                    {"channel": 0, "type": "buffer", "id": "257"},
+                    {"channel": 1, "type": "keyboard"},
+                    {"channel": 2, "type": "buffer", "id": 258,
+                     "sampler": {"filter": "nearest"}}
+                ]},
+                {"type": "common", "code": "common();"},
+                {"type": "buffer", "name": "Buffer B", "code": "b();",
+                 "outputs": [{"id": 258}]},
+                {"type": "buffer", "name": "Buffer A", "code": "a();",
+                 "outputs": [{"id": "257"}],
+                 "inputs": [{"channel": 0, "type": "buffer", "id": "257"}]}
+            ]}}"#,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(project.image, "common();\n#line 1\nimage();");
+        assert_eq!(
+            project.image_channels,
+            [
+                Some("buffer_a".to_owned()),
+                Some("keyboard".to_owned()),
+                Some("buffer_b".to_owned()),
+                None
+            ]
+        );
+        assert!(project.keyboard);
+
+        // Buffers are sorted by name, and keep reading themselves
+        let names: Vec<&str> = project
+            .buffers
+            .iter()
+            .map(|(p, _)| p.name.as_str())
+            .collect();
+        assert_eq!(names, ["buffer_a", "buffer_b"]);
+        let (a, a_channels) = &project.buffers[0];
+        assert_eq!(a.shader, "common();\n#line 1\na();");
+        assert_eq!(a.format, PassFormat::Rgba32F);
+        assert_eq!(a.filter, PassFilter::Linear);
+        assert_eq!(a_channels[0].as_deref(), Some("buffer_a"));
+        assert_eq!(project.buffers[1].0.filter, PassFilter::Nearest);
+    }
+
+    #[test]
+    fn parses_website_export() {
+        let project = parse_export(
+            r#"[{"renderpass": [{"type": "image", "code": "x", "inputs": [
+                {"channel": 0, "ctype": "buffer", "src": "/media/previz/buffer00.png"},
+                {"channel": 1, "ctype": "cubemap", "src": "/media/a/cube.png"},
+                {"channel": 7, "ctype": "keyboard"}
+            ]}]}]"#,
+            None,
+        )
+        .unwrap();
+        // Without common code, nothing is prepended
+        assert_eq!(project.image, "x");
+        // No buffer pass to bind, nor unsupported inputs
+        assert_eq!(project.image_channels, Channels::default());
+        assert!(!project.keyboard);
+        assert!(project.buffers.is_empty());
+    }
+
+    #[test]
+    fn finds_textures_in_media_dir() {
+        let dir = std::env::temp_dir().join(format!("shadertoy-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("noise.png"), b"").unwrap();
+
+        let project = parse_export(
+            r#"{"renderpass": [{"type": "image", "code": "", "inputs": [
+                {"channel": 0, "type": "texture", "filepath": "/media/a/noise.png"},
+                {"channel": 1, "type": "texture", "filepath": "/media/a/missing.png"},
+                {"channel": 3, "type": "texture", "filepath": "/media/b/noise.png"}
+            ]}]}"#,
+            Some(&dir),
+        )
+        .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(project.textures, [dir.join("noise.png")]);
+        assert_eq!(
+            project.image_channels,
+            [Some("tex0".to_owned()), None, None, Some("tex0".to_owned())]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_exports() {
+        assert!(parse_error("{").starts_with("Invalid Shadertoy export: "));
+        assert!(parse_error("[]").ends_with("no shader"));
+        assert!(parse_error(r#"{"renderpass": []}"#).ends_with("no image pass"));
+        assert!(parse_error(r#"{"renderpass": [{"type": "buffer"}]}"#).ends_with("no image pass"));
+        assert!(parse_error(r#""shader""#).ends_with("no image pass"));
+    }
+
+    #[test]
+    fn names_samplers() {
+        assert_eq!(sampler_name("Buffer A"), "buffer_a");
+        assert_eq!(sampler_name(" Cube-Map "), "cube_map");
+        assert_eq!(sampler_name("2nd"), "buffer_2nd");
+        assert_eq!(sampler_name(""), "buffer_");
+    }
+}
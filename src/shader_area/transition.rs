@@ -0,0 +1,145 @@
+//! gl-transitions compatibility.
+//!
+//! Transitions from the [gl-transitions](https://gl-transitions.com)
+//! collection define `vec4 transition(vec2 uv)`, reading both images through
+//! `getFromColor` and `getToColor` and the `progress` and `ratio` inputs.
+//! [`wrap`] provides these on top of the textures `tex0` (from) and `tex1`
+//! (to), while the widget drives `progress` over time.
+
+use gtk::glib;
+
+use super::{Uniform, imp::append_user_source};
+
+/// Easing curve applied to the progress of a transition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, glib::Enum)]
+#[enum_type(name = "GtkGlShadersEasing")]
+pub enum Easing {
+    /// Constant speed
+    #[default]
+    Linear,
+    /// Starts slowly and accelerates (cubic)
+    EaseIn,
+    /// Starts quickly and decelerates (cubic)
+    EaseOut,
+    /// Accelerates, then decelerates (cubic)
+    EaseInOut,
+}
+
+impl Easing {
+    /// Maps a linear progress from 0 to 1 onto the curve.
+    pub fn apply(self, t: f64) -> f64 {
+        match self {
+            Self::Linear => t,
+            Self::EaseIn => t * t * t,
+            Self::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Self::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
+/// Wraps a gl-transitions transition into a complete fragment shader.
+pub fn wrap(source: &str) -> String {
+    let mut shader = String::from(
+        "in vec2 uv;\n\
+         out vec4 transition_out_color;\n\
+         uniform sampler2D tex0;\n\
+         uniform sampler2D tex1;\n\
+         uniform float progress;\n\
+         uniform vec2 resolution;\n\
+         float ratio;\n\
+         vec4 getFromColor(vec2 p) { return texture(tex0, p); }\n\
+         vec4 getToColor(vec2 p) { return texture(tex1, p); }\n",
+    );
+
+    append_user_source(&mut shader, source);
+    shader.push_str(
+        "\n\n\
+         void main() {\n\
+         \x20   ratio = resolution.x / resolution.y;\n\
+         \x20   transition_out_color = transition(uv);\n\
+         }\n",
+    );
+    shader
+}
+
+/// Parses the default values of the parameters of a transition.
+///
+/// gl-transitions declare them in a comment after the uniform, as in
+/// `uniform vec2 direction; // = vec2(0.0, 1.0)`. Parameters of types other
+/// than `float`, `int`, `bool` and their vectors are skipped.
+pub fn parse_defaults(source: &str) -> Vec<(String, Uniform)> {
+    source
+        .lines()
+        .filter_map(|line| {
+            let (declaration, comment) = line.split_once("//")?;
+            let value = comment.trim().strip_prefix('=')?.trim();
+            let declaration = declaration.trim().strip_prefix("uniform")?;
+            let mut words = declaration
+                .trim_end()
+                .trim_end_matches(';')
+                .split_whitespace();
+            let ty = words.next()?;
+            let name = words.next()?;
+            if words.next().is_some() {
+                return None;
+            }
+            Some((name.to_owned(), parse_value(ty, value)?))
+        })
+        .collect()
+}
+
+/// Parses a GLSL literal such as `0.5`, `true` or `vec2(0.0, 1.0)` as a
+/// value of type `ty`.
+fn parse_value(ty: &str, value: &str) -> Option<Uniform> {
+    let value = value.trim_end_matches(';').trim();
+    let arguments = match value.strip_prefix(ty) {
+        Some(rest) => rest.trim().strip_prefix('(')?.strip_suffix(')')?,
+        None => value,
+    };
+    let numbers: Vec<f64> = arguments
+        .split(',')
+        .map(|n| match n.trim() {
+            "true" => Some(1.0),
+            "false" => Some(0.0),
+            n => n.parse().ok(),
+        })
+        .collect::<Option<_>>()?;
+
+    let size = match ty {
+        "float" | "int" | "bool" => 1,
+        "vec2" | "ivec2" => 2,
+        "vec3" | "ivec3" => 3,
+        "vec4" | "ivec4" => 4,
+        _ => return None,
+    };
+    // `vec2(0.5)` sets every component
+    let component = |i: usize| {
+        if numbers.len() == 1 {
+            numbers[0]
+        } else {
+            numbers[i]
+        }
+    };
+    if numbers.len() != 1 && numbers.len() != size {
+        return None;
+    }
+    let floats = |n| -> Vec<f32> { (0..n).map(|i| component(i) as f32).collect() };
+    let ints = |n| -> Vec<i32> { (0..n).map(|i| component(i) as i32).collect() };
+
+    Some(match ty {
+        "float" => Uniform::Float(component(0) as f32),
+        "int" | "bool" => Uniform::Int(component(0) as i32),
+        "vec2" => Uniform::Vec2(floats(2).try_into().ok()?),
+        "vec3" => Uniform::Vec3(floats(3).try_into().ok()?),
+        "vec4" => Uniform::Vec4(floats(4).try_into().ok()?),
+        "ivec2" => Uniform::IVec2(ints(2).try_into().ok()?),
+        "ivec3" => Uniform::IVec3(ints(3).try_into().ok()?),
+        _ => Uniform::IVec4(ints(4).try_into().ok()?),
+    })
+}
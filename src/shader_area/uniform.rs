@@ -0,0 +1,216 @@
+//! Uniform arrays and double-precision uniforms.
+//!
+//! An array uniform such as `uniform vec3 colors[8];` is set as a whole
+//! from one of the array variants of [`Uniform`], whose elements fill the
+//! array from its first element. Arrays can be built from their packed
+//! components, as given by a `GBytes` or a JS typed array, with
+//! [`Uniform::array_from_bytes`].
+//!
+//! The number of elements is checked against the size the driver reports
+//! for the active uniform: arrays with extra elements are rejected, while
+//! missing ones keep their previous value.
+//!
+//! `double` and `dvecN` uniforms need OpenGL 4.0 or the
+//! `GL_ARB_gpu_shader_fp64` extension. Like every value, doubles are
+//! converted to the type the shader declares (see [`super::active`]).
+
+use std::ffi::CStr;
+
+use gtk::{gdk, glib, prelude::*};
+
+use super::Uniform;
+
+/// First OpenGL version with double-precision uniforms in core.
+const FP64_VERSION: (i32, i32) = (4, 0);
+
+/// The element type of a uniform array.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, glib::Enum)]
+#[enum_type(name = "GtkGlShadersUniformType")]
+pub enum UniformType {
+    /// `float`
+    Float,
+    /// `vec2`
+    Vec2,
+    /// `vec3`
+    Vec3,
+    /// `vec4`
+    Vec4,
+    /// `int`
+    Int,
+    /// `ivec2`
+    IVec2,
+    /// `ivec3`
+    IVec3,
+    /// `ivec4`
+    IVec4,
+}
+
+impl UniformType {
+    /// Returns the number of components of an element.
+    pub fn components(self) -> usize {
+        match self {
+            Self::Float | Self::Int => 1,
+            Self::Vec2 | Self::IVec2 => 2,
+            Self::Vec3 | Self::IVec3 => 3,
+            Self::Vec4 | Self::IVec4 => 4,
+        }
+    }
+
+    /// Returns whether the components are integers.
+    pub fn is_int(self) -> bool {
+        matches!(self, Self::Int | Self::IVec2 | Self::IVec3 | Self::IVec4)
+    }
+}
+
+impl Uniform {
+    /// Builds a `float` or `vecN` array from packed components.
+    ///
+    /// Returns `None` if `components` isn't between 1 and 4, or if `values`
+    /// is empty or doesn't hold a whole number of elements.
+    pub fn float_array(components: usize, values: &[f32]) -> Option<Self> {
+        if values.is_empty()
+            || !(1..=4).contains(&components)
+            || !values.len().is_multiple_of(components)
+        {
+            return None;
+        }
+        Some(match components {
+            1 => Self::FloatArray(values.to_vec()),
+            2 => Self::Vec2Array(chunks(values)),
+            3 => Self::Vec3Array(chunks(values)),
+            _ => Self::Vec4Array(chunks(values)),
+        })
+    }
+
+    /// Builds an `int` or `ivecN` array from packed components.
+    ///
+    /// Returns `None` if `components` isn't between 1 and 4, or if `values`
+    /// is empty or doesn't hold a whole number of elements.
+    pub fn int_array(components: usize, values: &[i32]) -> Option<Self> {
+        if values.is_empty()
+            || !(1..=4).contains(&components)
+            || !values.len().is_multiple_of(components)
+        {
+            return None;
+        }
+        Some(match components {
+            1 => Self::IntArray(values.to_vec()),
+            2 => Self::IVec2Array(chunks(values)),
+            3 => Self::IVec3Array(chunks(values)),
+            _ => Self::IVec4Array(chunks(values)),
+        })
+    }
+
+    /// Builds a `uint` or `uvecN` array from packed components.
+    ///
+    /// Returns `None` if `components` isn't between 1 and 4, or if `values`
+    /// is empty or doesn't hold a whole number of elements.
+    pub fn uint_array(components: usize, values: &[u32]) -> Option<Self> {
+        if values.is_empty()
+            || !(1..=4).contains(&components)
+            || !values.len().is_multiple_of(components)
+        {
+            return None;
+        }
+        Some(match components {
+            1 => Self::UIntArray(values.to_vec()),
+            2 => Self::UVec2Array(chunks(values)),
+            3 => Self::UVec3Array(chunks(values)),
+            _ => Self::UVec4Array(chunks(values)),
+        })
+    }
+
+    /// Builds a `double` or `dvecN` array from packed components.
+    ///
+    /// Returns `None` if `components` isn't between 1 and 4, or if `values`
+    /// is empty or doesn't hold a whole number of elements.
+    pub fn double_array(components: usize, values: &[f64]) -> Option<Self> {
+        if values.is_empty()
+            || !(1..=4).contains(&components)
+            || !values.len().is_multiple_of(components)
+        {
+            return None;
+        }
+        Some(match components {
+            1 => Self::DoubleArray(values.to_vec()),
+            2 => Self::DVec2Array(chunks(values)),
+            3 => Self::DVec3Array(chunks(values)),
+            _ => Self::DVec4Array(chunks(values)),
+        })
+    }
+
+    /// Builds an array of `ty` elements from bytes holding their components
+    /// as native-endian 32-bit floats or integers.
+    ///
+    /// Returns `None` if the bytes are empty or don't hold a whole number of
+    /// elements.
+    pub fn array_from_bytes(ty: UniformType, data: &[u8]) -> Option<Self> {
+        if !data.len().is_multiple_of(4) {
+            return None;
+        }
+        let words = data.chunks_exact(4).map(|b| [b[0], b[1], b[2], b[3]]);
+        if ty.is_int() {
+            let values: Vec<i32> = words.map(i32::from_ne_bytes).collect();
+            Self::int_array(ty.components(), &values)
+        } else {
+            let values: Vec<f32> = words.map(f32::from_ne_bytes).collect();
+            Self::float_array(ty.components(), &values)
+        }
+    }
+
+    /// Returns the number of elements of an array, or `None` for a single
+    /// value.
+    pub fn array_len(&self) -> Option<usize> {
+        match self {
+            Self::FloatArray(v) => Some(v.len()),
+            Self::Vec2Array(v) => Some(v.len()),
+            Self::Vec3Array(v) => Some(v.len()),
+            Self::Vec4Array(v) => Some(v.len()),
+            Self::IntArray(v) => Some(v.len()),
+            Self::IVec2Array(v) => Some(v.len()),
+            Self::IVec3Array(v) => Some(v.len()),
+            Self::IVec4Array(v) => Some(v.len()),
+            Self::UIntArray(v) => Some(v.len()),
+            Self::UVec2Array(v) => Some(v.len()),
+            Self::UVec3Array(v) => Some(v.len()),
+            Self::UVec4Array(v) => Some(v.len()),
+            Self::DoubleArray(v) => Some(v.len()),
+            Self::DVec2Array(v) => Some(v.len()),
+            Self::DVec3Array(v) => Some(v.len()),
+            Self::DVec4Array(v) => Some(v.len()),
+            _ => None,
+        }
+    }
+}
+
+/// Groups packed components into elements of `N` components.
+fn chunks<T: Copy + Default, const N: usize>(values: &[T]) -> Vec<[T; N]> {
+    values
+        .chunks_exact(N)
+        .map(|chunk| {
+            let mut element = [T::default(); N];
+            element.copy_from_slice(chunk);
+            element
+        })
+        .collect()
+}
+
+/// Returns whether the current context supports double-precision uniforms.
+///
+/// # Safety
+///
+/// This function calls unsafe OpenGL functions. An active desktop OpenGL
+/// context must be bound before calling.
+pub unsafe fn fp64_supported(context: &gdk::GLContext) -> bool {
+    if context.version() >= FP64_VERSION {
+        return true;
+    }
+    unsafe {
+        let mut count = 0;
+        epoxy::GetIntegerv(epoxy::NUM_EXTENSIONS, &raw mut count);
+        (0..count.max(0) as u32).any(|i| {
+            let name = epoxy::GetStringi(epoxy::EXTENSIONS, i);
+            !name.is_null() && CStr::from_ptr(name.cast()).to_bytes() == b"GL_ARB_gpu_shader_fp64"
+        })
+    }
+}
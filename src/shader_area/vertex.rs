@@ -0,0 +1,61 @@
+//! Custom vertex shaders.
+//!
+//! By default the widget draws a fullscreen quad with its own vertex shader.
+//! A custom vertex shader instead generates procedural geometry from
+//! `gl_VertexID`, in the style of vertexshaderart: the widget draws
+//! `vertex-count` vertices as the chosen [`Primitive`], without any vertex
+//! attributes, and the shader computes each position from `vertexId` and
+//! `vertexCount`.
+
+use gtk::glib;
+
+use super::imp::append_user_source;
+
+/// The primitive type vertices are assembled into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, glib::Enum)]
+#[enum_type(name = "GtkGlShadersPrimitive")]
+pub enum Primitive {
+    /// Separate points, sized by `gl_PointSize`
+    Points,
+    /// Separate lines, from pairs of vertices
+    Lines,
+    /// Connected lines
+    LineStrip,
+    /// A closed loop of connected lines
+    LineLoop,
+    /// Separate triangles, from triples of vertices
+    Triangles,
+    /// Connected triangles, as used by the fullscreen quad
+    #[default]
+    TriangleStrip,
+    /// Triangles sharing the first vertex
+    TriangleFan,
+}
+
+impl Primitive {
+    /// Returns the OpenGL primitive mode.
+    pub fn gl(self) -> u32 {
+        match self {
+            Self::Points => epoxy::POINTS,
+            Self::Lines => epoxy::LINES,
+            Self::LineStrip => epoxy::LINE_STRIP,
+            Self::LineLoop => epoxy::LINE_LOOP,
+            Self::Triangles => epoxy::TRIANGLES,
+            Self::TriangleStrip => epoxy::TRIANGLE_STRIP,
+            Self::TriangleFan => epoxy::TRIANGLE_FAN,
+        }
+    }
+}
+
+/// Adds the `vertexId` and `vertexCount` inputs to a custom vertex shader.
+///
+/// `vertexCount` is a builtin uniform filled by the widget, while
+/// `vertexId` is `gl_VertexID` as a `float`.
+pub fn wrap(source: &str) -> String {
+    let mut shader = String::from(
+        "#define vertexId float(gl_VertexID)\n\
+         uniform float vertexCount;\n",
+    );
+    append_user_source(&mut shader, source);
+    shader
+}
@@ -0,0 +1,159 @@
+//! Pan/zoom/rotate view transform for the view builtin uniforms.
+//!
+//! The transform maps the `uv` interpolant to view coordinates. It is the
+//! identity when reset, and rotations are corrected for the aspect ratio of
+//! the widget so that they don't skew the image.
+
+use std::f64::consts::TAU;
+
+/// A view transform driven by the view controls.
+#[derive(Debug, Clone, Copy)]
+pub struct ViewTransform {
+    /// View coordinates shown at the center of the widget
+    pub center: [f64; 2],
+    /// Zoom factor, 1 showing the whole `[0, 1]` range
+    pub zoom: f64,
+    /// Rotation in radians, counter-clockwise
+    pub rotation: f64,
+}
+
+impl Default for ViewTransform {
+    fn default() -> Self {
+        Self {
+            center: [0.5, 0.5],
+            zoom: 1.0,
+            rotation: 0.0,
+        }
+    }
+}
+
+impl ViewTransform {
+    /// Maps a uv offset from the center of the widget to a view offset.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The offset in uv space
+    /// * `aspect` - Width of the widget divided by its height
+    fn map_offset(&self, [x, y]: [f64; 2], aspect: f64) -> [f64; 2] {
+        let (sin, cos) = self.rotation.sin_cos();
+        [
+            (cos * x - sin * y / aspect) / self.zoom,
+            (sin * aspect * x + cos * y) / self.zoom,
+        ]
+    }
+
+    /// Maps a point in uv space to view coordinates.
+    fn map_point(&self, [u, v]: [f64; 2], aspect: f64) -> [f64; 2] {
+        let [x, y] = self.map_offset([u - 0.5, v - 0.5], aspect);
+        [self.center[0] + x, self.center[1] + y]
+    }
+
+    /// Moves the center so that `anchor` maps to `target` again.
+    fn keep_anchor(&mut self, anchor: [f64; 2], target: [f64; 2], aspect: f64) {
+        let [x, y] = self.map_offset([anchor[0] - 0.5, anchor[1] - 0.5], aspect);
+        self.center = [target[0] - x, target[1] - y];
+    }
+
+    /// Pans the view so that the content follows a pointer moving by
+    /// `delta` in uv space.
+    pub fn pan(&mut self, delta: [f64; 2], aspect: f64) {
+        let [x, y] = self.map_offset(delta, aspect);
+        self.center = [self.center[0] - x, self.center[1] - y];
+    }
+
+    /// Sets the zoom factor, keeping the point under `anchor` in place.
+    ///
+    /// # Arguments
+    ///
+    /// * `zoom` - The new zoom factor, clamped to `limits`
+    /// * `anchor` - The fixed point in uv space
+    /// * `limits` - The minimum and maximum zoom factors
+    /// * `aspect` - Width of the widget divided by its height
+    pub fn zoom_at(&mut self, zoom: f64, anchor: [f64; 2], limits: [f64; 2], aspect: f64) {
+        let target = self.map_point(anchor, aspect);
+        self.zoom = zoom.clamp(limits[0], limits[1].max(limits[0]));
+        self.keep_anchor(anchor, target, aspect);
+    }
+
+    /// Sets the rotation, keeping the point under `anchor` in place.
+    pub fn rotate_at(&mut self, rotation: f64, anchor: [f64; 2], aspect: f64) {
+        let target = self.map_point(anchor, aspect);
+        self.rotation = rotation.rem_euclid(TAU);
+        self.keep_anchor(anchor, target, aspect);
+    }
+
+    /// Returns the transform as `(center.x, center.y, zoom, rotation)`.
+    pub fn params(&self) -> [f32; 4] {
+        [
+            self.center[0] as f32,
+            self.center[1] as f32,
+            self.zoom as f32,
+            self.rotation as f32,
+        ]
+    }
+
+    /// Returns the transform as a column-major 3×3 matrix mapping
+    /// `vec3(uv, 1.0)` to view coordinates.
+    pub fn matrix(&self, aspect: f64) -> [f32; 9] {
+        let [a, b] = self.map_offset([1.0, 0.0], aspect);
+        let [c, d] = self.map_offset([0.0, 1.0], aspect);
+        let [tx, ty] = self.map_point([0.0, 0.0], aspect);
+        [
+            a as f32, b as f32, 0.0, //
+            c as f32, d as f32, 0.0, //
+            tx as f32, ty as f32, 1.0,
+        ]
+    }
+}
+
+/// Velocity of the view after a drag, used for inertia.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Inertia {
+    /// Velocity in uv units per second
+    pub velocity: [f64; 2],
+    /// Time of the last update, in microseconds
+    pub last: Option<i64>,
+}
+
+impl Inertia {
+    /// Fraction of the velocity kept after one second.
+    const FRICTION: f64 = 0.02;
+
+    /// Speed under which the motion stops, in uv units per second.
+    const MIN_SPEED: f64 = 0.01;
+
+    /// Records a drag motion to estimate the release velocity.
+    ///
+    /// # Arguments
+    ///
+    /// * `delta` - The motion since the previous update, in uv space
+    /// * `now` - The current time in microseconds
+    pub fn track(&mut self, delta: [f64; 2], now: i64) {
+        if let Some(last) = self.last {
+            let dt = (now - last) as f64 / 1e6;
+            if dt > 0.0 {
+                // Smooth out jittery event timings
+                self.velocity = [
+                    0.5 * self.velocity[0] + 0.5 * delta[0] / dt,
+                    0.5 * self.velocity[1] + 0.5 * delta[1] / dt,
+                ];
+            }
+        }
+        self.last = Some(now);
+    }
+
+    /// Advances the motion to `now`.
+    ///
+    /// # Returns
+    ///
+    /// The uv delta to pan by, or `None` once the motion has stopped.
+    pub fn step(&mut self, now: i64) -> Option<[f64; 2]> {
+        let dt = (now - self.last.replace(now)?) as f64 / 1e6;
+        let delta = [self.velocity[0] * dt, self.velocity[1] * dt];
+
+        let decay = Self::FRICTION.powf(dt);
+        self.velocity = [self.velocity[0] * decay, self.velocity[1] * decay];
+
+        (self.velocity[0].hypot(self.velocity[1]) >= Self::MIN_SPEED).then_some(delta)
+    }
+}
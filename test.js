@@ -4,7 +4,7 @@
  *
  * This script demonstrates the usage of the ShaderArea widget with
  * animated uniforms. It creates a window displaying a texture with
- * a rainbow color cycling effect.
+ * a rainbow color cycling effect, driven by the builtin `time` uniform.
  */
 
 import GLib from "gi://GLib";
@@ -26,6 +26,8 @@ function makeUniforms(obj) {
             entries[key] = new GLib.Variant('ad', value);
         } else if (typeof value === "number") {
             entries[key] = GLib.Variant.new_double(value);
+        } else if (typeof value === "boolean") {
+            entries[key] = GLib.Variant.new_boolean(value);
         } else if (value instanceof GLib.Variant) {
             entries[key] = value;
         } else {
@@ -40,6 +42,7 @@ const SHADER_SOURCE = `
   in vec2 uv;
   uniform sampler2D tex0;
   uniform float time;
+  uniform float speed;
   out vec4 out_color;
 
   // Convert HSV to RGB
@@ -53,7 +56,7 @@ const SHADER_SOURCE = `
     vec4 texColor = texture(tex0, uv);
 
     // Cycle through rainbow colors (HSV with full saturation and value)
-    float hue = fract(time * 0.1);  // Slow color cycle
+    float hue = fract(time * speed);
     vec3 rainbow = hsv2rgb(vec3(hue, 1.0, 1.0));
 
     // Blend texture with rainbow color
@@ -72,37 +75,16 @@ const window = new Gtk.Window({
 let shaderArea = GtkGlShaders.ShaderArea.new(
     SHADER_SOURCE,
     ["./assets/img.jpg"],
-    makeUniforms({ time: 0.0 })
+    makeUniforms({ speed: 0.1 })  // Slow color cycle
 );
 
-window.set_child(shaderArea);
-
-// Animation state
-let startTime = GLib.get_monotonic_time() / 1000000.0;
-let timeoutSource = null;
-
-/**
- * Animation callback that updates the time uniform.
- * @returns {boolean} GLib.SOURCE_CONTINUE to keep the timeout running
- */
-function updateUniform() {
-    const currentTime = GLib.get_monotonic_time() / 1000000.0;
-    const elapsedTime = currentTime - startTime;
+// Render on every frame; `time` is filled in by the widget
+shaderArea.animate = true;
 
-    // Update time uniform
-    shaderArea.set_uniform_float("time", elapsedTime);
-
-    // Continue animation
-    return GLib.SOURCE_CONTINUE;
-}
+window.set_child(shaderArea);
 
 // Handle window close
 window.connect("close-request", () => {
-    // Clean up the animation timeout
-    if (timeoutSource) {
-        GLib.source_remove(timeoutSource);
-        timeoutSource = null;
-    }
     shaderArea = null;
 
     // Exit the main loop
@@ -110,9 +92,6 @@ window.connect("close-request", () => {
     return false;
 });
 
-// Set up the animation loop (10ms interval = ~100 FPS)
-timeoutSource = GLib.timeout_add(GLib.PRIORITY_DEFAULT, 10, updateUniform);
-
 // Create and run the main loop
 const loop = GLib.MainLoop.new(null, false);
 